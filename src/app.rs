@@ -1,47 +1,127 @@
-use crate::config::Config;
-use crate::midi::{MidiListener, MidiMessage, MidiOutput};
-use crate::pipewire_control::PipeWireController;
-use crate::spectrum::SpectrumAnalyzer;
-use crate::ui::UiState;
+use crate::config::{cc_label, Config, Selector, BANK_B};
+use crate::control_socket::{ControlCommand, ControlRequest, ControlSocket, ControlTarget};
+use crate::engine::{AudioBackend, FaderIndex, TargetKind, VolumeEngine, VolumeReadMode};
+use crate::log_file::LogFile;
+use crate::midi::{MidiListener, MidiMessage, MidiOutput, MidiThru, PortSelection};
+use crate::pipewire_control::{self, PipeWireController};
+use crate::spectrum::{FreqScale, SpectrumAnalyzer};
+use crate::ui::{FaderAction, ImportRow, UiState};
 use log::info;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-const MIDI_TO_PERCENT_FACTOR: f32 = 100.0 / 127.0;
+/// No tray backend is built in yet, so tray-dependent options are inert
+const TRAY_FUNCTIONAL: bool = false;
+
+/// Interval between volume steps while fading a fader out
+const RAMP_STEP_MS: u32 = 50;
+
+/// A mute button press this soon after the last toggle is treated as contact bounce
+const MUTE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Number of recent samples the logged volume latency averages over
+const LATENCY_WINDOW: usize = 32;
+
+/// Availability checks an app fader must find no streams in a row before it greys
+/// out, so streams briefly gone while rerouting don't make it flicker
+const APP_MISSES_BEFORE_UNAVAILABLE: u32 = 2;
+
+/// Weight of each new external volume read in the smoothed fader position
+const EXTERNAL_SMOOTHING: f32 = 0.5;
+
+/// External volume changes larger than this many percent are shown at once
+/// rather than smoothed, as they are real moves and not read jitter
+const EXTERNAL_SNAP_PERCENT: f32 = 3.0;
+
+/// A fader this app set more recently than this is not synced from reads, which
+/// may still report the volume from before the change
+const EXTERNAL_SYNC_QUIET: Duration = Duration::from_secs(1);
 
 pub struct MidiVolumeApp {
     ui_state: UiState,
-    midi_rx: mpsc::Receiver<MidiMessage>,
-    _midi_listener: MidiListener,
-    midi_output: MidiOutput,                  // MIDI output for LED feedback
-    pipewire: Arc<Mutex<PipeWireController>>, // Wrapped in Arc<Mutex> for thread-safe access
-    cc_mapping: HashMap<u8, String>,          // Maps CC number to audio target name
-    cc_types: HashMap<u8, bool>,              // Maps CC to is_sink (true=sink, false=app)
-    last_volume_values: HashMap<u8, u8>,      // Cache last sent volume for each CC
-    last_volume_time: HashMap<u8, Instant>,   // Track last volume change time
-    cc_to_sink_index: HashMap<u8, usize>,     // Maps CC to sink UI index
-    cc_to_app_index: HashMap<u8, usize>,      // Maps CC to app UI index
-    mute_button_mapping: HashMap<u8, u8>,     // Maps mute button CC to target fader CC
-    debounce_ms: u32,                         // Cached debounce value
-    logging_enabled: bool,                    // Cached logging flag
-    last_availability_check: Instant,         // Track last availability check time
+    midi_rx: mpsc::Receiver<(MidiMessage, Instant)>,
+    midi_listener: MidiListener,
+    midi_output: MidiOutput,                // MIDI output for LED feedback
+    thru_ccs: Arc<RwLock<HashSet<u8>>>,     // CCs kept from the MIDI thru port
+    port_selection: PortSelection,          // Device port chosen at startup, for reconnecting
+    midi_thru: bool,                        // Thru port opened at startup
+    led_feedback: bool,                     // MIDI output opened at startup
+    startup_ignore: Duration,               // MIDI discarded after (re)connecting
+    _control_socket: Option<ControlSocket>, // Scripting socket, when [control] is enabled
+    control_rx: Option<mpsc::Receiver<ControlRequest>>,
+    dry_run_rx: Option<mpsc::Receiver<String>>, // --dry-run: commands the backend skipped
+    identify_tx: mpsc::Sender<String>, // Identify threads report their outcome for the console
+    identify_rx: mpsc::Receiver<String>,
+    engine: VolumeEngine, // Fader CC to volume dispatch and debounce
+    cc_to_sink_index: HashMap<u8, usize>, // Maps CC to sink UI index
+    cc_to_app_index: HashMap<u8, usize>, // Maps CC to app UI index
+    mute_button_mapping: HashMap<u8, u8>, // Maps mute button CC to target fader CC
+    solo_button_mapping: HashMap<u8, u8>, // Maps solo button CC to target app fader CC
+    solo: Option<(u8, Vec<u8>)>, // Soloed app CC and the app CCs its solo muted
+    momentary_mute_buttons: HashSet<u8>, // Mute buttons that mute only while held
+    labeled_ccs: HashSet<u8>, // CCs whose mapping sets an explicit label
+    mute_presses: HashMap<u8, Instant>, // Press time of mute buttons still held down
+    last_mute_toggle: HashMap<u8, Instant>, // Last toggle by each mute button, for debouncing
+    app_misses: HashMap<u8, u32>, // Consecutive availability checks without streams, by app CC
+    fade_flags: HashMap<u8, Arc<AtomicBool>>, // Cancel flags for running fade-outs by fader CC
+    led_inverted: bool,   // Mute LEDs show active faders instead of muted ones
+    mute_hold_ms: u32,    // Hold time before a mute press fades instead
+    mute_fade_ms: u32,    // Duration of the fade-to-zero ramp
+    focus_fader_cc: Option<u8>, // Fader bound to the most recently started app
+    selector: Option<Selector>, // Knob that switches a fader between several apps
+    selector_index: usize, // App the selector fader currently drives
+    duck_button_cc: Option<u8>, // Button that ducks the other sinks while held
+    spectrum_cycle_cc: Option<u8>, // Button that steps the spectrum through the sinks
+    window_toggle_cc: Option<u8>, // Button that minimizes and restores the window
+    window_toggle_requested: bool, // Set by that button, applied with the egui context
+    duck_keep_cc: Option<u8>, // Sink fader left alone while ducking
+    duck_level: u8,       // Percent ducked sinks drop to
+    bank_button_cc: Option<u8>, // Button that switches faders between bank A and B
+    bank_b: bool,         // Faders drive their b_cc_N mappings
+    ducked: HashMap<u8, u8>, // Sink CC to its percent before ducking, while ducked
+    max_idle_fps: u32,    // Redraw rate when nothing is changing
+    max_fps: u32,         // Redraw rate cap while animating, 0 = unlimited
+    logging_enabled: bool, // Cached logging flag
+    latency_samples: VecDeque<Duration>, // Recent MIDI-to-volume times, when log_latency is on
+    last_latency_log: Instant, // When the average was last logged
+    latency_unlogged: bool, // Samples arrived since the last log line
+    last_availability_check: Instant, // Track last availability check time
     applications_sink_search_interval_secs: u64, // Interval (in seconds) for checking app availability
     spectrum_analyzer: SpectrumAnalyzer,         // Spectrum analyzer for visualizer
     last_window_width: u32,                      // Track previous window width for live resizing
     last_window_height: u32,                     // Track previous window height for live resizing
     last_spectrum_sink_name: String,             // Track spectrum sink name for change detection
+    last_spectrum_stereo: bool, // Track stereo/mono capture mode for change detection
+    last_spectrum_a_weighting: bool, // Track band weighting for change detection
+    last_spectrum_freq_scale: FreqScale, // Track frequency axis for change detection
+    last_mini_mode: bool,       // Track mini mode to resize the viewport on toggle
+    saved_tab: crate::ui::Tab,  // Tab last written to the config as last_tab
+    config_load_failed: bool,   // Running on defaults because the config file didn't load
 }
 
 impl MidiVolumeApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>, config: Config, config_path: String) -> Self {
+    /// `config_error` is why the config file couldn't be loaded, if `config` is the
+    /// built-in default because of it. With `dry_run` volume and mute commands are
+    /// logged to the console instead of run
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        config: Config,
+        config_path: String,
+        config_error: Option<String>,
+        dry_run: bool,
+    ) -> Self {
         let logging_enabled = config.logging.enabled.unwrap_or(true);
         let debounce_ms = config.audio.debounce_ms.unwrap_or(0);
+        let mute_hold_ms = config.audio.mute_hold_ms.unwrap_or(500);
+        let mute_fade_ms = config.audio.mute_fade_ms.unwrap_or(1500);
         let applications_sink_search_interval_secs =
             config.audio.applications_sink_search.unwrap_or(10);
         let show_console = config.ui.show_console.unwrap_or(false);
+        let max_idle_fps = config.ui.max_idle_fps.unwrap_or(10);
         let max_console_lines = config.ui.max_console_lines.unwrap_or(1000);
 
         if logging_enabled {
@@ -53,29 +133,48 @@ impl MidiVolumeApp {
         let app_labels = config.get_app_labels();
         let cc_count = cc_mapping.len();
 
-        // Build mapping of CC to type (bool: true=sink, false=app)
-        let mut cc_types = HashMap::with_capacity(cc_count);
-        for (cc, _) in &sink_labels {
-            cc_types.insert(*cc, true);
-        }
-        for (cc, _) in &app_labels {
-            cc_types.insert(*cc, false);
-        }
+        // CC to type (true = sink, false = app) and CC to UI index lookups
+        let FaderIndex {
+            cc_types,
+            cc_to_sink_index,
+            cc_to_app_index,
+        } = FaderIndex::new(&sink_labels, &app_labels);
 
         if logging_enabled {
             info!("Loaded {} MIDI controls from configuration", cc_count);
             info!("Sink controls:");
             for (cc, target) in &sink_labels {
-                info!("  CC{}: {}", cc, target);
+                info!("  {}: {}", cc_label(*cc), target);
             }
             info!("Application controls:");
             for (cc, app_name) in &app_labels {
-                info!("  CC{}: {}", cc, app_name);
+                info!("  {}: {}", cc_label(*cc), app_name);
             }
         }
 
-        // Start MIDI listener
-        let (listener, rx) = MidiListener::start().expect("Failed to initialize MIDI listener");
+        // Optional thru port for the messages the app doesn't use; the mapped CC
+        // set is filled in once the engine exists
+        let thru_ccs = Arc::new(RwLock::new(HashSet::new()));
+        let (thru, thru_error) = if config.midi.midi_thru.unwrap_or(false) {
+            match MidiThru::new(Arc::clone(&thru_ccs)) {
+                Ok(thru) => (Some(thru), None),
+                Err(e) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+        let thru_enabled = thru.is_some();
+
+        // Start MIDI listener; wake the UI as soon as a message arrives
+        let repaint_ctx = cc.egui_ctx.clone();
+        let port_selection = config.get_port_selection();
+        let startup_ignore =
+            Duration::from_millis(config.midi.startup_ignore_ms.unwrap_or(0) as u64);
+        let (listener, rx) =
+            MidiListener::start(port_selection.clone(), thru, startup_ignore, move || {
+                repaint_ctx.request_repaint()
+            })
+            .expect("Failed to initialize MIDI listener");
 
         // Initialize PipeWire controller with config mode
         let use_api = config.audio.volume_control_mode.as_deref() == Some("pipewire-api");
@@ -90,26 +189,33 @@ impl MidiVolumeApp {
             .default_sink
             .clone()
             .unwrap_or_else(|| "master_sink".to_string());
-        let pipewire = Arc::new(Mutex::new(PipeWireController::new(use_api, &default_sink)));
-
-        // Build CC to UI index mappings for fast lookup
-        let mut cc_to_sink_index = HashMap::with_capacity(sink_labels.len());
-        for (i, (cc, _)) in sink_labels.iter().enumerate() {
-            cc_to_sink_index.insert(*cc, i);
-        }
-
-        let mut cc_to_app_index = HashMap::with_capacity(app_labels.len());
-        for (i, (cc, _)) in app_labels.iter().enumerate() {
-            cc_to_app_index.insert(*cc, i);
-        }
+        let mut controller = PipeWireController::new(use_api, &default_sink);
+        controller.set_read_mode(VolumeReadMode::parse(
+            config
+                .audio
+                .volume_read_mode
+                .as_deref()
+                .unwrap_or("percent"),
+        ));
+        controller.set_app_wpctl(config.audio.volume_control_mode.as_deref() == Some("wpctl"));
+        controller.set_default_fallbacks(config.get_default_fallbacks());
+        let (identify_tx, identify_rx) = mpsc::channel();
+        let dry_run_rx = dry_run.then(|| {
+            let (tx, rx) = mpsc::channel();
+            controller.set_dry_run(tx);
+            rx
+        });
+        let pipewire: Arc<Mutex<dyn AudioBackend>> = Arc::new(Mutex::new(controller));
 
         // Load mute button mappings
         let mute_button_mapping = config.get_mute_button_mappings();
 
-        // Initialize MIDI output for LED feedback
-        let midi_output = match MidiOutput::new() {
-            Ok(output) => output,
-            Err(e) => {
+        // Initialize MIDI output for LED feedback; input-only setups turn it off
+        let led_feedback = config.midi.led_feedback.unwrap_or(true);
+        let midi_output = match led_feedback.then(|| MidiOutput::new(&port_selection)) {
+            None => MidiOutput::disabled(),
+            Some(Ok(output)) => output,
+            Some(Err(e)) => {
                 if logging_enabled {
                     info!(
                         "Warning: Could not initialize MIDI output for LED feedback: {}",
@@ -118,20 +224,22 @@ impl MidiVolumeApp {
                 }
                 // Try fallback, but if both fail, panic with clear message
                 panic!(
-                    "Failed to initialize MIDI output: {}. Is the nanoKontrol2 device connected?",
+                    "Failed to initialize MIDI output: {}. Is the nanoKontrol2 device connected? \
+                     Set led_feedback = false under [midi] for input-only devices.",
                     e
                 );
             }
         };
 
-        // Initialize spectrum analyzer
-        let default_sink = config
-            .audio
-            .default_sink
-            .clone()
-            .unwrap_or_else(|| "master_sink".to_string());
-        let mut spectrum_analyzer = SpectrumAnalyzer::new();
-        spectrum_analyzer.start(&default_sink);
+        // Initialize spectrum analyzer; update() starts it while the visualizer is enabled
+        let spectrum_stereo = config.ui.spectrum_stereo_mode.unwrap_or(false);
+        let spectrum_analyzer = SpectrumAnalyzer::new();
+        spectrum_analyzer.set_stereo(spectrum_stereo);
+        let spectrum_a_weighting = config.ui.spectrum_weighting.as_deref() == Some("a");
+        spectrum_analyzer.set_a_weighting(spectrum_a_weighting);
+        let spectrum_freq_scale =
+            FreqScale::parse(config.ui.spectrum_freq_scale.as_deref().unwrap_or("log"));
+        spectrum_analyzer.set_freq_scale(spectrum_freq_scale);
 
         let mut app = MidiVolumeApp {
             ui_state: UiState::new(
@@ -141,24 +249,63 @@ impl MidiVolumeApp {
                 max_console_lines,
                 false, // enable_tray
                 false, // close_to_tray
-                false, // start_minimized
+                config.ui.start_minimized.unwrap_or(false),
                 config_path,
                 &config,
             ),
             midi_rx: rx,
-            _midi_listener: listener,
+            midi_listener: listener,
+            port_selection,
+            midi_thru: thru_enabled,
+            led_feedback,
+            startup_ignore,
             midi_output,
-            pipewire: pipewire.clone(),
-            cc_mapping,
-            cc_types,
-            last_volume_values: HashMap::with_capacity(cc_count),
-            last_volume_time: HashMap::with_capacity(cc_count),
+            thru_ccs,
+            _control_socket: None,
+            control_rx: None,
+            dry_run_rx,
+            identify_tx,
+            identify_rx,
+            engine: VolumeEngine::new(
+                pipewire.clone(),
+                cc_mapping,
+                cc_types,
+                config.get_fader_settings(),
+                debounce_ms,
+            ),
             cc_to_sink_index,
             cc_to_app_index,
             mute_button_mapping,
-            debounce_ms,
+            solo_button_mapping: config.get_solo_button_mappings(),
+            solo: None,
+            momentary_mute_buttons: config.get_momentary_mute_buttons(),
+            labeled_ccs: config.get_labeled_ccs(),
+            mute_presses: HashMap::new(),
+            last_mute_toggle: HashMap::new(),
+            app_misses: HashMap::new(),
+            fade_flags: HashMap::new(),
+            led_inverted: config.midi.led_inverted.unwrap_or(false),
+            mute_hold_ms,
+            mute_fade_ms,
+            focus_fader_cc: config.get_focus_fader_cc(),
+            selector: config.get_selector(),
+            selector_index: 0,
+            duck_button_cc: config.audio.duck_button_cc,
+            spectrum_cycle_cc: config.ui.spectrum_cycle_cc,
+            window_toggle_cc: config.ui.window_toggle_cc,
+            window_toggle_requested: false,
+            duck_keep_cc: config.audio.duck_keep_cc,
+            duck_level: config.audio.duck_level.unwrap_or(20).min(100),
+            ducked: HashMap::new(),
+            bank_button_cc: config.midi.bank_button_cc,
+            bank_b: false,
+            max_idle_fps,
+            max_fps: config.ui.max_fps.unwrap_or(0),
             logging_enabled,
-            last_availability_check: Instant::now(),
+            latency_samples: VecDeque::with_capacity(LATENCY_WINDOW),
+            last_latency_log: Instant::now(),
+            latency_unlogged: false,
+            last_availability_check: rescan_due(applications_sink_search_interval_secs),
             applications_sink_search_interval_secs,
             spectrum_analyzer,
             last_window_width: config.ui.window_width.unwrap_or(1000),
@@ -168,24 +315,55 @@ impl MidiVolumeApp {
                 .spectrum_sink_name
                 .clone()
                 .unwrap_or_else(|| "master_sink".to_string()),
+            last_spectrum_stereo: spectrum_stereo,
+            last_spectrum_a_weighting: spectrum_a_weighting,
+            last_spectrum_freq_scale: spectrum_freq_scale,
+            last_mini_mode: false,
+            saved_tab: crate::ui::Tab::parse(config.ui.last_tab.as_deref().unwrap_or("control")),
+            config_load_failed: config_error.is_some(),
         };
 
-        // Initialize UI fader values for sink controls
-        for (i, (cc, target)) in sink_labels.iter().enumerate() {
-            let current_volume = pipewire.lock().unwrap().get_volume_for_sink(target);
-
-            // Set UI fader to current volume (0-127 range)
-            app.ui_state.system_fader_values[i] = ((current_volume as f32 / 100.0) * 127.0) as u8;
-            app.last_volume_values.insert(*cc, current_volume);
+        // Offer a choice in Settings when several devices are connected
+        app.ui_state.midi_ports = crate::midi::matching_input_ports();
+        app.update_thru_ccs();
+        app.query_server_info();
+        app.engine.log_latency = config.logging.log_latency.unwrap_or(false);
+        app.engine.zero_guard_ms = config.midi.zero_guard_ms.unwrap_or(0);
+        app.set_bank(false);
+
+        // Open the log file first so it gets the startup messages too
+        if let Some(path) = config.get_log_file_path() {
+            match LogFile::open(&path) {
+                Ok(log_file) => {
+                    app.ui_state.log_file = Some(log_file);
+                }
+                Err(e) => app
+                    .ui_state
+                    .add_console_message(format!("⚠ Log file not opened: {:#}", e)),
+            }
         }
 
-        // Initialize UI fader values for application controls
-        for (i, (cc, app_name)) in app_labels.iter().enumerate() {
-            let current_volume = pipewire.lock().unwrap().get_volume_for_app(app_name);
-
-            // Set UI fader to current volume (0-127 range)
-            app.ui_state.app_fader_values[i] = ((current_volume as f32 / 100.0) * 127.0) as u8;
-            app.last_volume_values.insert(*cc, current_volume);
+        let control_socket = config.get_control_socket_path().map(|path| {
+            let repaint_ctx = cc.egui_ctx.clone();
+            ControlSocket::start(&path, move || repaint_ctx.request_repaint())
+        });
+
+        // Initialize UI fader values from the current volumes
+        app.read_all_volumes();
+        app.apply_startup_levels(&config.get_startup_levels());
+
+        // Running on defaults hides the user's mappings, so say why even with logging off
+        if let Some(ref error) = config_error {
+            app.ui_state.add_console_message(format!(
+                "❌ Config could not be loaded, using defaults: {}",
+                error
+            ));
+        }
+        app.ui_state.config_error = config_error;
+        if app.dry_run_rx.is_some() {
+            app.ui_state.add_console_message(
+                "🧪 Dry run: volume and mute commands are logged, not run".to_string(),
+            );
         }
 
         // Only show console messages if logging is enabled
@@ -196,119 +374,780 @@ impl MidiVolumeApp {
                 .add_console_message("MIDI Volume Controller Started".to_string());
             app.ui_state
                 .add_console_message("Listening for nanoKontrol2 MIDI input...".to_string());
+            if app.ui_state.cfg_log_device_info {
+                // Everything midir sees, to diagnose a device that isn't matched
+                let inputs = crate::midi::list_input_ports();
+                let outputs = crate::midi::list_output_ports();
+                app.ui_state
+                    .add_console_message(format!("🎹 MIDI input ports ({}):", inputs.len()));
+                for name in inputs {
+                    app.ui_state.add_console_message(format!("  {}", name));
+                }
+                app.ui_state
+                    .add_console_message(format!("🎹 MIDI output ports ({}):", outputs.len()));
+                for name in outputs {
+                    app.ui_state.add_console_message(format!("  {}", name));
+                }
+            }
+            if app.ui_state.midi_ports.len() > 1 {
+                app.ui_state.add_console_message(format!(
+                    "🎛 Found {} nanoKontrol2 ports; pick one in Settings > MIDI Controls",
+                    app.ui_state.midi_ports.len()
+                ));
+            }
             app.ui_state.add_console_message(String::new());
             app.ui_state
                 .add_console_message(format!("📝 Loaded {} CC-to-target mappings", cc_count));
+            if let Ok(backend) = app.engine.backend.lock() {
+                app.ui_state.add_console_message(format!(
+                    "🔊 Available sinks: {}",
+                    backend.list_sinks().join(", ")
+                ));
+                app.ui_state.add_console_message(format!(
+                    "🎵 Playing apps: {}",
+                    backend.list_apps().join(", ")
+                ));
+            }
+            if app.ui_state.start_minimized && !TRAY_FUNCTIONAL {
+                app.ui_state.add_console_message(
+                    "ℹ start_minimized ignored: tray support is not available".to_string(),
+                );
+            }
+            for error in config.get_palette().1 {
+                app.ui_state.add_console_message(format!("⚠ {}", error));
+            }
+            for error in config.get_fader_colors().1 {
+                app.ui_state.add_console_message(format!("⚠ {}", error));
+            }
+            for warning in app.ui_state.mute_button_warnings.clone() {
+                app.ui_state.add_console_message(format!("⚠ {}", warning));
+            }
+            if thru_enabled {
+                app.ui_state.add_console_message(format!(
+                    "🎹 Unmapped MIDI is forwarded to virtual port '{}'",
+                    crate::midi::THRU_PORT_NAME
+                ));
+            }
+            if let Some(ref e) = thru_error {
+                app.ui_state
+                    .add_console_message(format!("⚠ MIDI thru not started: {}", e));
+            }
+            match control_socket {
+                Some(Ok((ref socket, _))) => app.ui_state.add_console_message(format!(
+                    "🔌 Control socket listening on {}",
+                    socket.path().display()
+                )),
+                Some(Err(ref e)) => app
+                    .ui_state
+                    .add_console_message(format!("⚠ Control socket not started: {:#}", e)),
+                None => {}
+            }
             app.ui_state.add_console_message(String::new());
             app.ui_state
                 .add_console_message("Waiting for MIDI CC messages...".to_string());
             app.ui_state.add_console_message(SEP.to_string());
         }
 
+        if let Some(Ok((socket, rx))) = control_socket {
+            app._control_socket = Some(socket);
+            app.control_rx = Some(rx);
+        }
+
         app
     }
 
-    fn process_midi_messages(&mut self) {
+    /// Move a fader on screen; `value` is the 0-127 position in UI orientation
+    fn set_ui_fader(&mut self, is_sink: bool, cc: u8, value: u8) {
+        let (index_map, values) = if is_sink {
+            (
+                &self.cc_to_sink_index,
+                &mut self.ui_state.system_fader_values,
+            )
+        } else {
+            (&self.cc_to_app_index, &mut self.ui_state.app_fader_values)
+        };
+        if let Some(slot) = index_map.get(&cc).and_then(|&i| values.get_mut(i)) {
+            *slot = value;
+        }
+    }
+
+    /// Record a fader's volume and show it on screen, converted the same way live
+    /// MIDI input is so the fader doesn't jump on first touch
+    fn sync_fader_volume(&mut self, cc: u8, percent: u8) {
+        self.engine.last_volume_values.insert(cc, percent);
+        self.set_ui_fader(
+            self.engine.is_sink(cc),
+            cc,
+            self.engine.to_midi(cc, percent),
+        );
+    }
+
+    /// Follow a volume changed outside the app (e.g. in pavucontrol). Reads can
+    /// jitter by a percent, so small changes are smoothed across reads; MIDI and
+    /// on-screen moves never pass through here and stay instant
+    fn sync_external_volume(&mut self, cc: u8, percent: u8) {
+        let recently_set = self
+            .engine
+            .last_volume_time
+            .get(&cc)
+            .is_some_and(|time| time.elapsed() < EXTERNAL_SYNC_QUIET);
+        let sink_muted = self
+            .cc_to_sink_index
+            .get(&cc)
+            .is_some_and(|&i| self.ui_state.system_muted.get(i) == Some(&true));
+        if recently_set || sink_muted || self.ducked.contains_key(&cc) || self.ramp_running(cc) {
+            return;
+        }
+        let Some(&shown) = self.engine.last_volume_values.get(&cc) else {
+            self.sync_fader_volume(cc, percent);
+            return;
+        };
+
+        // Start over from the shown level whenever something else moved the fader
+        let smoothed = self
+            .ui_state
+            .volume_smoothing
+            .entry(cc)
+            .or_insert(shown as f32);
+        if smoothed.round() as u8 != shown {
+            *smoothed = shown as f32;
+        }
+        let target = percent as f32;
+        if (target - *smoothed).abs() > EXTERNAL_SNAP_PERCENT {
+            *smoothed = target;
+        } else {
+            *smoothed += EXTERNAL_SMOOTHING * (target - *smoothed);
+        }
+
+        let smoothed = smoothed.round() as u8;
+        if smoothed != shown {
+            self.sync_fader_volume(cc, smoothed);
+        }
+    }
+
+    /// Set every fader from its target's current volume
+    fn read_all_volumes(&mut self) {
+        let ccs: Vec<u8> = self
+            .ui_state
+            .system_fader_labels
+            .iter()
+            .chain(&self.ui_state.app_fader_labels)
+            .map(|(cc, _)| *cc)
+            .collect();
+        for cc in ccs {
+            let Some(target) = self.engine.cc_mapping.get(&cc) else {
+                continue;
+            };
+            // Targets that can't be read keep their fader where it is
+            let volume = match self.engine.backend.lock() {
+                Ok(backend) => backend.get_volume(self.engine.kind(cc), target),
+                Err(_) => continue,
+            };
+            if let Some(volume) = volume {
+                self.sync_fader_volume(cc, volume);
+            }
+        }
+    }
+
+    /// Set faders that have a `startup_level` to it, through the volume workers
+    fn apply_startup_levels(&mut self, levels: &HashMap<u8, u8>) {
+        let mut ccs: Vec<_> = levels.keys().copied().collect();
+        ccs.sort_unstable();
+        for cc in ccs {
+            let settings = self.engine.settings(cc);
+            let percent = levels[&cc].clamp(settings.min, settings.max);
+            self.sync_fader_volume(cc, percent);
+            self.engine.set_volume(cc, percent);
+            if self.logging_enabled {
+                self.ui_state.add_console_message(format!(
+                    "{}: startup level {}%",
+                    cc_label(cc),
+                    percent
+                ));
+            }
+        }
+    }
+
+    /// Start over without restarting: reconnect the MIDI device, drop all cached
+    /// fader state and read every volume again
+    fn reset(&mut self, ctx: &egui::Context) {
+        // The old connection has to be closed before the port can be opened again
+        self.midi_listener.stop();
+        let thru = if self.midi_thru {
+            match MidiThru::new(Arc::clone(&self.thru_ccs)) {
+                Ok(thru) => Some(thru),
+                Err(e) => {
+                    self.ui_state
+                        .add_console_message(format!("⚠ MIDI thru not started: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let repaint_ctx = ctx.clone();
+        match MidiListener::start(
+            self.port_selection.clone(),
+            thru,
+            self.startup_ignore,
+            move || repaint_ctx.request_repaint(),
+        ) {
+            Ok((listener, rx)) => {
+                self.midi_listener = listener;
+                self.midi_rx = rx;
+            }
+            Err(e) => self
+                .ui_state
+                .add_console_message(format!("⚠ MIDI input not reconnected: {}", e)),
+        }
+        if crate::midi::find_input_port(&self.port_selection).is_none() {
+            self.ui_state.add_console_message(
+                "⚠ nanoKontrol2 not found, MIDI stays disconnected".to_string(),
+            );
+        }
+        if self.led_feedback {
+            self.midi_output = MidiOutput::disabled();
+            match MidiOutput::new(&self.port_selection) {
+                Ok(output) => self.midi_output = output,
+                Err(e) => self
+                    .ui_state
+                    .add_console_message(format!("⚠ MIDI output not reconnected: {}", e)),
+            }
+        }
+        self.ui_state.midi_ports = crate::midi::matching_input_ports();
+
+        // Nothing cached from before the reset may hold back the next fader move
+        self.engine.reset_state();
+        self.mute_presses.clear();
+        self.read_all_volumes();
+        // Restore what a held duck button or a solo changed; after the reads, so
+        // those don't pull the faders back to the ducked levels
+        self.end_duck();
+        self.end_solo();
+        self.set_bank(self.bank_b);
+        // Re-read app streams and mute states on the next frame
+        self.last_availability_check = rescan_due(self.applications_sink_search_interval_secs);
+
+        self.ui_state
+            .add_console_message("🔄 Reset: MIDI reconnected, volumes re-read".to_string());
+    }
+
+    /// Refresh the audio server details shown in Settings
+    fn query_server_info(&mut self) {
+        self.ui_state.server_info =
+            Some(PipeWireController::server_info().map_err(|e| e.to_string()));
+    }
+
+    /// Fill the Settings import list with the sinks and apps not mapped yet, each
+    /// suggesting the next free CC (sinks from 0, apps from 16)
+    fn load_import_rows(&mut self) {
+        let mapped: Vec<(u8, String)> = self
+            .ui_state
+            .cfg_sinks
+            .iter()
+            .chain(&self.ui_state.cfg_applications)
+            .map(|(cc, mapping)| (*cc, mapping.target.clone()))
+            .collect();
+        let mut used: HashSet<u8> = mapped.iter().map(|(cc, _)| *cc).collect();
+
+        let rows = PipeWireController::import_candidates()
+            .into_iter()
+            .filter(|candidate| !mapped.iter().any(|(_, target)| *target == candidate.target))
+            .map(|candidate| {
+                let first = if candidate.is_sink { 0 } else { 16 };
+                let cc = (first..BANK_B).find(|cc| !used.contains(cc));
+                used.extend(cc);
+                ImportRow {
+                    candidate,
+                    selected: false,
+                    cc: cc.map(|cc| cc.to_string()).unwrap_or_default(),
+                }
+            })
+            .collect();
+        self.ui_state.import_rows = Some(rows);
+    }
+
+    /// Tell the thru port which CCs the app keeps for itself
+    fn update_thru_ccs(&self) {
+        // Bank B faders arrive on the same hardware CCs as bank A
+        let mut ccs: HashSet<u8> = self
+            .engine
+            .handled_ccs()
+            .into_iter()
+            .map(|cc| cc & !BANK_B)
+            .collect();
+        ccs.extend(self.mute_button_mapping.keys());
+        ccs.extend(self.solo_button_mapping.keys());
+        ccs.extend(self.duck_button_cc);
+        ccs.extend(self.spectrum_cycle_cc);
+        ccs.extend(self.window_toggle_cc);
+        ccs.extend(self.selector.as_ref().map(|selector| selector.knob_cc));
+        ccs.extend(self.bank_button_cc);
+        if let Ok(mut mapped) = self.thru_ccs.write() {
+            *mapped = ccs;
+        }
+    }
+
+    /// Run commands queued by control socket clients
+    fn process_control_requests(&mut self) {
+        let Some(rx) = self.control_rx.take() else {
+            return;
+        };
+        while let Ok(request) = rx.try_recv() {
+            let result = self.run_control_command(&request.command);
+            if self.logging_enabled {
+                self.ui_state.add_console_message(match result {
+                    Ok(()) => format!("🔌 Control: {:?}", request.command),
+                    Err(ref e) => format!("🔌 Control: {:?} failed: {}", request.command, e),
+                });
+            }
+            let _ = request.reply.send(result);
+        }
+        self.control_rx = Some(rx);
+    }
+
+    fn run_control_command(&mut self, command: &ControlCommand) -> Result<(), String> {
+        let cc = self.resolve_control_target(command.target())?;
+        let is_sink = self.engine.is_sink(cc);
+        let index_map = if is_sink {
+            &self.cc_to_sink_index
+        } else {
+            &self.cc_to_app_index
+        };
+        let ui_index = *index_map
+            .get(&cc)
+            .ok_or_else(|| format!("CC{} is not a fader", cc))?;
+
+        match *command {
+            ControlCommand::SetVolume { percent, .. } => {
+                // Only levels the fader itself could reach, within its min, max and trim
+                let range = self.engine.settings(cc).percent_range();
+                if !range.contains(&percent) {
+                    return Err(format!(
+                        "{}% is outside CC{}'s range of {}-{}%",
+                        percent,
+                        cc,
+                        range.start(),
+                        range.end()
+                    ));
+                }
+                // Same path as dragging the on-screen fader
+                self.cancel_volume_ramp(cc);
+                self.sync_fader_volume(cc, percent);
+                self.engine.set_volume(cc, percent);
+            }
+            ControlCommand::SetMute { muted, .. } => {
+                let is_muted = if is_sink {
+                    self.ui_state.system_muted[ui_index]
+                } else {
+                    self.ui_state.app_muted[ui_index]
+                };
+                if muted != is_muted {
+                    self.handle_mute_button(cc, false);
+                }
+            }
+            ControlCommand::ToggleMute(_) => {
+                self.handle_mute_button(cc, false);
+            }
+        }
+        Ok(())
+    }
+
+    // Fader CC a control command refers to; names match a mapping's target or label
+    fn resolve_control_target(&self, target: &ControlTarget) -> Result<u8, String> {
+        let (name, labels) = match target {
+            ControlTarget::Cc(cc) => {
+                return self
+                    .engine
+                    .cc_mapping
+                    .contains_key(cc)
+                    .then_some(*cc)
+                    .ok_or_else(|| format!("CC{} is not mapped", cc));
+            }
+            ControlTarget::Sink(name) => (name, &self.ui_state.system_fader_labels),
+            ControlTarget::App(name) => (name, &self.ui_state.app_fader_labels),
+        };
+        labels
+            .iter()
+            .find(|(cc, label)| {
+                label.eq_ignore_ascii_case(name)
+                    || self
+                        .engine
+                        .cc_mapping
+                        .get(cc)
+                        .is_some_and(|mapped| mapped.eq_ignore_ascii_case(name))
+            })
+            .map(|(cc, _)| *cc)
+            .ok_or_else(|| format!("no fader is mapped to '{}'", name))
+    }
+
+    /// Whether a fader is muted, None if it has no on-screen slot
+    fn fader_muted(&self, cc: u8) -> Option<bool> {
+        let (index_map, muted) = if self.engine.is_sink(cc) {
+            (&self.cc_to_sink_index, &self.ui_state.system_muted)
+        } else {
+            (&self.cc_to_app_index, &self.ui_state.app_muted)
+        };
+        index_map.get(&cc).and_then(|&i| muted.get(i)).copied()
+    }
+
+    // Light or clear the LEDs of all mute buttons on a fader, so several buttons
+    // (e.g. a footswitch and a panel button) stay in sync
+    fn set_mute_leds(&self, fader_cc: u8, muted: bool) {
+        for (&button_cc, &target) in &self.mute_button_mapping {
+            if self.active_cc(target) == fader_cc {
+                self.set_mute_led(button_cc, muted);
+            }
+        }
+    }
+
+    // Mute LEDs light when muted, or when active with `led_inverted`
+    fn set_mute_led(&self, button_cc: u8, muted: bool) {
+        if muted != self.led_inverted {
+            self.midi_output.light_button(button_cc);
+        } else {
+            self.midi_output.unlight_button(button_cc);
+        }
+    }
+
+    /// Set every mute button LED from its fader's current state
+    fn sync_mute_leds(&self) {
+        for (&button_cc, &target) in &self.mute_button_mapping {
+            let muted = self.fader_muted(self.active_cc(target)) == Some(true);
+            self.set_mute_led(button_cc, muted);
+        }
+    }
+
+    /// The fader a hardware CC drives in the active bank: its bank B mapping (or
+    /// that mapping's 14-bit LSB) while bank B is active, otherwise the CC itself
+    fn active_cc(&self, cc: u8) -> u8 {
+        let bank_cc = cc | BANK_B;
+        if self.bank_b && self.engine.handles(bank_cc) {
+            bank_cc
+        } else {
+            cc
+        }
+    }
+
+    /// Switch faders and mute buttons to bank B or back to A. The bank button is
+    /// lit while bank B is active and the mute LEDs follow the new bank's faders
+    fn set_bank(&mut self, bank_b: bool) {
+        self.bank_b = bank_b;
+        self.ui_state.active_bank = self.bank_button_cc.map(|_| if bank_b { 'B' } else { 'A' });
+        if let Some(button_cc) = self.bank_button_cc {
+            if bank_b {
+                self.midi_output.light_button(button_cc);
+            } else {
+                self.midi_output.unlight_button(button_cc);
+            }
+        }
+        self.sync_mute_leds();
+        self.sync_solo_leds();
+    }
+
+    /// Light the solo button of the soloed app, in the active bank
+    fn sync_solo_leds(&self) {
+        let soloed = self.solo.as_ref().map(|(cc, _)| *cc);
+        for (&button_cc, &target) in &self.solo_button_mapping {
+            if soloed == Some(self.active_cc(target)) {
+                self.midi_output.light_button(button_cc);
+            } else {
+                self.midi_output.unlight_button(button_cc);
+            }
+        }
+    }
+
+    /// Solo an app: mute every other unmuted app and remember which, so a second
+    /// press (or soloing another app) unmutes exactly those again
+    fn toggle_app_solo(&mut self, cc: u8) {
+        if self.end_solo() == Some(cc) {
+            return;
+        }
+        let Some(&ui_index) = self.cc_to_app_index.get(&cc) else {
+            return;
+        };
+
+        if self.ui_state.app_muted.get(ui_index) == Some(&true) {
+            self.toggle_app_mute(ui_index, cc, false);
+        }
+        let others: Vec<(u8, usize)> = self
+            .cc_to_app_index
+            .iter()
+            .filter(|&(&other, &i)| other != cc && self.ui_state.app_muted.get(i) == Some(&false))
+            .map(|(&other, &i)| (other, i))
+            .collect();
+        for &(other, i) in &others {
+            self.toggle_app_mute(i, other, false);
+        }
+
+        self.solo = Some((cc, others.into_iter().map(|(other, _)| other).collect()));
+        self.sync_solo_leds();
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message(format!("🎧 Solo {}", cc_label(cc)));
+        }
+    }
+
+    /// Unmute the apps muted by the current solo, if any, and return the soloed CC
+    fn end_solo(&mut self) -> Option<u8> {
+        let (soloed, silenced) = self.solo.take()?;
+        for cc in silenced {
+            if let Some(&i) = self.cc_to_app_index.get(&cc) {
+                if self.ui_state.app_muted.get(i) == Some(&true) {
+                    self.toggle_app_mute(i, cc, false);
+                }
+            }
+        }
+        self.sync_solo_leds();
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message(format!("🎧 Solo {} off", cc_label(soloed)));
+        }
+        Some(soloed)
+    }
+
+    /// Window toggle button: restore and raise the window when it is minimized or
+    /// behind others, otherwise minimize it. Without a tray it stays in the taskbar
+    fn toggle_window(&mut self, ctx: &egui::Context) {
+        let restore = ctx
+            .input(|i| i.viewport().minimized == Some(true) || i.viewport().focused == Some(false));
+        if restore {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+        if self.logging_enabled {
+            let action = if restore { "restored" } else { "minimized" };
+            self.ui_state
+                .add_console_message(format!("🪟 Window {} by button", action));
+        }
+    }
+
+    /// Returns true if any MIDI message was handled this frame
+    fn process_midi_messages(&mut self) -> bool {
+        let mut processed = false;
+
         // Process all pending MIDI messages immediately for instant response
-        while let Ok(msg) = self.midi_rx.try_recv() {
+        while let Ok((msg, received)) = self.midi_rx.try_recv() {
+            processed = true;
             let MidiMessage::ControlChange { cc, value } = msg;
+            self.ui_state
+                .raw_midi_values
+                .insert(self.active_cc(cc), value);
+            if self.ui_state.console_midi_table {
+                self.ui_state.add_midi_event(msg, received);
+            }
             // Log MIDI CC message to console if logging is enabled
             if self.logging_enabled {
-                self.ui_state
-                    .add_console_message(format!("MIDI CC{} -> value: {}", cc, value));
+                self.ui_state.add_midi_console_message(cc, value);
             }
 
-            // Check if this CC is a mute button
-            if let Some(&target_cc) = self.mute_button_mapping.get(&cc) {
-                // Mute button pressed (CC value > 0 means button pressed on nanoKontrol2)
+            if Some(cc) == self.spectrum_cycle_cc {
                 if value > 0 {
-                    self.handle_mute_button(cc, target_cc);
+                    self.cycle_spectrum_source();
                 }
                 continue;
             }
 
-            // Check if this CC is mapped to an audio target (volume fader)
-            if self.cc_mapping.contains_key(&cc) {
-                let percent = ((value as f32) * MIDI_TO_PERCENT_FACTOR) as u8;
+            if Some(cc) == self.window_toggle_cc {
+                if value > 0 {
+                    self.window_toggle_requested = true;
+                }
+                continue;
+            }
 
-                // Debounce: Skip if value hasn't changed or updated too recently
-                let now = Instant::now();
-                let should_update = if let Some(&last_val) = self.last_volume_values.get(&cc) {
-                    if last_val == percent {
-                        false // Same value, skip
-                    } else if let Some(&last_time) = self.last_volume_time.get(&cc) {
-                        now.duration_since(last_time).as_millis() >= self.debounce_ms as u128
-                    } else {
-                        true
-                    }
-                } else {
-                    true // First update
-                };
+            if self.selector.as_ref().map(|selector| selector.knob_cc) == Some(cc) {
+                self.select_target(value);
+                continue;
+            }
 
-                if !should_update {
-                    continue; // Skip this update
+            if Some(cc) == self.duck_button_cc {
+                if value > 0 {
+                    self.start_duck();
+                } else {
+                    self.end_duck();
                 }
+                continue;
+            }
 
-                // Cache the new value and time
-                self.last_volume_values.insert(cc, percent);
-                self.last_volume_time.insert(cc, now);
-
-                // Determine if this is a sink or app control
-                if self.cc_types.get(&cc).copied().unwrap_or(true) {
-                    // Sink control - spawn thread to avoid blocking UI
-                    if let Some(target) = self.cc_mapping.get(&cc) {
-                        let pipewire = self.pipewire.clone();
-                        let target_clone = target.clone();
-                        thread::spawn(move || {
-                            if let Ok(pw) = pipewire.lock() {
-                                let _ = pw.set_volume_for_sink(&target_clone, percent);
-                            }
-                        });
+            if Some(cc) == self.bank_button_cc {
+                if value > 0 {
+                    self.set_bank(!self.bank_b);
+                    if self.logging_enabled {
+                        self.ui_state.add_console_message(format!(
+                            "🔀 Bank {} active",
+                            if self.bank_b { 'B' } else { 'A' }
+                        ));
                     }
+                }
+                continue;
+            }
 
-                    // Update UI fader for this sink using cached index
-                    if let Some(&ui_index) = self.cc_to_sink_index.get(&cc) {
-                        if ui_index < self.ui_state.system_fader_values.len() {
-                            self.ui_state.system_fader_values[ui_index] = value;
-                        }
-                    }
-                } else {
-                    // App control - spawn thread to avoid blocking UI
-                    if let Some(target) = self.cc_mapping.get(&cc) {
-                        let pipewire = self.pipewire.clone();
-                        let target_clone = target.clone();
-                        thread::spawn(move || {
-                            if let Ok(pw) = pipewire.lock() {
-                                let _ = pw.set_volume_for_app(&target_clone, percent);
-                            }
-                        });
+            if let Some(&app_cc) = self.solo_button_mapping.get(&cc) {
+                if value > 0 {
+                    self.toggle_app_solo(self.active_cc(app_cc));
+                }
+                continue;
+            }
+
+            // Check if this CC is a mute button
+            if let Some(&target_cc) = self.mute_button_mapping.get(&cc) {
+                let target_cc = self.active_cc(target_cc);
+                if self.momentary_mute_buttons.contains(&cc) {
+                    // Momentary: muted exactly while held, so only act when the
+                    // state differs from the button (press = muted, release = unmuted)
+                    if self.fader_muted(target_cc) == Some(value == 0) {
+                        self.handle_mute_button(target_cc, false);
                     }
+                    continue;
+                }
 
-                    // Update UI fader for this app using cached index
-                    if let Some(&ui_index) = self.cc_to_app_index.get(&cc) {
-                        if ui_index < self.ui_state.app_fader_values.len() {
-                            self.ui_state.app_fader_values[ui_index] = value;
+                // Mute button pressed (CC value > 0 means button pressed on nanoKontrol2)
+                if value > 0 {
+                    if self.mute_hold_ms == 0 {
+                        // Hold-to-fade disabled: toggle immediately on press
+                        if !self.mute_bounced(cc) {
+                            self.handle_mute_button(target_cc, false);
                         }
+                    } else {
+                        // Defer until release (tap) or hold threshold (fade)
+                        self.mute_presses.entry(cc).or_insert_with(Instant::now);
                     }
+                } else if self.mute_presses.remove(&cc).is_some() && !self.mute_bounced(cc) {
+                    // Released before the hold threshold: instant mute toggle
+                    self.handle_mute_button(target_cc, false);
+                }
+                continue;
+            }
+
+            // Fader CCs go to the volume engine, as the active bank's fader
+            let msg = MidiMessage::ControlChange {
+                cc: self.active_cc(cc),
+                value,
+            };
+            if let Some(change) = self.engine.handle_message(msg, received) {
+                // A fader move takes over from any fade-out in progress, and
+                // from ducking, so releasing the duck button won't undo it
+                self.cancel_volume_ramp(change.cc);
+                self.ducked.remove(&change.cc);
+                self.ui_state
+                    .fader_moved_at
+                    .insert(change.cc, Instant::now());
+
+                self.set_ui_fader(change.is_sink, change.cc, change.value);
+            }
+        }
+
+        // Faders moved together this frame share one worker
+        self.engine.flush();
+
+        processed
+    }
+
+    /// True if this mute button already toggled within `MUTE_DEBOUNCE`; otherwise
+    /// records the toggle about to happen
+    fn mute_bounced(&mut self, button_cc: u8) -> bool {
+        let now = Instant::now();
+        match self.last_mute_toggle.get(&button_cc) {
+            Some(&last) if now.duration_since(last) < MUTE_DEBOUNCE => true,
+            _ => {
+                self.last_mute_toggle.insert(button_cc, now);
+                false
+            }
+        }
+    }
+
+    /// Drop every unmuted sink except the kept one to `duck_level`, remembering
+    /// the levels to restore
+    fn start_duck(&mut self) {
+        if !self.ducked.is_empty() {
+            return;
+        }
+        let sink_ccs: Vec<u8> = self.cc_to_sink_index.keys().copied().collect();
+        for cc in sink_ccs {
+            if Some(cc) == self.duck_keep_cc || self.fader_muted(cc) != Some(false) {
+                continue;
+            }
+            let Some(&percent) = self.engine.last_volume_values.get(&cc) else {
+                continue;
+            };
+            if percent <= self.duck_level {
+                continue;
+            }
+            self.cancel_volume_ramp(cc);
+            self.ducked.insert(cc, percent);
+            self.sync_fader_volume(cc, self.duck_level);
+            self.engine.set_volume(cc, self.duck_level);
+        }
+        if self.logging_enabled && !self.ducked.is_empty() {
+            self.ui_state.add_console_message(format!(
+                "🦆 Ducked {} sink(s) to {}%",
+                self.ducked.len(),
+                self.duck_level
+            ));
+        }
+    }
+
+    /// Put ducked sinks back to where they were
+    fn end_duck(&mut self) {
+        if self.ducked.is_empty() {
+            return;
+        }
+        for (cc, percent) in std::mem::take(&mut self.ducked) {
+            if self.fader_muted(cc) == Some(true) {
+                // Muted while ducked: unmuting should bring back the pre-duck level
+                if let Some(&i) = self.cc_to_sink_index.get(&cc) {
+                    self.ui_state.system_muted_volume[i] = self.engine.to_midi(cc, percent);
                 }
+                continue;
             }
+            self.sync_fader_volume(cc, percent);
+            self.engine.set_volume(cc, percent);
+        }
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message("🦆 Duck released, sink volumes restored".to_string());
         }
     }
 
-    fn handle_mute_button(&mut self, button_cc: u8, target_cc: u8) {
+    fn check_mute_button_holds(&mut self) {
+        if self.mute_presses.is_empty() {
+            return;
+        }
+
+        // Buttons held past the threshold fade out instead of waiting for release
+        let hold = Duration::from_millis(self.mute_hold_ms as u64);
+        let held: Vec<u8> = self
+            .mute_presses
+            .iter()
+            .filter(|(_, pressed_at)| pressed_at.elapsed() >= hold)
+            .map(|(&button_cc, _)| button_cc)
+            .collect();
+
+        for button_cc in held {
+            self.mute_presses.remove(&button_cc);
+            if let Some(&target_cc) = self.mute_button_mapping.get(&button_cc) {
+                self.handle_mute_button(self.active_cc(target_cc), true);
+            }
+        }
+    }
+
+    /// Toggle a fader's mute and the LEDs of every mute button on it
+    fn handle_mute_button(&mut self, target_cc: u8, fade: bool) {
         // Determine if target is a sink or app
-        let is_sink = self.cc_types.get(&target_cc).copied().unwrap_or(true);
+        let is_sink = self.engine.is_sink(target_cc);
 
         if is_sink {
             // Handle sink mute
             if let Some(&ui_index) = self.cc_to_sink_index.get(&target_cc) {
                 if ui_index < self.ui_state.system_muted.len() {
-                    self.toggle_sink_mute(ui_index, target_cc, button_cc);
+                    self.toggle_sink_mute(ui_index, target_cc, fade);
                     if self.logging_enabled {
                         let muted = self.ui_state.system_muted[ui_index];
                         self.ui_state.add_console_message(format!(
-                            "🔇 CC{} {} ",
-                            target_cc,
+                            "🔇 {} {} ",
+                            cc_label(target_cc),
                             if muted { "muted" } else { "unmuted" }
                         ));
                     }
@@ -318,12 +1157,12 @@ impl MidiVolumeApp {
             // Handle app mute
             if let Some(&ui_index) = self.cc_to_app_index.get(&target_cc) {
                 if ui_index < self.ui_state.app_muted.len() {
-                    self.toggle_app_mute(ui_index, target_cc, button_cc);
+                    self.toggle_app_mute(ui_index, target_cc, fade);
                     if self.logging_enabled {
                         let muted = self.ui_state.app_muted[ui_index];
                         self.ui_state.add_console_message(format!(
-                            "🔇 CC{} {}",
-                            target_cc,
+                            "🔇 {} {}",
+                            cc_label(target_cc),
                             if muted { "muted" } else { "unmuted" }
                         ));
                     }
@@ -332,27 +1171,31 @@ impl MidiVolumeApp {
         }
     }
 
-    fn toggle_sink_mute(&mut self, ui_index: usize, cc: u8, button_cc: u8) {
+    fn toggle_sink_mute(&mut self, ui_index: usize, cc: u8, fade: bool) {
         let is_muted = self.ui_state.system_muted[ui_index];
 
         if is_muted {
+            self.cancel_volume_ramp(cc);
+
             // Unmute: restore previous volume
             let previous_volume = self.ui_state.system_muted_volume[ui_index];
             self.ui_state.system_fader_values[ui_index] = previous_volume;
             self.ui_state.system_muted[ui_index] = false;
 
             // Turn off LED on button
-            self.midi_output.unlight_button(button_cc);
+            self.set_mute_leds(cc, false);
 
-            if let Some(target) = self.cc_mapping.get(&cc) {
-                let percent = ((previous_volume as f32) * MIDI_TO_PERCENT_FACTOR) as u8;
-                let pipewire = self.pipewire.clone();
+            if let Some(target) = self.engine.cc_mapping.get(&cc) {
+                let percent = self.engine.to_percent(cc, previous_volume);
+                let pipewire = self.engine.backend.clone();
                 let target_clone = target.clone();
 
                 // Spawn thread to avoid blocking UI
                 thread::spawn(move || {
                     if let Ok(pw) = pipewire.lock() {
-                        let _ = pw.set_volume_for_sink(&target_clone, percent);
+                        // Also clear a mute set outside the app (e.g. pavucontrol)
+                        let _ = pw.set_mute(TargetKind::Sink, &target_clone, false);
+                        let _ = pw.set_volume(TargetKind::Sink, &target_clone, percent);
                     }
                 });
             }
@@ -364,118 +1207,329 @@ impl MidiVolumeApp {
             self.ui_state.system_muted[ui_index] = true;
 
             // Turn on LED on button
-            self.midi_output.light_button(button_cc);
+            self.set_mute_leds(cc, true);
 
-            if let Some(target) = self.cc_mapping.get(&cc) {
-                let pipewire = self.pipewire.clone();
+            if fade && self.mute_fade_ms > 0 {
+                let percent = self.engine.to_percent(cc, current_volume);
+                self.start_volume_ramp(cc, true, percent, false);
+            } else if let Some(target) = self.engine.cc_mapping.get(&cc) {
+                let pipewire = self.engine.backend.clone();
                 let target_clone = target.clone();
 
                 // Spawn thread to avoid blocking UI
                 thread::spawn(move || {
                     if let Ok(pw) = pipewire.lock() {
-                        let _ = pw.set_volume_for_sink(&target_clone, 0);
+                        let _ = pw.set_volume(TargetKind::Sink, &target_clone, 0);
                     }
                 });
             }
         }
     }
 
-    fn toggle_app_mute(&mut self, ui_index: usize, cc: u8, button_cc: u8) {
+    fn toggle_app_mute(&mut self, ui_index: usize, cc: u8, fade: bool) {
         let is_muted = self.ui_state.app_muted[ui_index];
 
+        // Apps use the streams' own mute flag, so the fader keeps its level
+        let percent = self
+            .engine
+            .to_percent(cc, self.ui_state.app_fader_values[ui_index]);
+
         if is_muted {
-            // Unmute: restore previous volume
-            let previous_volume = self.ui_state.app_muted_volume[ui_index];
-            self.ui_state.app_fader_values[ui_index] = previous_volume;
+            self.cancel_volume_ramp(cc);
             self.ui_state.app_muted[ui_index] = false;
 
             // Turn off LED on button
-            self.midi_output.unlight_button(button_cc);
+            self.set_mute_leds(cc, false);
 
-            if let Some(target) = self.cc_mapping.get(&cc) {
-                let percent = ((previous_volume as f32) * MIDI_TO_PERCENT_FACTOR) as u8;
-                let pipewire = self.pipewire.clone();
+            if let Some(target) = self.engine.cc_mapping.get(&cc) {
+                let pipewire = self.engine.backend.clone();
                 let target_clone = target.clone();
 
                 // Spawn thread to avoid blocking UI
                 thread::spawn(move || {
                     if let Ok(pw) = pipewire.lock() {
-                        let _ = pw.set_volume_for_app(&target_clone, percent);
+                        let _ = pw.set_mute(TargetKind::App, &target_clone, false);
+                        // Undo a fade-out that was cut short
+                        let _ = pw.set_volume(TargetKind::App, &target_clone, percent);
                     }
                 });
             }
         } else {
-            // Mute: save current volume and set to 0
-            let current_volume = self.ui_state.app_fader_values[ui_index];
-            self.ui_state.app_muted_volume[ui_index] = current_volume;
-            self.ui_state.app_fader_values[ui_index] = 0;
             self.ui_state.app_muted[ui_index] = true;
 
             // Turn on LED on button
-            self.midi_output.light_button(button_cc);
+            self.set_mute_leds(cc, true);
 
-            if let Some(target) = self.cc_mapping.get(&cc) {
-                let pipewire = self.pipewire.clone();
+            if fade && self.mute_fade_ms > 0 {
+                self.start_volume_ramp(cc, false, percent, true);
+            } else if let Some(target) = self.engine.cc_mapping.get(&cc) {
+                let pipewire = self.engine.backend.clone();
                 let target_clone = target.clone();
 
                 // Spawn thread to avoid blocking UI
                 thread::spawn(move || {
                     if let Ok(pw) = pipewire.lock() {
-                        let _ = pw.set_volume_for_app(&target_clone, 0);
+                        let _ = pw.set_mute(TargetKind::App, &target_clone, true);
                     }
                 });
             }
         }
     }
 
-    fn process_ui_slider_changes(&mut self, changed_faders: Vec<(bool, usize, u8)>) {
-        for (is_sink, ui_index, new_value) in changed_faders {
-            if is_sink {
-                // Handle sink volume change from UI
-                if ui_index < self.ui_state.system_fader_labels.len() {
-                    let cc = self.ui_state.system_fader_labels[ui_index].0;
-                    let percent = ((new_value as f32) * MIDI_TO_PERCENT_FACTOR) as u8;
-
-                    if let Some(target) = self.cc_mapping.get(&cc) {
-                        let pipewire = self.pipewire.clone();
-                        let target_clone = target.clone();
-
-                        // Spawn thread to avoid blocking UI
-                        thread::spawn(move || {
-                            if let Ok(pw) = pipewire.lock() {
-                                let _ = pw.set_volume_for_sink(&target_clone, percent);
-                            }
-                        });
+    /// Fade a fader's target from `from_percent` down to zero over `mute_fade_ms`.
+    /// With `then_mute` the target is muted at the end and its volume put back
+    fn start_volume_ramp(&mut self, cc: u8, is_sink: bool, from_percent: u8, then_mute: bool) {
+        self.cancel_volume_ramp(cc);
+
+        if let Some(target) = self.engine.cc_mapping.get(&cc) {
+            let cancelled = Arc::new(AtomicBool::new(false));
+            self.fade_flags.insert(cc, cancelled.clone());
+
+            let pipewire = self.engine.backend.clone();
+            let target_clone = target.clone();
+            let steps = (self.mute_fade_ms / RAMP_STEP_MS).max(1);
+            let kind = TargetKind::from_is_sink(is_sink);
+
+            // Ramp worker: one volume step per tick, re-locking each step so
+            // other volume changes are not blocked for the whole fade
+            thread::spawn(move || {
+                for step in 1..=steps {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
                     }
-
-                    if self.logging_enabled {
-                        self.ui_state
-                            .add_console_message(format!("UI Slider CC{}: {}", cc, percent));
+                    let percent = (from_percent as u32 * (steps - step) / steps) as u8;
+                    if let Ok(pw) = pipewire.lock() {
+                        let _ = pw.set_volume(kind, &target_clone, percent);
+                    }
+                    if step < steps {
+                        thread::sleep(Duration::from_millis(RAMP_STEP_MS as u64));
                     }
                 }
-            } else {
-                // Handle app volume change from UI
-                if ui_index < self.ui_state.app_fader_labels.len() {
-                    let cc = self.ui_state.app_fader_labels[ui_index].0;
-                    let percent = ((new_value as f32) * MIDI_TO_PERCENT_FACTOR) as u8;
-
-                    if let Some(target) = self.cc_mapping.get(&cc) {
-                        let pipewire = self.pipewire.clone();
-                        let target_clone = target.clone();
-
-                        // Spawn thread to avoid blocking UI
-                        thread::spawn(move || {
-                            if let Ok(pw) = pipewire.lock() {
-                                let _ = pw.set_volume_for_app(&target_clone, percent);
-                            }
-                        });
+                if then_mute {
+                    if let Ok(pw) = pipewire.lock() {
+                        let _ = pw.set_mute(kind, &target_clone, true);
+                        let _ = pw.set_volume(kind, &target_clone, from_percent);
                     }
+                }
+                // Finished, as far as `ramp_running` is concerned
+                cancelled.store(true, Ordering::Relaxed);
+            });
+        }
+    }
 
-                    if self.logging_enabled {
-                        self.ui_state
-                            .add_console_message(format!("UI Slider CC{}: {}", cc, percent));
-                    }
+    /// Log the commands the backend would have run in dry-run mode
+    fn process_dry_run_commands(&mut self) {
+        let Some(ref rx) = self.dry_run_rx else {
+            return;
+        };
+        let commands: Vec<String> = rx.try_iter().collect();
+        for command in commands {
+            self.ui_state
+                .add_console_message(format!("🧪 Dry run: {}", command));
+        }
+    }
+
+    /// Collect latency reports from the volume workers and log their rolling average
+    /// at most once a second
+    fn process_latency_samples(&mut self) {
+        while let Some(latency) = self.engine.try_recv_latency() {
+            if self.latency_samples.len() == LATENCY_WINDOW {
+                self.latency_samples.pop_front();
+            }
+            self.latency_samples.push_back(latency);
+            self.latency_unlogged = true;
+        }
+
+        if !self.latency_unlogged || self.last_latency_log.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        self.latency_unlogged = false;
+        self.last_latency_log = Instant::now();
+        let total: Duration = self.latency_samples.iter().sum();
+        let average = total / self.latency_samples.len() as u32;
+        let worst = self
+            .latency_samples
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or_default();
+        if self.logging_enabled {
+            self.ui_state.add_console_message(format!(
+                "⏱ MIDI to volume: avg {:.1} ms, max {:.1} ms over {} moves",
+                average.as_secs_f64() * 1000.0,
+                worst.as_secs_f64() * 1000.0,
+                self.latency_samples.len()
+            ));
+        }
+    }
+
+    /// Snap sink faders to the volume the workers read back after applying it, and
+    /// pulse the bar of each confirmed fader when `confirm_flash` is on
+    fn process_volume_readbacks(&mut self) {
+        while let Some((cc, requested, applied)) = self.engine.try_recv_readback() {
+            // Ignore stale reports superseded by a newer fader position
+            if self.engine.last_volume_values.get(&cc) != Some(&requested) {
+                continue;
+            }
+            if self.ui_state.cfg_confirm_flash {
+                self.ui_state.fader_confirmed_at.insert(cc, Instant::now());
+            }
+            // A dry run leaves the sink where it was, so keep the fader where it was put
+            if applied == requested || self.dry_run_rx.is_some() {
+                continue;
+            }
+            self.sync_fader_volume(cc, applied);
+
+            if self.logging_enabled {
+                self.ui_state.add_console_message(format!(
+                    "{}: sink applied {}% (requested {}%)",
+                    cc_label(cc),
+                    applied,
+                    requested
+                ));
+            }
+        }
+    }
+
+    /// Start the analyzer on the monitor of the selected spectrum sink
+    fn restart_spectrum(&mut self) {
+        let target = self.ui_state.cfg_spectrum_sink_name.clone();
+        let sink_name = self
+            .engine
+            .backend
+            .lock()
+            .ok()
+            .and_then(|backend| backend.sink_name(&target));
+
+        let sink_name = sink_name.unwrap_or_else(|| {
+            if self.logging_enabled {
+                self.ui_state.add_console_message(format!(
+                    "Spectrum: sink '{}' not found, monitoring default output",
+                    target
+                ));
+            }
+            "master_sink".to_string()
+        });
+        self.spectrum_analyzer.start(&sink_name);
+    }
+
+    /// Move the spectrum to the next configured sink's monitor, wrapping around to
+    /// the default output. The choice is saved like one made in the picker
+    fn cycle_spectrum_source(&mut self) {
+        let sources = self.ui_state.spectrum_sources();
+        let next = sources
+            .iter()
+            .position(|(target, _)| *target == self.ui_state.cfg_spectrum_sink_name)
+            .map_or(0, |i| (i + 1) % sources.len());
+        let (target, label) = sources[next].clone();
+
+        self.ui_state.cfg_spectrum_sink_name = target.clone();
+        self.last_spectrum_sink_name = target;
+        self.ui_state.settings_dirty = true;
+        if self.spectrum_analyzer.is_running() {
+            self.restart_spectrum();
+        }
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message(format!("📊 Spectrum source: {}", label));
+        }
+    }
+
+    /// Carry out an action picked from a fader's right-click menu
+    fn run_fader_action(&mut self, cc: u8, action: FaderAction) {
+        match action {
+            FaderAction::ToggleMute => self.handle_mute_button(cc, false),
+            FaderAction::Solo => self.toggle_app_solo(cc),
+            FaderAction::Remove => {
+                let before = self.ui_state.cfg_sinks.len() + self.ui_state.cfg_applications.len();
+                self.ui_state.cfg_sinks.retain(|(mapped, _)| *mapped != cc);
+                self.ui_state
+                    .cfg_applications
+                    .retain(|(mapped, _)| *mapped != cc);
+                if self.ui_state.cfg_sinks.len() + self.ui_state.cfg_applications.len() == before {
+                    return;
+                }
+                if self.logging_enabled {
+                    self.ui_state
+                        .add_console_message(format!("🗑 Removed mapping {}", cc_label(cc)));
                 }
+                self.ui_state.settings_dirty = true;
+                self.save_settings();
+            }
+        }
+    }
+
+    /// Beep on the sink a mapping resolves to, off the UI thread
+    fn identify_sink(&mut self, target: String) {
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message(format!("Identify: test tone on '{}'", target));
+        }
+        let pipewire = Arc::clone(&self.engine.backend);
+        let report = self.identify_tx.clone();
+        thread::spawn(move || {
+            // Release the backend before the tone plays so faders keep working
+            let sink_name = match pipewire.lock() {
+                Ok(pw) => pw.sink_name(&target),
+                Err(_) => return,
+            };
+            let result = match sink_name {
+                Some(name) => pipewire_control::play_test_tone(&name).map(|()| name),
+                None => Err(anyhow::anyhow!("sink '{}' not found", target)),
+            };
+            let _ = report.send(match result {
+                Ok(name) => format!("🔊 Identify: tone played on {}", name),
+                Err(e) => format!("❌ Identify failed: {}", e),
+            });
+        });
+    }
+
+    /// Show how the Identify tones went; failures are shown even with logging off
+    fn process_identify_results(&mut self) {
+        let results: Vec<String> = self.identify_rx.try_iter().collect();
+        for result in results {
+            if self.logging_enabled || result.starts_with('❌') {
+                self.ui_state.add_console_message(result);
+            }
+        }
+    }
+
+    /// Stop a running fade-out so a newer volume change wins
+    fn cancel_volume_ramp(&mut self, cc: u8) {
+        if let Some(cancelled) = self.fade_flags.remove(&cc) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn ramp_running(&self, cc: u8) -> bool {
+        self.fade_flags
+            .get(&cc)
+            .is_some_and(|done| !done.load(Ordering::Relaxed))
+    }
+
+    fn process_ui_slider_changes(&mut self, changed_faders: Vec<(bool, usize, u8)>) {
+        for (is_sink, ui_index, new_value) in changed_faders {
+            let labels = if is_sink {
+                &self.ui_state.system_fader_labels
+            } else {
+                &self.ui_state.app_fader_labels
+            };
+            if ui_index >= labels.len() {
+                continue;
+            }
+
+            let cc = labels[ui_index].0;
+            let percent = self.engine.to_percent(cc, new_value);
+            self.cancel_volume_ramp(cc);
+            self.engine.last_volume_values.insert(cc, percent);
+            self.engine.set_volume(cc, percent);
+
+            if self.logging_enabled {
+                self.ui_state.add_console_message(format!(
+                    "UI Slider {}: {}",
+                    cc_label(cc),
+                    percent
+                ));
             }
         }
     }
@@ -489,58 +1543,178 @@ impl MidiVolumeApp {
         }
         self.last_availability_check = Instant::now();
 
+        if let Some(cc) = self.focus_fader_cc {
+            self.refresh_focus_fader(cc);
+        }
+
+        // App mutes found on the server that the UI doesn't show yet
+        let mut mute_changes = Vec::new();
+        // Current volumes, to follow changes made outside the app
+        let mut volume_reads = Vec::new();
+
         // Check sink availability - assume available unless it errors
-        if let Ok(pipewire) = self.pipewire.lock() {
+        if let Ok(pipewire) = self.engine.backend.lock() {
+            // Sinks may have come or gone since the last scan
+            pipewire.rescan();
             for i in 0..self.ui_state.system_fader_labels.len() {
-                let sink_name = &self.ui_state.system_fader_labels[i].1;
+                let cc = self.ui_state.system_fader_labels[i].0;
+                let Some(sink_name) = self.engine.cc_mapping.get(&cc) else {
+                    continue;
+                };
                 // Sinks are typically always available, so default to true
-                let _ = pipewire.get_volume_for_sink(sink_name);
+                volume_reads.extend(
+                    pipewire
+                        .get_volume(TargetKind::Sink, sink_name)
+                        .map(|volume| (cc, volume)),
+                );
                 self.ui_state.system_available[i] = true;
             }
 
-            // Check app availability
-            for i in 0..self.ui_state.app_fader_labels.len() {
-                let app_name = &self.ui_state.app_fader_labels[i].1;
-                let is_available = pipewire.is_app_available(app_name);
-                self.ui_state.app_available[i] = is_available;
-                self.ui_state.app_input_count[i] = pipewire.get_app_input_count(app_name);
+            // Check app availability; unlabeled faders show the friendly name PipeWire reports.
+            // One look at the server covers every app
+            let mapped_apps: Vec<(usize, u8, String)> = (0..self.ui_state.app_fader_labels.len())
+                .filter_map(|i| {
+                    let cc = self.ui_state.app_fader_labels[i].0;
+                    Some((i, cc, self.engine.cc_mapping.get(&cc)?.clone()))
+                })
+                .collect();
+            let app_names: Vec<String> = mapped_apps
+                .iter()
+                .map(|(_, _, name)| name.clone())
+                .collect();
+            let statuses = pipewire.app_statuses(&app_names);
+            for ((i, cc, app_name), status) in mapped_apps.into_iter().zip(statuses) {
+                let input_count = status.input_count;
+                // Without history, a first miss greys the fader out right away
+                let misses = self
+                    .app_misses
+                    .entry(cc)
+                    .or_insert(APP_MISSES_BEFORE_UNAVAILABLE - 1);
+                if input_count > 0 {
+                    *misses = 0;
+                    volume_reads.extend(status.volume.map(|volume| (cc, volume)));
+                } else {
+                    *misses = misses.saturating_add(1);
+                }
+                self.ui_state.app_available[i] = *misses < APP_MISSES_BEFORE_UNAVAILABLE;
+
+                // Mutes done elsewhere (e.g. pavucontrol); a fade-out in progress only
+                // mutes once it finishes, so leave those alone
+                if let Some(muted) = status.muted {
+                    if muted != self.ui_state.app_muted[i] && !self.ramp_running(cc) {
+                        mute_changes.push((i, cc, muted));
+                    }
+                }
+                self.ui_state.app_input_count[i] = input_count;
+                if !self.labeled_ccs.contains(&cc) {
+                    self.ui_state.app_fader_labels[i].1 = status.display_name.unwrap_or(app_name);
+                }
+            }
+        }
+
+        // Nothing was really set in a dry run, so the real levels and mutes would
+        // pull the faders back
+        if self.dry_run_rx.is_some() {
+            return;
+        }
+        for (cc, percent) in volume_reads {
+            self.sync_external_volume(cc, percent);
+        }
+
+        for (i, cc, muted) in mute_changes {
+            self.ui_state.app_muted[i] = muted;
+            self.set_mute_leds(cc, muted);
+            if self.logging_enabled {
+                self.ui_state.add_console_message(format!(
+                    "🔇 {} {} outside the app",
+                    cc_label(cc),
+                    if muted { "muted" } else { "unmuted" }
+                ));
             }
         }
     }
 
+    /// Point the focus fader at the newest playing app. It keeps its last app
+    /// while nothing newer is playing
+    fn refresh_focus_fader(&mut self, cc: u8) {
+        let (app_name, volume) = {
+            let Ok(backend) = self.engine.backend.lock() else {
+                return;
+            };
+            let Some(app_name) = backend.newest_app() else {
+                return;
+            };
+            if self.engine.cc_mapping.get(&cc) == Some(&app_name) {
+                return;
+            }
+            let volume = backend.get_volume(TargetKind::App, &app_name);
+            (app_name, volume)
+        };
+
+        if self.logging_enabled {
+            self.ui_state
+                .add_console_message(format!("🎯 CC{} now follows {}", cc, app_name));
+        }
+        self.engine.cc_mapping.insert(cc, app_name);
+        if let Some(volume) = volume {
+            self.sync_fader_volume(cc, volume);
+        }
+        self.update_thru_ccs();
+    }
+
+    /// Point the selector fader at the app the knob position picks
+    fn select_target(&mut self, value: u8) {
+        let Some(selector) = &self.selector else {
+            return;
+        };
+        let index = selector.index_at(value);
+        if index == self.selector_index {
+            return;
+        }
+        let cc = selector.fader_cc;
+        let count = selector.targets.len();
+        let target = selector.targets[index].clone();
+        let (volume, name) = {
+            let Ok(backend) = self.engine.backend.lock() else {
+                return;
+            };
+            (
+                backend.get_volume(TargetKind::App, &target),
+                backend.app_display_name(&target),
+            )
+        };
+        self.selector_index = index;
+
+        if self.logging_enabled {
+            self.ui_state.add_console_message(format!(
+                "🎛 {} now controls {} ({}/{})",
+                cc_label(cc),
+                target,
+                index + 1,
+                count
+            ));
+        }
+        if let Some(&i) = self.cc_to_app_index.get(&cc) {
+            self.ui_state.app_fader_labels[i].1 = name.unwrap_or_else(|| target.clone());
+        }
+        self.engine.cc_mapping.insert(cc, target);
+        if let Some(volume) = volume {
+            self.sync_fader_volume(cc, volume);
+        }
+        // Availability and mute belonged to the previous app; check the new one next frame
+        self.app_misses.remove(&cc);
+        self.last_availability_check = rescan_due(self.applications_sink_search_interval_secs);
+    }
+
     fn save_settings(&mut self) {
         // Create config from UI state
-        let config = Config::from_ui_state(
-            &self.ui_state.cfg_sinks,
-            &self.ui_state.cfg_applications,
-            &self.ui_state.cfg_mute_buttons,
-            self.ui_state.cfg_use_pipewire,
-            &self.ui_state.cfg_default_sink,
-            &self.ui_state.cfg_volume_control_mode,
-            &self.ui_state.cfg_volume_curve,
-            self.ui_state.cfg_debounce_ms,
-            self.ui_state.cfg_applications_sink_search,
-            self.ui_state.cfg_window_width,
-            self.ui_state.cfg_window_height,
-            &self.ui_state.cfg_theme,
-            self.ui_state.cfg_show_console,
-            self.ui_state.cfg_max_console_lines,
-            self.ui_state.cfg_show_spectrum,
-            self.ui_state.cfg_spectrum_stereo_mode,
-            self.ui_state.cfg_spectrum_show_waterfall,
-            self.ui_state.cfg_spectrum_show_labels,
-            &self.ui_state.cfg_spectrum_sink_name,
-            self.ui_state.cfg_logging_enabled,
-            &self.ui_state.cfg_log_level,
-            self.ui_state.cfg_timestamps,
-            self.ui_state.cfg_log_fader_events,
-            self.ui_state.cfg_log_device_info,
-        );
+        let config = Config::from_ui_state(&self.ui_state);
 
         // Save to file
         match config.save_to_file(&self.ui_state.config_path) {
             Ok(()) => {
                 self.ui_state.settings_dirty = false;
+                self.saved_tab = self.ui_state.selected_tab;
                 self.ui_state.settings_save_message = Some((
                     "SUCCESS: Settings saved".to_string(),
                     std::time::Instant::now(),
@@ -551,14 +1725,57 @@ impl MidiVolumeApp {
                     &self.ui_state.config_path,
                     "~/.bin/audio/nanokontrol2/config.toml",
                 ) {
+                    // The file on disk is valid again
+                    self.ui_state.config_error = None;
+                    self.config_load_failed = false;
+
                     // Update runtime values from reloaded config
-                    self.debounce_ms = reloaded_config.audio.debounce_ms.unwrap_or(0);
+                    self.engine.debounce_ms = reloaded_config.audio.debounce_ms.unwrap_or(0);
+                    if let Ok(mut backend) = self.engine.backend.lock() {
+                        backend.set_read_mode(VolumeReadMode::parse(
+                            reloaded_config
+                                .audio
+                                .volume_read_mode
+                                .as_deref()
+                                .unwrap_or("percent"),
+                        ));
+                        backend.set_app_wpctl(
+                            reloaded_config.audio.volume_control_mode.as_deref() == Some("wpctl"),
+                        );
+                        backend.set_default_fallbacks(reloaded_config.get_default_fallbacks());
+                    }
+                    self.mute_hold_ms = reloaded_config.audio.mute_hold_ms.unwrap_or(500);
+                    self.mute_fade_ms = reloaded_config.audio.mute_fade_ms.unwrap_or(1500);
+                    self.focus_fader_cc = reloaded_config.get_focus_fader_cc();
+                    // The rebuilt mappings put the selector fader back on its first app
+                    self.selector = reloaded_config.get_selector();
+                    self.selector_index = 0;
+                    self.end_duck();
+                    // Unmute what the solo silenced while the old mappings still apply
+                    self.end_solo();
+                    self.duck_button_cc = reloaded_config.audio.duck_button_cc;
+                    self.spectrum_cycle_cc = reloaded_config.ui.spectrum_cycle_cc;
+                    self.window_toggle_cc = reloaded_config.ui.window_toggle_cc;
+                    self.duck_keep_cc = reloaded_config.audio.duck_keep_cc;
+                    self.duck_level = reloaded_config.audio.duck_level.unwrap_or(20).min(100);
                     self.applications_sink_search_interval_secs =
                         reloaded_config.audio.applications_sink_search.unwrap_or(10);
                     self.logging_enabled = reloaded_config.logging.enabled.unwrap_or(true);
+                    self.engine.log_latency = reloaded_config.logging.log_latency.unwrap_or(false);
+                    self.engine.zero_guard_ms = reloaded_config.midi.zero_guard_ms.unwrap_or(0);
+                    self.latency_samples.clear();
+                    self.latency_unlogged = false;
+                    self.max_idle_fps = reloaded_config.ui.max_idle_fps.unwrap_or(10);
+                    self.max_fps = reloaded_config.ui.max_fps.unwrap_or(0);
+                    self.ui_state.set_max_console_lines(
+                        reloaded_config.ui.max_console_lines.unwrap_or(1000),
+                    );
 
                     // Reload sink and app mappings
-                    self.cc_mapping = reloaded_config.get_cc_mapping();
+                    self.engine.cc_mapping = reloaded_config.get_cc_mapping();
+                    self.engine.fader_settings = reloaded_config.get_fader_settings();
+                    self.ui_state.fader_settings = self.engine.fader_settings.clone();
+                    self.ui_state.fader_colors = reloaded_config.get_fader_colors().0;
                     let sink_labels = reloaded_config.get_sink_labels();
                     let app_labels = reloaded_config.get_app_labels();
 
@@ -578,7 +1795,6 @@ impl MidiVolumeApp {
                     self.ui_state.app_fader_labels = app_labels.clone();
                     self.ui_state.app_fader_values.resize(app_labels.len(), 0);
                     self.ui_state.app_muted.resize(app_labels.len(), false);
-                    self.ui_state.app_muted_volume.resize(app_labels.len(), 0);
                     self.ui_state.app_available.resize(app_labels.len(), true);
 
                     // Reset visibility, display order, and input count to match new config size
@@ -587,28 +1803,35 @@ impl MidiVolumeApp {
                     self.ui_state.app_visibility = vec![true; app_labels.len()];
                     self.ui_state.app_display_order = (0..app_labels.len()).collect();
                     self.ui_state.app_input_count = vec![0; app_labels.len()];
+                    self.app_misses.clear();
 
-                    // Rebuild CC type mappings
-                    self.cc_types.clear();
-                    for (cc, _) in &sink_labels {
-                        self.cc_types.insert(*cc, true);
-                    }
-                    for (cc, _) in &app_labels {
-                        self.cc_types.insert(*cc, false);
-                    }
-
-                    // Rebuild CC to UI index mappings
-                    self.cc_to_sink_index.clear();
-                    for (i, (cc, _)) in sink_labels.iter().enumerate() {
-                        self.cc_to_sink_index.insert(*cc, i);
-                    }
-                    self.cc_to_app_index.clear();
-                    for (i, (cc, _)) in app_labels.iter().enumerate() {
-                        self.cc_to_app_index.insert(*cc, i);
-                    }
+                    // Rebuild CC type and UI index mappings
+                    let index = FaderIndex::new(&sink_labels, &app_labels);
+                    self.engine.cc_types = index.cc_types;
+                    self.cc_to_sink_index = index.cc_to_sink_index;
+                    self.cc_to_app_index = index.cc_to_app_index;
 
                     // Reload mute button mappings
                     self.mute_button_mapping = reloaded_config.get_mute_button_mappings();
+                    self.momentary_mute_buttons = reloaded_config.get_momentary_mute_buttons();
+                    self.ui_state.mute_button_warnings = reloaded_config.get_mute_button_warnings();
+                    for &button_cc in self.solo_button_mapping.keys() {
+                        self.midi_output.unlight_button(button_cc);
+                    }
+                    self.solo_button_mapping = reloaded_config.get_solo_button_mappings();
+                    // Also lights a button added to an already muted fader
+                    self.led_inverted = reloaded_config.midi.led_inverted.unwrap_or(false);
+                    if let Some(old_button) = self.bank_button_cc {
+                        self.midi_output.unlight_button(old_button);
+                    }
+                    self.bank_button_cc = reloaded_config.midi.bank_button_cc;
+                    self.set_bank(self.bank_b && self.bank_button_cc.is_some());
+                    self.labeled_ccs = reloaded_config.get_labeled_ccs();
+                    self.update_thru_ccs();
+
+                    // Rediscover apps for the new mappings on the next frame
+                    self.last_availability_check =
+                        rescan_due(self.applications_sink_search_interval_secs);
                 }
 
                 if self.logging_enabled {
@@ -646,17 +1869,72 @@ impl eframe::App for MidiVolumeApp {
             self.last_window_height = self.ui_state.cfg_window_height;
         }
 
-        // Check for spectrum sink name changes
-        if self.last_spectrum_sink_name != self.ui_state.cfg_spectrum_sink_name {
+        // Shrink the viewport in mini mode and restore the configured size afterwards
+        if self.last_mini_mode != self.ui_state.mini_mode {
+            self.last_mini_mode = self.ui_state.mini_mode;
+            let new_size = if self.last_mini_mode {
+                self.ui_state.mini_window_size()
+            } else {
+                egui::Vec2::new(
+                    self.ui_state.cfg_window_width as f32,
+                    self.ui_state.cfg_window_height as f32,
+                )
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(new_size));
+        }
+
+        // Capture audio only while the visualizer is enabled, and follow sink changes
+        let sink_changed = self.last_spectrum_sink_name != self.ui_state.cfg_spectrum_sink_name;
+        if sink_changed {
             self.last_spectrum_sink_name = self.ui_state.cfg_spectrum_sink_name.clone();
-            self.spectrum_analyzer
-                .start(&self.ui_state.cfg_spectrum_sink_name);
+        }
+        let spectrum_wanted = self.ui_state.cfg_show_spectrum
+            || self.ui_state.selected_tab == crate::ui::Tab::Spectrum;
+        if spectrum_wanted {
+            if sink_changed || !self.spectrum_analyzer.is_running() {
+                self.restart_spectrum();
+            }
+        } else if self.spectrum_analyzer.is_running() {
+            self.spectrum_analyzer.stop();
+        }
+
+        // Mono mode sums L+R into a single FFT to save CPU
+        if self.last_spectrum_stereo != self.ui_state.cfg_spectrum_stereo_mode {
+            self.last_spectrum_stereo = self.ui_state.cfg_spectrum_stereo_mode;
+            self.spectrum_analyzer.set_stereo(self.last_spectrum_stereo);
+        }
+
+        let a_weighting = self.ui_state.cfg_spectrum_weighting == "a";
+        if self.last_spectrum_a_weighting != a_weighting {
+            self.last_spectrum_a_weighting = a_weighting;
+            self.spectrum_analyzer.set_a_weighting(a_weighting);
+        }
+
+        let freq_scale = FreqScale::parse(&self.ui_state.cfg_spectrum_freq_scale);
+        if self.last_spectrum_freq_scale != freq_scale {
+            self.last_spectrum_freq_scale = freq_scale;
+            self.spectrum_analyzer.set_freq_scale(freq_scale);
         }
 
         // Process incoming MIDI messages immediately
-        self.process_midi_messages();
+        let midi_active = self.process_midi_messages();
+        if std::mem::take(&mut self.window_toggle_requested) {
+            self.toggle_window(ctx);
+        }
+
+        // Commands from the control socket
+        self.process_control_requests();
+
+        // Sync faders with the volume actually applied by the workers
+        self.process_volume_readbacks();
+        self.process_latency_samples();
+        self.process_dry_run_commands();
+        self.process_identify_results();
+
+        // Start fade-outs for mute buttons held past the threshold
+        self.check_mute_button_holds();
 
-        // Check audio availability every 10 seconds
+        // Rediscover apps every applications_sink_search seconds
         self.check_audio_availability();
 
         // Update spectrum data from analyzer
@@ -665,14 +1943,27 @@ impl eframe::App for MidiVolumeApp {
         // Render UI
         self.ui_state.render_tabs(ctx);
 
+        if self.ui_state.cfg_show_midi_debug {
+            self.ui_state
+                .applied_percents
+                .clone_from(&self.engine.last_volume_values);
+        }
+
+        // Set again by the Control tab while a fader highlight is fading
+        self.ui_state.fader_animating = false;
         let changed_faders = match self.ui_state.selected_tab {
+            _ if self.ui_state.mini_mode => self.ui_state.render_mini_tab(ctx),
             crate::ui::Tab::Control => self.ui_state.render_faders_tab(ctx),
+            crate::ui::Tab::Spectrum => {
+                self.ui_state.render_spectrum_tab(ctx);
+                Vec::new()
+            }
             crate::ui::Tab::Console => {
                 self.ui_state.render_console_tab(ctx);
                 Vec::new()
             }
             crate::ui::Tab::Settings => {
-                let settings_changed = self.ui_state.render_settings_tab(ctx, false);
+                let settings_changed = self.ui_state.render_settings_tab(ctx, TRAY_FUNCTIONAL);
                 if settings_changed && self.ui_state.settings_dirty {
                     // Save settings to config file
                     self.save_settings();
@@ -681,24 +1972,69 @@ impl eframe::App for MidiVolumeApp {
             }
         };
 
+        if let Some(target) = self.ui_state.identify_sink.take() {
+            self.identify_sink(target);
+        }
+        if let Some((cc, action)) = self.ui_state.fader_action.take() {
+            self.run_fader_action(cc, action);
+        }
+        if std::mem::take(&mut self.ui_state.refresh_server_info) {
+            self.query_server_info();
+        }
+        if std::mem::take(&mut self.ui_state.import_requested) {
+            self.load_import_rows();
+        }
+        if std::mem::take(&mut self.ui_state.reset_requested) {
+            self.reset(ctx);
+        }
+
         // Render MIDI UI modal if open
         crate::panels::render_midi_ui_modal(&mut self.ui_state, ctx);
 
         // Handle UI slider changes
         self.process_ui_slider_changes(changed_faders);
 
-        // Request continuous repainting for instant MIDI response
-        // This ensures the UI updates immediately when MIDI events occur
-        ctx.request_repaint();
-
-        // Also request a repaint for the next frame to maintain responsiveness
-        ctx.request_repaint_after(std::time::Duration::from_millis(16)); // ~60 FPS
+        // Repaint continuously only while something is animating; MIDI input wakes
+        // the UI through the listener, so idle frames can be spaced out
+        let spectrum_visible = !self.ui_state.mini_mode
+            && match self.ui_state.selected_tab {
+                crate::ui::Tab::Control => self.ui_state.cfg_show_spectrum,
+                crate::ui::Tab::Spectrum => true,
+                _ => false,
+            };
+        if midi_active
+            || spectrum_visible
+            || self.ui_state.fader_animating
+            || !self.mute_presses.is_empty()
+        {
+            // Animation timing follows the frame time, so a cap only lowers the rate
+            if self.max_fps > 0 {
+                ctx.request_repaint_after(Duration::from_millis(1000 / self.max_fps as u64));
+            } else {
+                ctx.request_repaint();
+            }
+        } else {
+            let idle_ms = 1000 / self.max_idle_fps.max(1) as u64;
+            ctx.request_repaint_after(Duration::from_millis(idle_ms));
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        // Save settings on app exit
-        if self.ui_state.settings_dirty {
+        // Save settings on app exit, including the tab to reopen on next launch. A
+        // config that failed to load is only overwritten by an explicit change
+        let tab_changed = self.ui_state.selected_tab != self.saved_tab;
+        if self.ui_state.settings_dirty || (tab_changed && !self.config_load_failed) {
             self.save_settings();
         }
+
+        // Release the MIDI port before the window goes away
+        self.midi_listener.stop();
     }
 }
+
+// Last-check time that makes the app rediscovery run on the next frame
+fn rescan_due(interval_secs: u64) -> Instant {
+    Instant::now()
+        .checked_sub(Duration::from_secs(interval_secs))
+        .unwrap_or_else(Instant::now)
+}
@@ -1,64 +1,477 @@
+use crate::control_socket;
+use crate::engine::{FaderSettings, VolumeCurve, CHANNEL_SUFFIX, DEFAULT_CURVE_EXPONENT};
+use crate::midi::PortSelection;
+use crate::panels::theme::{parse_hex_color, Palette};
+use crate::ui::UiState;
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use egui::Color32;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::PathBuf;
+
+/// Label of the fader `[audio] focus_fader_cc` binds to the newest playing app
+pub const FOCUS_FADER_LABEL: &str = "🎯 Focused App";
+
+/// The `[audio] selector_cc` knob and the fader it switches between several apps
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub knob_cc: u8,
+    pub fader_cc: u8,
+    pub targets: Vec<String>,
+}
+
+impl Selector {
+    /// Target picked by a knob value; the knob travel is split evenly between the targets
+    pub fn index_at(&self, value: u8) -> usize {
+        (value as usize * self.targets.len() / 128).min(self.targets.len().saturating_sub(1))
+    }
+}
+
+/// A string as a TOML value, with quotes and backslashes escaped
+fn toml_quote(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// Trim a mapping's `trim_db` is kept within, as in the Settings popover
+pub const TRIM_DB_RANGE: std::ops::RangeInclusive<f32> = -30.0..=12.0;
+
+/// Highest percent a fader with `allow_boost` reaches; PipeWire amplifies past 100%
+pub const BOOST_MAX_PERCENT: u8 = 150;
+
+/// Set on a fader CC for its bank B mapping; MIDI CCs only go up to 127
+pub const BANK_B: u8 = 0x80;
+
+/// Fader CC of a sink or application mapping key: `cc_3` is CC 3 in bank A and
+/// `b_cc_3` is CC 3 in bank B (`3 | BANK_B`)
+pub fn mapping_cc(key: &str) -> Option<u8> {
+    let (digits, bank) = match key.strip_prefix("b_cc_") {
+        Some(digits) => (digits, BANK_B),
+        None => (key.strip_prefix("cc_")?, 0),
+    };
+    let cc = digits.parse::<u8>().ok().filter(|&cc| cc < BANK_B)?;
+    Some(cc | bank)
+}
+
+/// Mapping key for a fader CC, the inverse of `mapping_cc`
+pub fn mapping_key(cc: u8) -> String {
+    if cc & BANK_B != 0 {
+        format!("b_cc_{}", cc & !BANK_B)
+    } else {
+        format!("cc_{}", cc)
+    }
+}
+
+/// Fader CC as shown in the UI, e.g. `CC3` or `B:CC3` for bank B
+pub fn cc_label(cc: u8) -> String {
+    if cc & BANK_B != 0 {
+        format!("B:CC{}", cc & !BANK_B)
+    } else {
+        format!("CC{}", cc)
+    }
+}
+
+/// Parse a CC typed in Settings; a `b` prefix (`b3`) selects bank B
+pub fn parse_cc_input(text: &str) -> Option<u8> {
+    let text = text.trim();
+    match text.strip_prefix(['b', 'B']) {
+        Some(digits) => mapping_cc(&format!("b_cc_{}", digits.trim())),
+        None => mapping_cc(&format!("cc_{}", text)),
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub midi_controls: MidiControlsConfig,
+    #[serde(default)]
+    pub midi: MidiConfig,
+    // Missing sections fall back to the built-in defaults field by field
+    #[serde(default)]
     pub audio: AudioConfig,
+    #[serde(default)]
     pub ui: UiConfig,
+    #[serde(default)]
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MidiControlsConfig {
     // Map MIDI CC number to sink targets (sink names)
     // Example: cc_0 = "alsa_output.pci-0000_25_00.0.analog-stereo", cc_1 = "comms_sink"
-    #[serde(default)]
-    pub sinks: HashMap<String, String>,
+    // or with a display label: cc_0 = { target = "alsa_output...", label = "Speakers" }
+    #[serde(default, deserialize_with = "deserialize_mappings")]
+    pub sinks: HashMap<String, MappingConfig>,
     // Map MIDI CC number to application names
     // Example: cc_16 = "Google Chrome"
-    #[serde(default)]
-    pub applications: HashMap<String, String>,
+    #[serde(default, deserialize_with = "deserialize_mappings")]
+    pub applications: HashMap<String, MappingConfig>,
     // Map mute button CC to target fader CC (e.g., cc_64 = "cc_0" means CC64 mutes CC0)
     // The key is the mute button CC, the value is the target fader CC number
+    // or a table: cc_64 = { fader = 0, behavior = "momentary" }
+    #[serde(default, deserialize_with = "deserialize_mute_buttons")]
+    pub mute_buttons: HashMap<String, MuteButtonConfig>,
+    // Map solo button CC to an app fader CC (e.g., cc_32 = 16); soloing mutes every other app
     #[serde(default)]
-    pub mute_buttons: HashMap<String, u8>,
+    pub solo_buttons: HashMap<String, u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A fader mapping. Only `target` is required; a plain string in the config is
+/// read as a mapping with just the target set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MappingConfig {
+    // Sink/app name used for matching
+    pub target: String,
+    // Shown on the fader instead of the raw target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // Sinks: "name" (default), "description" (device.description substring) or "node" (node id)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_mode: Option<String>,
+    // Volume percent at the bottom and top of the fader travel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<u8>,
+    // Overrides [audio] volume_curve for this fader
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
+    // Percent the on-screen fader resets to on double-click
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_level: Option<u8>,
+    // 14-bit fader sending its LSB on CC + 32 (CCs 0-31 only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_res: Option<bool>,
+    // Reverse the fader direction (for a fader mounted upside down)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invert: Option<bool>,
+    // Sinks: control only this channel index (e.g. 3 for the LFE of a 5.1 sink)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    // Let the top of the fader go past 100%, up to BOOST_MAX_PERCENT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_boost: Option<bool>,
+    // Gain in dB added to every level this fader sets, to match outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_db: Option<f32>,
+    // Percent applied on every launch, whatever the volume was before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_level: Option<u8>,
+    // "#rrggbb" accent for this fader, instead of its section color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // Sinks: drive the default sink while this one isn't present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_to_default: Option<bool>,
+}
+
+impl MappingConfig {
+    pub fn new(target: impl Into<String>) -> Self {
+        MappingConfig {
+            target: target.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Target as understood by the audio backend, with the match mode applied
+    pub fn resolved_target(&self) -> String {
+        let target = self.target.trim();
+        let target = match self.match_mode.as_deref() {
+            Some("description") => format!("desc:{}", target),
+            Some("node") => format!("node:{}", target),
+            _ => target.to_string(),
+        };
+        match self.channel {
+            Some(channel) => format!("{}{}{}", target, CHANNEL_SUFFIX, channel),
+            None => target,
+        }
+    }
+
+    /// App target as understood by the audio backend: `match_mode = "role"` matches
+    /// streams by `media.role` instead of by app name
+    pub fn app_target(&self) -> String {
+        let target = self.target.trim();
+        match self.match_mode.as_deref() {
+            Some("role") => format!("role:{}", target),
+            _ => target.to_string(),
+        }
+    }
+
+    /// Volume shaping for this fader, falling back to the global curve
+    pub fn fader_settings(&self, default_curve: &str, exponent: f32) -> FaderSettings {
+        let min = self.min.unwrap_or(0).min(100);
+        let top = if self.allow_boost.unwrap_or(false) {
+            BOOST_MAX_PERCENT
+        } else {
+            100
+        };
+        FaderSettings {
+            min,
+            max: self.max.unwrap_or(top).clamp(min, top),
+            curve: VolumeCurve::parse(self.curve.as_deref().unwrap_or(default_curve)),
+            default_level: self.default_level,
+            high_res: self.high_res.unwrap_or(false),
+            invert: self.invert.unwrap_or(false),
+            trim_db: self
+                .trim_db
+                .filter(|trim| trim.is_finite())
+                .unwrap_or(0.0)
+                .clamp(*TRIM_DB_RANGE.start(), *TRIM_DB_RANGE.end()),
+            top,
+            exponent,
+        }
+    }
+
+    /// Label shown in the UI, falling back to the target
+    pub fn display_label(&self) -> String {
+        self.label
+            .as_deref()
+            .filter(|label| !label.trim().is_empty())
+            .unwrap_or(&self.target)
+            .trim()
+            .to_string()
+    }
+
+    /// TOML value: a plain string when only the target is set, an inline table otherwise
+    fn to_toml_value(&self) -> String {
+        let mut fields = vec![format!("target = {}", toml_quote(&self.target))];
+        if let Some(ref label) = self.label {
+            fields.push(format!("label = {}", toml_quote(label)));
+        }
+        if let Some(ref mode) = self.match_mode {
+            fields.push(format!("match_mode = {}", toml_quote(mode)));
+        }
+        if let Some(min) = self.min {
+            fields.push(format!("min = {}", min));
+        }
+        if let Some(max) = self.max {
+            fields.push(format!("max = {}", max));
+        }
+        if let Some(ref curve) = self.curve {
+            fields.push(format!("curve = {}", toml_quote(curve)));
+        }
+        if let Some(level) = self.default_level {
+            fields.push(format!("default_level = {}", level));
+        }
+        if let Some(high_res) = self.high_res {
+            fields.push(format!("high_res = {}", high_res));
+        }
+        if let Some(invert) = self.invert {
+            fields.push(format!("invert = {}", invert));
+        }
+        if let Some(channel) = self.channel {
+            fields.push(format!("channel = {}", channel));
+        }
+        if let Some(boost) = self.allow_boost {
+            fields.push(format!("allow_boost = {}", boost));
+        }
+        if let Some(trim) = self.trim_db {
+            fields.push(format!("trim_db = {:?}", trim));
+        }
+        if let Some(level) = self.startup_level {
+            fields.push(format!("startup_level = {}", level));
+        }
+        if let Some(ref color) = self.color {
+            fields.push(format!("color = {}", toml_quote(color)));
+        }
+        if let Some(fallback) = self.fallback_to_default {
+            fields.push(format!("fallback_to_default = {}", fallback));
+        }
+
+        if fields.len() == 1 {
+            toml_quote(&self.target)
+        } else {
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+// Mapping values may be a bare target string (legacy) or a table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MappingValue {
+    Target(String),
+    Table(MappingConfig),
+}
+
+fn deserialize_mappings<'de, D>(deserializer: D) -> Result<HashMap<String, MappingConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = HashMap::<String, MappingValue>::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .map(|(key, value)| {
+            let mapping = match value {
+                MappingValue::Target(target) => MappingConfig::new(target),
+                MappingValue::Table(mapping) => mapping,
+            };
+            (key, mapping)
+        })
+        .collect())
+}
+
+/// A mute button. A plain fader CC number in the config is read as a toggle button
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MuteButtonConfig {
+    // CC of the fader this button mutes
+    pub fader: u8,
+    // "toggle" (default) flips mute on each press, "momentary" mutes only while held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<MuteBehavior>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MuteBehavior {
+    #[default]
+    Toggle,
+    Momentary,
+}
+
+impl MuteButtonConfig {
+    pub fn new(fader: u8) -> Self {
+        MuteButtonConfig {
+            fader,
+            behavior: None,
+        }
+    }
+
+    pub fn is_momentary(&self) -> bool {
+        self.behavior == Some(MuteBehavior::Momentary)
+    }
+
+    /// TOML value: the bare fader CC for a toggle button, an inline table otherwise
+    fn to_toml_value(self) -> String {
+        match self.behavior {
+            Some(MuteBehavior::Momentary) => {
+                format!("{{ fader = {}, behavior = \"momentary\" }}", self.fader)
+            }
+            _ => self.fader.to_string(),
+        }
+    }
+}
+
+// Mute button values may be a bare fader CC (legacy) or a table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MuteButtonValue {
+    Fader(u8),
+    Table(MuteButtonConfig),
+}
+
+fn deserialize_mute_buttons<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, MuteButtonConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = HashMap::<String, MuteButtonValue>::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .map(|(key, value)| {
+            let button = match value {
+                MuteButtonValue::Fader(fader) => MuteButtonConfig::new(fader),
+                MuteButtonValue::Table(button) => button,
+            };
+            (key, button)
+        })
+        .collect())
+}
+
+// Device port choice for when several nanoKontrol2 ports are connected
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MidiConfig {
+    pub port: Option<String>,
+    pub port_index: Option<usize>,  // Among ports sharing the same name
+    pub midi_thru: Option<bool>,    // Forward unmapped messages to a virtual port
+    pub led_feedback: Option<bool>, // Light mute button LEDs; false skips the output port
+    pub led_inverted: Option<bool>, // Light LEDs of unmuted faders instead of muted ones
+    pub startup_ignore_ms: Option<u32>, // Discard MIDI for this long after connecting
+    pub zero_guard_ms: Option<u32>, // Ignore a fader's 0 unless it moved this recently
+    pub bank_button_cc: Option<u8>, // Button that switches faders between bank A and B
+}
+
+// Local command socket for scripting volume changes
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ControlConfig {
+    pub enabled: Option<bool>,
+    pub socket_path: Option<String>, // Defaults to $XDG_RUNTIME_DIR/korg-midi-volume.sock
+}
+
+// Custom colors as "#rrggbb" hex strings; unset keeps the built-in theme
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ThemeConfig {
+    pub bg: Option<String>,
+    pub accent: Option<String>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioConfig {
     pub use_pipewire: Option<bool>,
     pub default_sink: Option<String>,
     pub volume_control_mode: Option<String>,
     pub volume_curve: Option<String>,
+    pub volume_curve_exponent: Option<f32>, // Power of the exponential curve, e.g. 1.5 or 3.0
+    pub volume_read_mode: Option<String>,   // "percent" or "db"
     pub debounce_ms: Option<u32>,
     pub applications_sink_search: Option<u64>,
+    pub mute_hold_ms: Option<u32>,
+    pub mute_fade_ms: Option<u32>,
+    pub focus_fader_cc: Option<u8>, // Fader that follows the most recently started app
+    pub duck_button_cc: Option<u8>, // Button that lowers sinks to duck_level while held
+    pub duck_keep_cc: Option<u8>,   // Sink fader the duck button leaves alone
+    pub duck_level: Option<u8>,     // Percent ducked sinks drop to
+    pub selector_cc: Option<u8>,    // Knob that picks which selector_targets app the fader drives
+    pub selector_fader_cc: Option<u8>, // Fader that follows the selected app
+    pub selector_targets: Option<Vec<String>>, // Apps the selector knob steps through
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UiConfig {
     pub window_width: Option<u32>,
     pub window_height: Option<u32>,
     pub theme: Option<String>,
     pub show_console: Option<bool>,
     pub max_console_lines: Option<usize>,
+    pub hide_unavailable: Option<bool>,
+    pub decimal_percent: Option<bool>,
+    pub color_by_level: Option<bool>, // Fader bars shade green to red as volume rises
+    #[serde(alias = "show_raw_midi")]
+    pub show_midi_debug: Option<bool>, // Raw CC value and applied percent on each fader
+    pub confirm_flash: Option<bool>,  // Pulse a fader bar once its volume is applied
+    pub max_idle_fps: Option<u32>,
+    pub max_fps: Option<u32>, // Redraw rate cap while animating, 0 = unlimited
     pub show_spectrum: Option<bool>,
     pub spectrum_stereo_mode: Option<bool>,
     pub spectrum_show_waterfall: Option<bool>,
     pub spectrum_show_labels: Option<bool>,
     pub spectrum_sink_name: Option<String>,
+    pub spectrum_cycle_cc: Option<u8>, // Button that steps the spectrum through the sinks
+    pub spectrum_weighting: Option<String>,
+    pub spectrum_freq_scale: Option<String>,
+    pub spectrum_smoothing: Option<f32>, // Bar attack speed, higher is snappier
+    pub spectrum_peak_decay_ui: Option<f32>, // Peak marker fall speed
+    pub spectrum_scale: Option<f32>, // Size of the spectrum's labels, gaps and lines, 1.0 = default
+    pub last_tab: Option<String>,
+    pub start_minimized: Option<bool>,
+    pub window_toggle_cc: Option<u8>, // Button that minimizes and restores the window
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LoggingConfig {
     pub enabled: Option<bool>,
     pub log_level: Option<String>,
     pub timestamps: Option<bool>,
     pub log_fader_events: Option<bool>,
     pub log_device_info: Option<bool>,
+    pub log_latency: Option<bool>, // Rolling MIDI-to-volume latency in the console
+    pub log_file: Option<String>,  // Also write console messages to this file
 }
 
 impl Config {
@@ -86,34 +499,38 @@ impl Config {
         let mut mapping = HashMap::with_capacity(capacity);
 
         // Add sink controls
-        for (key, target) in &self.midi_controls.sinks {
-            if let Some(cc_str) = key.strip_prefix("cc_") {
-                if let Ok(cc_num) = cc_str.parse::<u8>() {
-                    mapping.insert(cc_num, target.trim().to_string());
-                }
+        for (key, sink) in &self.midi_controls.sinks {
+            if let Some(cc_num) = mapping_cc(key) {
+                mapping.insert(cc_num, sink.resolved_target());
             }
         }
 
         // Add application controls
-        for (key, app_name) in &self.midi_controls.applications {
-            if let Some(cc_str) = key.strip_prefix("cc_") {
-                if let Ok(cc_num) = cc_str.parse::<u8>() {
-                    mapping.insert(cc_num, app_name.trim().to_string());
-                }
+        for (key, app) in &self.midi_controls.applications {
+            if let Some(cc_num) = mapping_cc(key) {
+                mapping.insert(cc_num, app.app_target());
             }
         }
 
+        // The selector fader starts on its first app
+        if let Some(selector) = self.get_selector() {
+            mapping.insert(selector.fader_cc, selector.targets[0].clone());
+        }
+
         mapping
     }
 
     pub fn get_sink_labels(&self) -> Vec<(u8, String)> {
-        // Returns sorted list of sink controls
+        // Returns sorted list of sink controls with their display labels
         let mut controls = Vec::with_capacity(self.midi_controls.sinks.len());
-        for (key, target) in &self.midi_controls.sinks {
-            if let Some(cc_str) = key.strip_prefix("cc_") {
-                if let Ok(cc_num) = cc_str.parse::<u8>() {
-                    controls.push((cc_num, target.trim().to_string()));
-                }
+        for (key, sink) in &self.midi_controls.sinks {
+            if let Some(cc_num) = mapping_cc(key) {
+                // Name the channel so per-channel faders of one sink can be told apart
+                let label = match sink.channel {
+                    Some(channel) => format!("{} (ch {})", sink.display_label(), channel),
+                    None => sink.display_label(),
+                };
+                controls.push((cc_num, label));
             }
         }
         controls.sort_by_key(|(cc, _)| *cc);
@@ -121,31 +538,244 @@ impl Config {
     }
 
     pub fn get_app_labels(&self) -> Vec<(u8, String)> {
-        // Returns sorted list of application controls
+        // Returns sorted list of application controls with their display labels
         let mut controls = Vec::with_capacity(self.midi_controls.applications.len());
-        for (key, app_name) in &self.midi_controls.applications {
-            if let Some(cc_str) = key.strip_prefix("cc_") {
-                if let Ok(cc_num) = cc_str.parse::<u8>() {
-                    controls.push((cc_num, app_name.trim().to_string()));
-                }
+        for (key, app) in &self.midi_controls.applications {
+            if let Some(cc_num) = mapping_cc(key) {
+                controls.push((cc_num, app.display_label()));
             }
         }
+        if let Some(cc) = self.get_focus_fader_cc() {
+            controls.push((cc, FOCUS_FADER_LABEL.to_string()));
+        }
+        if let Some(selector) = self.get_selector() {
+            controls.push((selector.fader_cc, selector.targets[0].clone()));
+        }
         controls.sort_by_key(|(cc, _)| *cc);
         controls
     }
 
+    /// CC of the focus fader, unless a sink or app mapping already uses it
+    pub fn get_focus_fader_cc(&self) -> Option<u8> {
+        let cc = self.audio.focus_fader_cc?;
+        let key = mapping_key(cc);
+        let mapped = self.midi_controls.sinks.contains_key(&key)
+            || self.midi_controls.applications.contains_key(&key);
+        (!mapped).then_some(cc)
+    }
+
+    /// The selector knob with its fader and apps, if all three are set and the
+    /// fader isn't already a sink, app or focus fader
+    pub fn get_selector(&self) -> Option<Selector> {
+        let knob_cc = self.audio.selector_cc?;
+        let fader_cc = self.audio.selector_fader_cc?;
+        let targets: Vec<String> = self
+            .audio
+            .selector_targets
+            .iter()
+            .flatten()
+            .map(|target| target.trim().to_string())
+            .filter(|target| !target.is_empty())
+            .collect();
+        let key = mapping_key(fader_cc);
+        let taken = self.midi_controls.sinks.contains_key(&key)
+            || self.midi_controls.applications.contains_key(&key)
+            || self.get_focus_fader_cc() == Some(fader_cc);
+        (!targets.is_empty() && !taken).then_some(Selector {
+            knob_cc,
+            fader_cc,
+            targets,
+        })
+    }
+
+    /// Per-fader volume range and curve, keyed by CC
+    pub fn get_fader_settings(&self) -> HashMap<u8, FaderSettings> {
+        let default_curve = self.audio.volume_curve.as_deref().unwrap_or("linear");
+        let exponent = self.get_curve_exponent();
+        self.midi_controls
+            .sinks
+            .iter()
+            .chain(&self.midi_controls.applications)
+            .filter_map(|(key, mapping)| {
+                Some((
+                    mapping_cc(key)?,
+                    mapping.fader_settings(default_curve, exponent),
+                ))
+            })
+            .collect()
+    }
+
+    /// Power of the exponential curve, kept between 1 (linear) and 4
+    pub fn get_curve_exponent(&self) -> f32 {
+        self.audio
+            .volume_curve_exponent
+            .filter(|exponent| exponent.is_finite())
+            .unwrap_or(DEFAULT_CURVE_EXPONENT)
+            .clamp(1.0, 4.0)
+    }
+
+    /// Which matching MIDI port to connect to
+    pub fn get_port_selection(&self) -> PortSelection {
+        PortSelection {
+            name: self.midi.port.clone(),
+            index: self.midi.port_index.unwrap_or(0),
+        }
+    }
+
+    /// UI colors with the `[theme]` overrides applied, plus a message for each
+    /// color that isn't valid hex and was left at its default
+    pub fn get_palette(&self) -> (Palette, Vec<String>) {
+        let mut palette = Palette::default();
+        let mut errors = Vec::new();
+        let overrides = [
+            ("bg", &self.theme.bg, &mut palette.bg),
+            ("accent", &self.theme.accent, &mut palette.accent),
+            ("text", &self.theme.text, &mut palette.text),
+        ];
+        for (key, value, color) in overrides {
+            let Some(value) = value else { continue };
+            match parse_hex_color(value) {
+                Some(parsed) => *color = parsed,
+                None => errors.push(format!(
+                    "[theme] {} = \"{}\" is not a #rrggbb color, using the default",
+                    key, value
+                )),
+            }
+        }
+        (palette, errors)
+    }
+
+    /// File the console is copied to, if one is set
+    pub fn get_log_file_path(&self) -> Option<PathBuf> {
+        let path = self.logging.log_file.as_deref()?.trim();
+        (!path.is_empty()).then(|| PathBuf::from(shellexpand::tilde(path).to_string()))
+    }
+
+    /// Where the control socket listens, if it is enabled
+    pub fn get_control_socket_path(&self) -> Option<PathBuf> {
+        if !self.control.enabled.unwrap_or(false) {
+            return None;
+        }
+        match self.control.socket_path {
+            Some(ref path) => Some(PathBuf::from(shellexpand::tilde(path).to_string())),
+            None => control_socket::default_socket_path(),
+        }
+    }
+
+    /// CCs whose mapping sets an explicit label, plus the focus fader
+    pub fn get_labeled_ccs(&self) -> HashSet<u8> {
+        self.midi_controls
+            .sinks
+            .iter()
+            .chain(&self.midi_controls.applications)
+            .filter(|(_, mapping)| mapping.label.is_some())
+            .filter_map(|(key, _)| mapping_cc(key))
+            .chain(self.get_focus_fader_cc())
+            .collect()
+    }
+
+    /// Accent of each fader with a `color`, plus a message for each color that
+    /// isn't valid hex and leaves its fader in the section color
+    pub fn get_fader_colors(&self) -> (HashMap<u8, Color32>, Vec<String>) {
+        let mut colors = HashMap::new();
+        let mut errors = Vec::new();
+        let sections = [
+            ("sinks", &self.midi_controls.sinks),
+            ("applications", &self.midi_controls.applications),
+        ];
+        for (section, mappings) in sections {
+            for (key, mapping) in mappings {
+                let (Some(cc), Some(value)) = (mapping_cc(key), mapping.color.as_deref()) else {
+                    continue;
+                };
+                match parse_hex_color(value) {
+                    Some(color) => {
+                        colors.insert(cc, color);
+                    }
+                    None => errors.push(format!(
+                        "[midi_controls.{}] {} color = \"{}\" is not a #rrggbb color, using the section color",
+                        section, key, value
+                    )),
+                }
+            }
+        }
+        (colors, errors)
+    }
+
+    /// Backend targets of the sink mappings with `fallback_to_default`
+    pub fn get_default_fallbacks(&self) -> HashSet<String> {
+        self.midi_controls
+            .sinks
+            .values()
+            .filter(|mapping| mapping.fallback_to_default.unwrap_or(false))
+            .map(MappingConfig::resolved_target)
+            .collect()
+    }
+
+    /// Percent each fader with a `startup_level` is set to on launch
+    pub fn get_startup_levels(&self) -> HashMap<u8, u8> {
+        self.midi_controls
+            .sinks
+            .iter()
+            .chain(&self.midi_controls.applications)
+            .filter_map(|(key, mapping)| Some((mapping_cc(key)?, mapping.startup_level?)))
+            .collect()
+    }
+
     pub fn get_mute_button_mappings(&self) -> HashMap<u8, u8> {
         // Returns mapping of mute button CC to target fader CC
         let mut mappings = HashMap::with_capacity(self.midi_controls.mute_buttons.len());
-        for (key, &target_cc) in &self.midi_controls.mute_buttons {
+        for (key, button) in &self.midi_controls.mute_buttons {
             if let Some(cc_str) = key.strip_prefix("cc_") {
                 if let Ok(cc_num) = cc_str.parse::<u8>() {
-                    mappings.insert(cc_num, target_cc);
+                    mappings.insert(cc_num, button.fader);
                 }
             }
         }
         mappings
     }
+
+    /// Solo button CC to the app fader CC it solos
+    pub fn get_solo_button_mappings(&self) -> HashMap<u8, u8> {
+        self.midi_controls
+            .solo_buttons
+            .iter()
+            .filter_map(|(key, &fader)| Some((key.strip_prefix("cc_")?.parse::<u8>().ok()?, fader)))
+            .collect()
+    }
+
+    /// A message for each mute button whose target CC has no fader mapping,
+    /// sorted by button CC
+    pub fn get_mute_button_warnings(&self) -> Vec<String> {
+        let mapped = self.get_cc_mapping();
+        let focus_cc = self.get_focus_fader_cc();
+        let mut unmapped: Vec<(u8, u8)> = self
+            .get_mute_button_mappings()
+            .into_iter()
+            .filter(|&(_, fader)| !mapped.contains_key(&fader) && focus_cc != Some(fader))
+            .collect();
+        unmapped.sort_unstable();
+        unmapped
+            .into_iter()
+            .map(|(button, fader)| {
+                format!(
+                    "Mute button CC {} targets {}, which has no fader mapping",
+                    button,
+                    cc_label(fader)
+                )
+            })
+            .collect()
+    }
+
+    /// Mute buttons that mute only while held down
+    pub fn get_momentary_mute_buttons(&self) -> HashSet<u8> {
+        self.midi_controls
+            .mute_buttons
+            .iter()
+            .filter(|(_, button)| button.is_momentary())
+            .filter_map(|(key, _)| key.strip_prefix("cc_")?.parse::<u8>().ok())
+            .collect()
+    }
 }
 
 impl Default for Config {
@@ -153,9 +783,9 @@ impl Default for Config {
         let mut sinks = HashMap::new();
         sinks.insert(
             "cc_0".to_string(),
-            "alsa_output.pci-0000_25_00.0.analog-stereo".to_string(),
+            MappingConfig::new("alsa_output.pci-0000_25_00.0.analog-stereo"),
         );
-        sinks.insert("cc_1".to_string(), "comms_sink".to_string());
+        sinks.insert("cc_1".to_string(), MappingConfig::new("comms_sink"));
 
         let applications = HashMap::new();
         let mute_buttons = HashMap::new();
@@ -165,14 +795,29 @@ impl Default for Config {
                 sinks,
                 applications,
                 mute_buttons,
+                solo_buttons: HashMap::new(),
             },
+            midi: MidiConfig::default(),
+            theme: ThemeConfig::default(),
+            control: ControlConfig::default(),
             audio: AudioConfig {
                 use_pipewire: Some(true),
                 default_sink: Some("alsa_output.pci-0000_25_00.0.analog-stereo".to_string()),
                 volume_control_mode: Some("pipewire-api".to_string()),
                 volume_curve: Some("linear".to_string()),
+                volume_curve_exponent: Some(DEFAULT_CURVE_EXPONENT),
+                volume_read_mode: Some("percent".to_string()),
                 debounce_ms: Some(10),
                 applications_sink_search: Some(10),
+                mute_hold_ms: Some(500),
+                mute_fade_ms: Some(1500),
+                focus_fader_cc: None,
+                duck_button_cc: None,
+                duck_keep_cc: None,
+                duck_level: Some(20),
+                selector_cc: None,
+                selector_fader_cc: None,
+                selector_targets: None,
             },
             ui: UiConfig {
                 window_width: Some(1000),
@@ -180,11 +825,27 @@ impl Default for Config {
                 theme: Some("default".to_string()),
                 show_console: Some(false),
                 max_console_lines: Some(1000),
+                hide_unavailable: Some(false),
+                decimal_percent: Some(false),
+                color_by_level: Some(false),
+                show_midi_debug: Some(false),
+                confirm_flash: Some(false),
+                max_idle_fps: Some(10),
+                max_fps: Some(0),
                 show_spectrum: Some(true),
                 spectrum_stereo_mode: Some(false),
                 spectrum_show_waterfall: Some(false),
                 spectrum_show_labels: Some(true),
                 spectrum_sink_name: Some("master_sink".to_string()),
+                spectrum_cycle_cc: None,
+                spectrum_weighting: Some("none".to_string()),
+                spectrum_freq_scale: Some("log".to_string()),
+                spectrum_smoothing: Some(20.0),
+                spectrum_peak_decay_ui: Some(2.0),
+                spectrum_scale: Some(1.0),
+                last_tab: Some("control".to_string()),
+                start_minimized: Some(false),
+                window_toggle_cc: None,
             },
             logging: LoggingConfig {
                 enabled: Some(true),
@@ -192,6 +853,8 @@ impl Default for Config {
                 timestamps: Some(true),
                 log_fader_events: Some(true),
                 log_device_info: Some(true),
+                log_latency: Some(false),
+                log_file: None,
             },
         }
     }
@@ -218,20 +881,32 @@ impl Config {
         output.push_str("[midi_controls.sinks]\n");
         output.push_str("# Map MIDI CC numbers to audio sinks (faders)\n");
         output.push_str("# Use CC numbers 0-31 for sink volume controls\n");
+        output.push_str("# Use a table for per-fader options, e.g.\n");
+        output.push_str(
+            "# cc_0 = { target = \"sink_name\", label = \"Speakers\", min = 10, max = 80 }\n",
+        );
+        output.push_str(
+            "# Table keys: target, label, match_mode (name/description/node, role for apps), min, max,\n",
+        );
+        output.push_str(
+            "# curve (linear/exponential/logarithmic), default_level, high_res, invert, channel,\n",
+        );
+        output.push_str(
+            "# allow_boost (fader top goes to 150% instead of 100%), trim_db (calibration gain),\n",
+        );
+        output.push_str(
+            "# startup_level (percent set on every launch), color (\"#rrggbb\" accent),\n",
+        );
+        output.push_str(
+            "# fallback_to_default (control the default sink while this one is absent)\n",
+        );
+        output.push_str(
+            "# Keys named b_cc_N map CC N while bank B is active (see [midi] bank_button_cc)\n",
+        );
         let mut sink_entries: Vec<_> = self.midi_controls.sinks.iter().collect();
-        sink_entries.sort_by(|a, b| {
-            let a_num =
-                a.0.strip_prefix("cc_")
-                    .and_then(|s| s.parse::<u8>().ok())
-                    .unwrap_or(255);
-            let b_num =
-                b.0.strip_prefix("cc_")
-                    .and_then(|s| s.parse::<u8>().ok())
-                    .unwrap_or(255);
-            a_num.cmp(&b_num)
-        });
+        sink_entries.sort_by_key(|(key, _)| mapping_cc(key).unwrap_or(255));
         for (key, value) in sink_entries {
-            output.push_str(&format!("{} = \"{}\"\n", key, value));
+            output.push_str(&format!("{} = {}\n", key, value.to_toml_value()));
         }
         output.push('\n');
 
@@ -240,19 +915,9 @@ impl Config {
         output.push_str("# Map MIDI CC numbers to application names\n");
         output.push_str("# Use CC numbers 32-63 or 16-31 for app volume controls\n");
         let mut app_entries: Vec<_> = self.midi_controls.applications.iter().collect();
-        app_entries.sort_by(|a, b| {
-            let a_num =
-                a.0.strip_prefix("cc_")
-                    .and_then(|s| s.parse::<u8>().ok())
-                    .unwrap_or(255);
-            let b_num =
-                b.0.strip_prefix("cc_")
-                    .and_then(|s| s.parse::<u8>().ok())
-                    .unwrap_or(255);
-            a_num.cmp(&b_num)
-        });
+        app_entries.sort_by_key(|(key, _)| mapping_cc(key).unwrap_or(255));
         for (key, value) in app_entries {
-            output.push_str(&format!("{} = \"{}\"\n", key, value));
+            output.push_str(&format!("{} = {}\n", key, value.to_toml_value()));
         }
         output.push('\n');
 
@@ -265,6 +930,9 @@ impl Config {
             "# Format: cc_BUTTON_CC = FADER_CC_NUMBER (where FADER_CC_NUMBER is an integer)\n",
         );
         output.push_str("# Example: cc_64 = 0 means CC64 button mutes the CC0 fader\n");
+        output.push_str(
+            "# cc_64 = { fader = 0, behavior = \"momentary\" } mutes only while the button is held\n",
+        );
         let mut mute_entries: Vec<_> = self.midi_controls.mute_buttons.iter().collect();
         mute_entries.sort_by(|a, b| {
             let a_num =
@@ -278,10 +946,25 @@ impl Config {
             a_num.cmp(&b_num)
         });
         for (key, value) in mute_entries {
-            output.push_str(&format!("{} = {}\n", key, value));
+            output.push_str(&format!("{} = {}\n", key, value.to_toml_value()));
         }
         output.push('\n');
 
+        // MIDI Controls - Solo Buttons, only written once one is mapped
+        if !self.midi_controls.solo_buttons.is_empty() {
+            output.push_str("[midi_controls.solo_buttons]\n");
+            output.push_str(
+                "# Map solo button CC numbers to an app fader CC; a press mutes every other app\n",
+            );
+            output.push_str("# and the next press unmutes them again\n");
+            let mut solo_entries: Vec<_> = self.midi_controls.solo_buttons.iter().collect();
+            solo_entries.sort_by_key(|(key, _)| mapping_cc(key).unwrap_or(255));
+            for (key, fader) in solo_entries {
+                output.push_str(&format!("{} = {}\n", key, fader));
+            }
+            output.push('\n');
+        }
+
         // Audio section
         output.push_str("[audio]\n");
         output.push_str("# PipeWire settings\n");
@@ -289,19 +972,35 @@ impl Config {
             output.push_str(&format!("use_pipewire = {}\n", use_pipewire));
         }
         if let Some(ref default_sink) = self.audio.default_sink {
-            output.push_str(&format!("default_sink = \"{}\"\n", default_sink));
+            output.push_str(&format!("default_sink = {}\n", toml_quote(default_sink)));
         }
         output.push('\n');
         output.push_str("# Volume control mode:\n");
         output.push_str("# \"pw-volume\"     - Use pw-volume command (default, simple)\n");
         output.push_str("# \"pipewire-api\"  - Use PipeWire Rust API (requires libpipewire-dev)\n");
+        output.push_str(
+            "# \"wpctl\"         - Find app streams with wpctl status, set them by node id\n",
+        );
         if let Some(ref mode) = self.audio.volume_control_mode {
-            output.push_str(&format!("volume_control_mode = \"{}\"\n", mode));
+            output.push_str(&format!("volume_control_mode = {}\n", toml_quote(mode)));
         }
         output.push('\n');
-        output.push_str("# Volume curve (linear/exponential)\n");
+        output.push_str("# Volume curve (linear/exponential/logarithmic)\n");
         if let Some(ref curve) = self.audio.volume_curve {
-            output.push_str(&format!("volume_curve = \"{}\"\n", curve));
+            output.push_str(&format!("volume_curve = {}\n", toml_quote(curve)));
+        }
+        output.push_str(
+            "# Power of the exponential curve (1.0-4.0): higher gives finer low volumes\n",
+        );
+        if let Some(exponent) = self.audio.volume_curve_exponent {
+            output.push_str(&format!("volume_curve_exponent = {:?}\n", exponent));
+        }
+        output.push('\n');
+        output.push_str(
+            "# Read current volumes from pactl's percent (\"percent\") or dB (\"db\") field\n",
+        );
+        if let Some(ref mode) = self.audio.volume_read_mode {
+            output.push_str(&format!("volume_read_mode = {}\n", toml_quote(mode)));
         }
         output.push('\n');
         output.push_str(
@@ -316,6 +1015,52 @@ impl Config {
             output.push_str(&format!("applications_sink_search = {}\n", search));
         }
         output.push('\n');
+        output
+            .push_str("# Mute buttons: a tap mutes instantly, holding longer than mute_hold_ms\n");
+        output.push_str(
+            "# fades the volume to zero over mute_fade_ms (mute_hold_ms = 0 disables fading)\n",
+        );
+        if let Some(hold) = self.audio.mute_hold_ms {
+            output.push_str(&format!("mute_hold_ms = {}\n", hold));
+        }
+        if let Some(fade) = self.audio.mute_fade_ms {
+            output.push_str(&format!("mute_fade_ms = {}\n", fade));
+        }
+        output.push('\n');
+        output
+            .push_str("# Fader CC that follows the most recently started app (unset to disable)\n");
+        if let Some(cc) = self.audio.focus_fader_cc {
+            output.push_str(&format!("focus_fader_cc = {}\n", cc));
+        }
+        output.push('\n');
+        output
+            .push_str("# While duck_button_cc is held, every sink except the duck_keep_cc fader\n");
+        output.push_str("# drops to duck_level percent, and is restored on release\n");
+        if let Some(cc) = self.audio.duck_button_cc {
+            output.push_str(&format!("duck_button_cc = {}\n", cc));
+        }
+        if let Some(cc) = self.audio.duck_keep_cc {
+            output.push_str(&format!("duck_keep_cc = {}\n", cc));
+        }
+        if let Some(level) = self.audio.duck_level {
+            output.push_str(&format!("duck_level = {}\n", level));
+        }
+        output.push('\n');
+        output.push_str(
+            "# Turning the selector_cc knob switches the selector_fader_cc fader between\n",
+        );
+        output.push_str("# the selector_targets apps, splitting the knob travel evenly\n");
+        if let Some(cc) = self.audio.selector_cc {
+            output.push_str(&format!("selector_cc = {}\n", cc));
+        }
+        if let Some(cc) = self.audio.selector_fader_cc {
+            output.push_str(&format!("selector_fader_cc = {}\n", cc));
+        }
+        if let Some(ref targets) = self.audio.selector_targets {
+            let targets: Vec<String> = targets.iter().map(|t| toml_quote(t)).collect();
+            output.push_str(&format!("selector_targets = [{}]\n", targets.join(", ")));
+        }
+        output.push('\n');
 
         // UI section
         output.push_str("[ui]\n");
@@ -327,7 +1072,10 @@ impl Config {
             output.push_str(&format!("window_height = {}\n", height));
         }
         if let Some(ref theme) = self.ui.theme {
-            output.push_str(&format!("theme = \"{}\"  # default, dark, light\n", theme));
+            output.push_str(&format!(
+                "theme = {}  # default, dark, light\n",
+                toml_quote(theme)
+            ));
         }
         output.push('\n');
         output.push_str("# Show console by default\n");
@@ -340,6 +1088,44 @@ impl Config {
             output.push_str(&format!("max_console_lines = {}\n", lines));
         }
         output.push('\n');
+        output
+            .push_str("# Hide faders whose sink/app is unavailable instead of graying them out\n");
+        if let Some(hide) = self.ui.hide_unavailable {
+            output.push_str(&format!("hide_unavailable = {}\n", hide));
+        }
+        output.push('\n');
+        output.push_str("# Show fader percentages with one decimal (0-127 steps don't land on whole percents)\n");
+        if let Some(decimal) = self.ui.decimal_percent {
+            output.push_str(&format!("decimal_percent = {}\n", decimal));
+        }
+        output.push('\n');
+        output.push_str("# Shade fader bars green, yellow then red as volume nears 100%\n");
+        if let Some(by_level) = self.ui.color_by_level {
+            output.push_str(&format!("color_by_level = {}\n", by_level));
+        }
+        output.push('\n');
+        output.push_str("# Show the last raw MIDI value and the applied percent on each fader\n");
+        if let Some(debug) = self.ui.show_midi_debug {
+            output.push_str(&format!("show_midi_debug = {}\n", debug));
+        }
+        output.push('\n');
+        output
+            .push_str("# Briefly pulse a fader bar when the backend confirms its volume was set\n");
+        if let Some(flash) = self.ui.confirm_flash {
+            output.push_str(&format!("confirm_flash = {}\n", flash));
+        }
+        output.push('\n');
+        output.push_str(
+            "# Redraw rate while nothing is changing (MIDI input always redraws instantly)\n",
+        );
+        if let Some(fps) = self.ui.max_idle_fps {
+            output.push_str(&format!("max_idle_fps = {}\n", fps));
+        }
+        output.push_str("# Redraw rate cap while the spectrum or a fade runs (0 = unlimited)\n");
+        if let Some(fps) = self.ui.max_fps {
+            output.push_str(&format!("max_fps = {}\n", fps));
+        }
+        output.push('\n');
         output.push_str("# Spectrum analyzer settings\n");
         if let Some(show) = self.ui.show_spectrum {
             output.push_str(&format!("show_spectrum = {}\n", show));
@@ -353,8 +1139,140 @@ impl Config {
         if let Some(labels) = self.ui.spectrum_show_labels {
             output.push_str(&format!("spectrum_show_labels = {}\n", labels));
         }
+        output.push_str("# Sink whose monitor feeds the spectrum (master_sink = default output)\n");
+        if let Some(ref sink) = self.ui.spectrum_sink_name {
+            output.push_str(&format!("spectrum_sink_name = {}\n", toml_quote(sink)));
+        }
+        output.push_str("# Button CC that switches the spectrum to the next sink's monitor\n");
+        if let Some(cc) = self.ui.spectrum_cycle_cc {
+            output.push_str(&format!("spectrum_cycle_cc = {}\n", cc));
+        }
+        output
+            .push_str("# Band weighting: none or a (A-weighting, closer to perceived loudness)\n");
+        if let Some(ref weighting) = self.ui.spectrum_weighting {
+            output.push_str(&format!("spectrum_weighting = {}\n", toml_quote(weighting)));
+        }
+        output.push_str("# Frequency axis: log or linear\n");
+        if let Some(ref scale) = self.ui.spectrum_freq_scale {
+            output.push_str(&format!("spectrum_freq_scale = {}\n", toml_quote(scale)));
+        }
+        output.push_str("# How fast bars follow the audio and peak markers fall (per second)\n");
+        if let Some(smoothing) = self.ui.spectrum_smoothing {
+            output.push_str(&format!("spectrum_smoothing = {:?}\n", smoothing));
+        }
+        if let Some(decay) = self.ui.spectrum_peak_decay_ui {
+            output.push_str(&format!("spectrum_peak_decay_ui = {:?}\n", decay));
+        }
+        output
+            .push_str("# Size of the spectrum's labels, bar gaps and lines (e.g. 2.0 on HiDPI)\n");
+        if let Some(scale) = self.ui.spectrum_scale {
+            output.push_str(&format!("spectrum_scale = {:?}\n", scale));
+        }
+        output.push('\n');
+        output.push_str("# Tab shown at startup (control/console/settings), saved on exit\n");
+        if let Some(ref tab) = self.ui.last_tab {
+            output.push_str(&format!("last_tab = {}\n", toml_quote(tab)));
+        }
+        output.push('\n');
+        output.push_str("# Launch hidden in the tray (ignored when tray support is unavailable)\n");
+        if let Some(minimized) = self.ui.start_minimized {
+            output.push_str(&format!("start_minimized = {}\n", minimized));
+        }
+        output.push_str("# Button CC that minimizes the window, or restores and raises it\n");
+        if let Some(cc) = self.ui.window_toggle_cc {
+            output.push_str(&format!("window_toggle_cc = {}\n", cc));
+        }
         output.push('\n');
 
+        // MIDI device section, only written once a port has been chosen, thru enabled,
+        // LED feedback turned off or inverted, spurious message filtering turned on or
+        // a bank button set
+        if self.midi.port.is_some()
+            || self.midi.midi_thru == Some(true)
+            || self.midi.led_feedback == Some(false)
+            || self.midi.led_inverted == Some(true)
+            || self.midi.startup_ignore_ms.unwrap_or(0) > 0
+            || self.midi.zero_guard_ms.unwrap_or(0) > 0
+            || self.midi.bank_button_cc.is_some()
+        {
+            output.push_str("[midi]\n");
+            if let Some(ref port) = self.midi.port {
+                output.push_str("# Device port to use when several nanoKontrol2 ports are found\n");
+                output.push_str(&format!("port = {}\n", toml_quote(port)));
+                if let Some(index) = self.midi.port_index {
+                    output.push_str("# Which of several ports with the same name (0 = first)\n");
+                    output.push_str(&format!("port_index = {}\n", index));
+                }
+            }
+            if let Some(thru) = self.midi.midi_thru {
+                output.push_str(
+                    "# Re-emit unmapped MIDI on a virtual \"nanoKontrol2 Thru\" port for other software\n",
+                );
+                output.push_str(&format!("midi_thru = {}\n", thru));
+            }
+            if let Some(led_feedback) = self.midi.led_feedback {
+                output.push_str(
+                    "# Light mute button LEDs (false for input-only devices, no output port needed)\n",
+                );
+                output.push_str(&format!("led_feedback = {}\n", led_feedback));
+            }
+            if let Some(led_inverted) = self.midi.led_inverted {
+                output.push_str("# Light mute LEDs while a fader is active instead of muted\n");
+                output.push_str(&format!("led_inverted = {}\n", led_inverted));
+            }
+            if let Some(ms) = self.midi.startup_ignore_ms {
+                output.push_str("# Ignore all MIDI for this many ms after connecting (0 = off)\n");
+                output.push_str(&format!("startup_ignore_ms = {}\n", ms));
+            }
+            if let Some(ms) = self.midi.zero_guard_ms {
+                output.push_str(
+                    "# Ignore a fader's 0 unless it sent a nonzero value within this many ms (0 = off)\n",
+                );
+                output.push_str(&format!("zero_guard_ms = {}\n", ms));
+            }
+            if let Some(cc) = self.midi.bank_button_cc {
+                output.push_str(
+                    "# Button that switches faders between their cc_N (A) and b_cc_N (B) mappings\n",
+                );
+                output.push_str(&format!("bank_button_cc = {}\n", cc));
+            }
+            output.push('\n');
+        }
+
+        // Control socket section, only written once it has been turned on or given a path
+        if self.control.enabled == Some(true) || self.control.socket_path.is_some() {
+            output.push_str("[control]\n");
+            output.push_str(
+                "# Accept commands like \"set cc 0 80\" or \"mute app firefox\" on a local Unix socket\n",
+            );
+            if let Some(enabled) = self.control.enabled {
+                output.push_str(&format!("enabled = {}\n", enabled));
+            }
+            if let Some(ref path) = self.control.socket_path {
+                output.push_str("# Must be in a directory only you can write to\n");
+                output.push_str(&format!("socket_path = {}\n", toml_quote(path)));
+            }
+            output.push('\n');
+        }
+
+        // Theme colors, only written when at least one is customized
+        let theme = &self.theme;
+        if theme.bg.is_some() || theme.accent.is_some() || theme.text.is_some() {
+            output.push_str("[theme]\n");
+            output
+                .push_str("# Custom colors as \"#rrggbb\"; invalid values fall back to defaults\n");
+            for (key, value) in [
+                ("bg", &theme.bg),
+                ("accent", &theme.accent),
+                ("text", &theme.text),
+            ] {
+                if let Some(value) = value {
+                    output.push_str(&format!("{} = {}\n", key, toml_quote(value)));
+                }
+            }
+            output.push('\n');
+        }
+
         // Logging section
         output.push_str("[logging]\n");
         output.push_str("# Enable or disable logging globally\n");
@@ -364,7 +1282,7 @@ impl Config {
         output.push('\n');
         output.push_str("# Log level: off, error, warn, info, debug, trace\n");
         if let Some(ref level) = self.logging.log_level {
-            output.push_str(&format!("log_level = \"{}\"\n", level));
+            output.push_str(&format!("log_level = {}\n", toml_quote(level)));
         }
         output.push('\n');
         output.push_str("# Show timestamps in console\n");
@@ -381,85 +1299,372 @@ impl Config {
         if let Some(device) = self.logging.log_device_info {
             output.push_str(&format!("log_device_info = {}\n", device));
         }
+        output.push('\n');
+        output.push_str("# Show the average delay from MIDI input to volume applied\n");
+        if let Some(latency) = self.logging.log_latency {
+            output.push_str(&format!("log_latency = {}\n", latency));
+        }
+        if let Some(ref path) = self.logging.log_file {
+            output.push('\n');
+            output.push_str(&format!(
+                "# Also write console messages here, moved to {}.1 at 5 MB\n",
+                path
+            ));
+            output.push_str(&format!("log_file = {}\n", toml_quote(path)));
+        }
 
         Ok(output)
     }
 
-    /// Create a Config from UI state values
-    pub fn from_ui_state(
-        sinks: &[(u8, String)],
-        applications: &[(u8, String)],
-        mute_buttons: &[(u8, u8)],
-        use_pipewire: bool,
-        default_sink: &str,
-        volume_control_mode: &str,
-        volume_curve: &str,
-        debounce_ms: u32,
-        applications_sink_search: u64,
-        window_width: u32,
-        window_height: u32,
-        theme: &str,
-        show_console: bool,
-        max_console_lines: usize,
-        show_spectrum: bool,
-        spectrum_stereo_mode: bool,
-        spectrum_show_waterfall: bool,
-        spectrum_show_labels: bool,
-        spectrum_sink_name: &str,
-        logging_enabled: bool,
-        log_level: &str,
-        timestamps: bool,
-        log_fader_events: bool,
-        log_device_info: bool,
-    ) -> Self {
+    /// Create a Config from the `cfg_*` fields Settings edits
+    pub fn from_ui_state(ui_state: &UiState) -> Self {
         let mut sinks_map = HashMap::new();
-        for (cc, name) in sinks {
-            sinks_map.insert(format!("cc_{}", cc), name.clone());
+        for (cc, mapping) in &ui_state.cfg_sinks {
+            sinks_map.insert(mapping_key(*cc), mapping.clone());
         }
 
         let mut apps_map = HashMap::new();
-        for (cc, name) in applications {
-            apps_map.insert(format!("cc_{}", cc), name.clone());
+        for (cc, mapping) in &ui_state.cfg_applications {
+            apps_map.insert(mapping_key(*cc), mapping.clone());
         }
 
         let mut mute_map = HashMap::new();
-        for (button_cc, fader_cc) in mute_buttons {
-            mute_map.insert(format!("cc_{}", button_cc), *fader_cc);
+        for (button_cc, button) in &ui_state.cfg_mute_buttons {
+            mute_map.insert(format!("cc_{}", button_cc), *button);
         }
 
+        let solo_map = ui_state
+            .cfg_solo_buttons
+            .iter()
+            .map(|(button_cc, fader)| (format!("cc_{}", button_cc), *fader))
+            .collect();
+
         Config {
             midi_controls: MidiControlsConfig {
                 sinks: sinks_map,
                 applications: apps_map,
                 mute_buttons: mute_map,
+                solo_buttons: solo_map,
+            },
+            midi: MidiConfig {
+                port: ui_state.cfg_midi_port.clone(),
+                port_index: ui_state
+                    .cfg_midi_port
+                    .as_ref()
+                    .map(|_| ui_state.cfg_midi_port_index),
+                midi_thru: Some(ui_state.cfg_midi_thru),
+                led_feedback: Some(ui_state.cfg_led_feedback),
+                led_inverted: Some(ui_state.cfg_led_inverted),
+                startup_ignore_ms: Some(ui_state.cfg_startup_ignore_ms),
+                zero_guard_ms: Some(ui_state.cfg_zero_guard_ms),
+                bank_button_cc: ui_state.cfg_bank_button_cc,
             },
             audio: AudioConfig {
-                use_pipewire: Some(use_pipewire),
-                default_sink: Some(default_sink.to_string()),
-                volume_control_mode: Some(volume_control_mode.to_string()),
-                volume_curve: Some(volume_curve.to_string()),
-                debounce_ms: Some(debounce_ms),
-                applications_sink_search: Some(applications_sink_search),
+                use_pipewire: Some(ui_state.cfg_use_pipewire),
+                default_sink: Some(ui_state.cfg_default_sink.clone()),
+                volume_control_mode: Some(ui_state.cfg_volume_control_mode.clone()),
+                volume_curve: Some(ui_state.cfg_volume_curve.clone()),
+                volume_curve_exponent: Some(ui_state.cfg_volume_curve_exponent),
+                volume_read_mode: Some(ui_state.cfg_volume_read_mode.clone()),
+                debounce_ms: Some(ui_state.cfg_debounce_ms),
+                applications_sink_search: Some(ui_state.cfg_applications_sink_search),
+                mute_hold_ms: Some(ui_state.cfg_mute_hold_ms),
+                mute_fade_ms: Some(ui_state.cfg_mute_fade_ms),
+                focus_fader_cc: ui_state.cfg_focus_fader_cc,
+                duck_button_cc: ui_state.cfg_duck_button_cc,
+                duck_keep_cc: ui_state.cfg_duck_keep_cc,
+                duck_level: Some(ui_state.cfg_duck_level),
+                selector_cc: ui_state.cfg_selector_cc,
+                selector_fader_cc: ui_state.cfg_selector_fader_cc,
+                selector_targets: {
+                    let targets: Vec<String> = ui_state
+                        .cfg_selector_targets
+                        .split(',')
+                        .map(|target| target.trim().to_string())
+                        .filter(|target| !target.is_empty())
+                        .collect();
+                    (!targets.is_empty()).then_some(targets)
+                },
             },
             ui: UiConfig {
-                window_width: Some(window_width),
-                window_height: Some(window_height),
-                theme: Some(theme.to_string()),
-                show_console: Some(show_console),
-                max_console_lines: Some(max_console_lines),
-                show_spectrum: Some(show_spectrum),
-                spectrum_stereo_mode: Some(spectrum_stereo_mode),
-                spectrum_show_waterfall: Some(spectrum_show_waterfall),
-                spectrum_show_labels: Some(spectrum_show_labels),
-                spectrum_sink_name: Some(spectrum_sink_name.to_string()),
+                window_width: Some(ui_state.cfg_window_width),
+                window_height: Some(ui_state.cfg_window_height),
+                theme: Some(ui_state.cfg_theme.clone()),
+                show_console: Some(ui_state.cfg_show_console),
+                max_console_lines: Some(ui_state.cfg_max_console_lines),
+                hide_unavailable: Some(ui_state.cfg_hide_unavailable),
+                decimal_percent: Some(ui_state.cfg_decimal_percent),
+                color_by_level: Some(ui_state.cfg_color_by_level),
+                show_midi_debug: Some(ui_state.cfg_show_midi_debug),
+                confirm_flash: Some(ui_state.cfg_confirm_flash),
+                max_idle_fps: Some(ui_state.cfg_max_idle_fps),
+                max_fps: Some(ui_state.cfg_max_fps),
+                show_spectrum: Some(ui_state.cfg_show_spectrum),
+                spectrum_stereo_mode: Some(ui_state.cfg_spectrum_stereo_mode),
+                spectrum_show_waterfall: Some(ui_state.cfg_spectrum_show_waterfall),
+                spectrum_show_labels: Some(ui_state.cfg_spectrum_show_labels),
+                spectrum_sink_name: Some(ui_state.cfg_spectrum_sink_name.clone()),
+                spectrum_cycle_cc: ui_state.cfg_spectrum_cycle_cc,
+                spectrum_weighting: Some(ui_state.cfg_spectrum_weighting.clone()),
+                spectrum_freq_scale: Some(ui_state.cfg_spectrum_freq_scale.clone()),
+                spectrum_smoothing: Some(ui_state.cfg_spectrum_smoothing),
+                spectrum_peak_decay_ui: Some(ui_state.cfg_spectrum_peak_decay_ui),
+                spectrum_scale: Some(ui_state.cfg_spectrum_scale),
+                last_tab: Some(ui_state.selected_tab.as_str().to_string()),
+                start_minimized: Some(ui_state.start_minimized),
+                window_toggle_cc: ui_state.cfg_window_toggle_cc,
             },
             logging: LoggingConfig {
-                enabled: Some(logging_enabled),
-                log_level: Some(log_level.to_string()),
-                timestamps: Some(timestamps),
-                log_fader_events: Some(log_fader_events),
-                log_device_info: Some(log_device_info),
+                enabled: Some(ui_state.cfg_logging_enabled),
+                log_level: Some(ui_state.cfg_log_level.clone()),
+                timestamps: Some(ui_state.cfg_timestamps),
+                log_fader_events: Some(ui_state.cfg_log_fader_events),
+                log_device_info: Some(ui_state.cfg_log_device_info),
+                log_latency: Some(ui_state.cfg_log_latency),
+                log_file: (!ui_state.cfg_log_file.trim().is_empty())
+                    .then(|| ui_state.cfg_log_file.trim().to_string()),
+            },
+            theme: ui_state.cfg_theme_colors.clone(),
+            control: ControlConfig {
+                enabled: Some(ui_state.cfg_control_socket),
+                socket_path: ui_state.cfg_control_socket_path.clone(),
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_with_only_sinks_keeps_mappings() {
+        let config: Config = toml::from_str(
+            r#"
+            [midi_controls.sinks]
+            cc_0 = "master_sink"
+            cc_1 = { target = "comms_sink", label = "Comms" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get_sink_labels(),
+            vec![(0, "master_sink".to_string()), (1, "Comms".to_string())]
+        );
+        assert!(config.audio.default_sink.is_none());
+        assert!(config.ui.window_width.is_none());
+        assert!(config.logging.enabled.is_none());
+    }
+
+    #[test]
+    fn bank_b_mappings_use_their_own_ccs() {
+        let config: Config = toml::from_str(
+            r#"
+            [midi_controls.applications]
+            cc_16 = "firefox"
+            b_cc_16 = "spotify"
+            "#,
+        )
+        .unwrap();
+
+        let mapping = config.get_cc_mapping();
+        assert_eq!(mapping.get(&16).map(String::as_str), Some("firefox"));
+        assert_eq!(
+            mapping.get(&(16 | BANK_B)).map(String::as_str),
+            Some("spotify")
+        );
+        assert_eq!(mapping_key(16 | BANK_B), "b_cc_16");
+        assert_eq!(parse_cc_input("b16"), Some(16 | BANK_B));
+        assert_eq!(mapping_cc("b_cc_128"), None);
+
+        // A bank-B focus fader that already has a mapping stays that mapping
+        let config: Config = toml::from_str(
+            r#"
+            [audio]
+            focus_fader_cc = 144
+
+            [midi_controls.applications]
+            b_cc_16 = "spotify"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.get_focus_fader_cc(), None);
+    }
+
+    #[test]
+    fn role_mappings_target_media_roles() {
+        let config: Config = toml::from_str(
+            r#"
+            [midi_controls.applications]
+            cc_16 = { target = "phone", match_mode = "role" }
+            cc_17 = "role:music"
+            cc_18 = "firefox"
+            "#,
+        )
+        .unwrap();
+
+        let mapping = config.get_cc_mapping();
+        assert_eq!(mapping.get(&16).map(String::as_str), Some("role:phone"));
+        assert_eq!(mapping.get(&17).map(String::as_str), Some("role:music"));
+        assert_eq!(mapping.get(&18).map(String::as_str), Some("firefox"));
+    }
+
+    #[test]
+    fn mute_buttons_without_a_fader_are_flagged() {
+        let config: Config = toml::from_str(
+            r#"
+            [audio]
+            focus_fader_cc = 7
+
+            [midi_controls.sinks]
+            cc_0 = "master_sink"
+
+            [midi_controls.mute_buttons]
+            cc_48 = 0
+            cc_49 = 7
+            cc_50 = 3
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get_mute_button_warnings(),
+            vec!["Mute button CC 50 targets CC3, which has no fader mapping".to_string()]
+        );
+    }
+
+    #[test]
+    fn mapping_colors_parse_or_are_reported() {
+        let config: Config = toml::from_str(
+            r##"
+            [midi_controls.sinks]
+            cc_0 = { target = "game_sink", color = "#64a0dc" }
+            cc_1 = { target = "music_sink", color = "blue" }
+            cc_2 = "comms_sink"
+            "##,
+        )
+        .unwrap();
+
+        let (colors, errors) = config.get_fader_colors();
+        assert_eq!(colors.len(), 1);
+        assert_eq!(colors.get(&0), Some(&Color32::from_rgb(100, 160, 220)));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("cc_1"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn selector_splits_the_knob_between_its_apps() {
+        let config: Config = toml::from_str(
+            r#"
+            [audio]
+            selector_cc = 23
+            selector_fader_cc = 6
+            selector_targets = ["firefox", " spotify ", "", "mpv"]
+            "#,
+        )
+        .unwrap();
+
+        let selector = config.get_selector().unwrap();
+        assert_eq!(selector.targets, vec!["firefox", "spotify", "mpv"]);
+        assert_eq!(selector.index_at(0), 0);
+        assert_eq!(selector.index_at(42), 0);
+        assert_eq!(selector.index_at(43), 1);
+        assert_eq!(selector.index_at(127), 2);
+        assert_eq!(
+            config.get_cc_mapping().get(&6).map(String::as_str),
+            Some("firefox")
+        );
+
+        let mut taken = config.clone();
+        taken
+            .midi_controls
+            .applications
+            .insert("cc_6".to_string(), MappingConfig::new("chrome"));
+        assert!(taken.get_selector().is_none());
+    }
+
+    #[test]
+    fn show_raw_midi_turns_on_the_debug_overlay() {
+        let config: Config = toml::from_str(
+            r#"
+            [ui]
+            show_raw_midi = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.ui.show_midi_debug, Some(true));
+    }
+
+    #[test]
+    fn boosted_faders_go_past_100_percent() {
+        let mut mapping = MappingConfig::new("quiet_app");
+        mapping.max = Some(200);
+        assert_eq!(mapping.fader_settings("linear", 2.0).max, 100);
+
+        mapping.allow_boost = Some(true);
+        assert_eq!(mapping.fader_settings("linear", 2.0).max, BOOST_MAX_PERCENT);
+        mapping.max = None;
+        let settings = mapping.fader_settings("linear", 2.0);
+        assert_eq!(settings.to_percent(127), BOOST_MAX_PERCENT);
+        assert_eq!(settings.to_percent(settings.to_midi(120)), 120);
+
+        // A trim past the Settings range is clamped, and can't pass the fader's top
+        mapping.trim_db = Some(40.0);
+        let settings = mapping.fader_settings("linear", 2.0);
+        assert_eq!(settings.trim_db, 12.0);
+        assert_eq!(settings.to_percent(127), BOOST_MAX_PERCENT);
+        mapping.allow_boost = None;
+        assert_eq!(mapping.fader_settings("linear", 2.0).to_percent(127), 100);
+    }
+
+    #[test]
+    fn quotes_and_backslashes_survive_save_and_load() {
+        let mut config = Config::default();
+        let mut mapping = MappingConfig::new(r#"Chrome "Beta""#);
+        mapping.label = Some(r#"Music \ "Loud""#.to_string());
+        config
+            .midi_controls
+            .applications
+            .insert("cc_16".to_string(), mapping.clone());
+        config.audio.selector_targets = Some(vec![r#"say "hi""#.to_string()]);
+        config.logging.log_file = Some(r"C:\logs\korg.log".to_string());
+
+        let loaded: Config = toml::from_str(&config.to_toml_string().unwrap()).unwrap();
+        assert_eq!(
+            loaded.midi_controls.applications.get("cc_16"),
+            Some(&mapping)
+        );
+        assert_eq!(loaded.audio.selector_targets, config.audio.selector_targets);
+        assert_eq!(loaded.logging.log_file, config.logging.log_file);
+    }
+
+    #[test]
+    fn spectrum_settings_survive_save_and_load() {
+        let mut config = Config::default();
+        config.ui.show_spectrum = Some(false);
+        config.ui.spectrum_stereo_mode = Some(true);
+        config.ui.spectrum_show_waterfall = Some(true);
+        config.ui.spectrum_show_labels = Some(false);
+        config.ui.spectrum_sink_name = Some("comms_sink".to_string());
+        config.ui.spectrum_weighting = Some("a".to_string());
+        config.ui.spectrum_freq_scale = Some("linear".to_string());
+        config.ui.spectrum_smoothing = Some(35.5);
+        config.ui.spectrum_peak_decay_ui = Some(0.5);
+        config.ui.spectrum_scale = Some(1.5);
+
+        let loaded: Config = toml::from_str(&config.to_toml_string().unwrap()).unwrap();
+
+        assert_eq!(loaded.ui.show_spectrum, Some(false));
+        assert_eq!(loaded.ui.spectrum_stereo_mode, Some(true));
+        assert_eq!(loaded.ui.spectrum_show_waterfall, Some(true));
+        assert_eq!(loaded.ui.spectrum_show_labels, Some(false));
+        assert_eq!(loaded.ui.spectrum_sink_name.as_deref(), Some("comms_sink"));
+        assert_eq!(loaded.ui.spectrum_weighting.as_deref(), Some("a"));
+        assert_eq!(loaded.ui.spectrum_freq_scale.as_deref(), Some("linear"));
+        assert_eq!(loaded.ui.spectrum_smoothing, Some(35.5));
+        assert_eq!(loaded.ui.spectrum_peak_decay_ui, Some(0.5));
+        assert_eq!(loaded.ui.spectrum_scale, Some(1.5));
+    }
+}
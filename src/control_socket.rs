@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context, Result};
+use log::error;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// How long a client waits for the UI thread to run its command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Fader a control command applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlTarget {
+    Cc(u8),
+    Sink(String), // Mapping target or label
+    App(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    SetVolume { target: ControlTarget, percent: u8 },
+    SetMute { target: ControlTarget, muted: bool },
+    ToggleMute(ControlTarget),
+}
+
+impl ControlCommand {
+    /// Parse one line such as `set cc 0 80`, `mute app firefox` or `toggle sink master_sink`
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let verb = words.next().ok_or("empty command")?;
+        let args: Vec<&str> = words.collect();
+
+        match verb {
+            "set" => {
+                let (percent, target) = args.split_last().ok_or("usage: set <target> <percent>")?;
+                // Checked against the fader's range once the target is known
+                let percent = percent
+                    .trim_end_matches('%')
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid percent '{}'", percent))?;
+                Ok(ControlCommand::SetVolume {
+                    target: parse_target(target)?,
+                    percent,
+                })
+            }
+            "mute" | "unmute" => Ok(ControlCommand::SetMute {
+                target: parse_target(&args)?,
+                muted: verb == "mute",
+            }),
+            "toggle" => Ok(ControlCommand::ToggleMute(parse_target(&args)?)),
+            _ => Err(format!(
+                "unknown command '{}' (expected set, mute, unmute or toggle)",
+                verb
+            )),
+        }
+    }
+
+    pub fn target(&self) -> &ControlTarget {
+        match self {
+            ControlCommand::SetVolume { target, .. } => target,
+            ControlCommand::SetMute { target, .. } => target,
+            ControlCommand::ToggleMute(target) => target,
+        }
+    }
+}
+
+// `cc <number>`, `sink <name>` or `app <name>`; names may contain spaces
+fn parse_target(words: &[&str]) -> Result<ControlTarget, String> {
+    match words.split_first() {
+        Some((&"cc", [number])) => number
+            .parse()
+            .map(ControlTarget::Cc)
+            .map_err(|_| format!("invalid CC '{}'", number)),
+        Some((&"sink", name)) if !name.is_empty() => Ok(ControlTarget::Sink(name.join(" "))),
+        Some((&"app", name)) if !name.is_empty() => Ok(ControlTarget::App(name.join(" "))),
+        _ => Err("expected a target: cc <number>, sink <name> or app <name>".to_string()),
+    }
+}
+
+/// A parsed command plus the channel the UI thread answers on
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: mpsc::Sender<Result<(), String>>,
+}
+
+/// Socket path used when `[control] socket_path` is unset
+pub fn default_socket_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR").map(|dir| Path::new(&dir).join("korg-midi-volume.sock"))
+}
+
+/// Local Unix socket that accepts one text command per line and answers `ok` or
+/// `error: <reason>`. The socket file is removed when this is dropped
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Bind the socket and start accepting clients; `notify` is called after each
+    /// command is queued so the UI can wake up and run it
+    pub fn start<F>(path: &Path, notify: F) -> Result<(Self, mpsc::Receiver<ControlRequest>)>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        // A socket left behind by a previous run would make bind fail
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(anyhow!("{} exists and is not a socket", path.display()));
+            }
+            fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind control socket {}", path.display()))?;
+        let socket = ControlSocket {
+            path: path.to_path_buf(),
+        };
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+        // Only serve from a directory no other user can write to, so nobody can
+        // swap the socket for their own
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let dir_meta = fs::metadata(dir)?;
+        if dir_meta.uid() != fs::metadata(path)?.uid() || dir_meta.mode() & 0o022 != 0 {
+            return Err(anyhow!(
+                "{} must be a directory owned and writable only by the current user",
+                dir.display()
+            ));
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let notify = Arc::new(notify);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        error!("Control socket error: {}", e);
+                        continue;
+                    }
+                };
+                let tx = tx.clone();
+                let notify = Arc::clone(&notify);
+                thread::spawn(move || {
+                    if let Err(e) = serve_client(stream, &tx, &*notify) {
+                        error!("Control socket client error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok((socket, rx))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn serve_client(
+    stream: UnixStream,
+    tx: &mpsc::Sender<ControlRequest>,
+    notify: &dyn Fn(),
+) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = ControlCommand::parse(&line).and_then(|command| {
+            let (reply_tx, reply_rx) = mpsc::channel();
+            tx.send(ControlRequest {
+                command,
+                reply: reply_tx,
+            })
+            .map_err(|_| "the app is shutting down".to_string())?;
+            notify();
+            reply_rx
+                .recv_timeout(REPLY_TIMEOUT)
+                .map_err(|_| "no response from the app".to_string())?
+        });
+
+        match result {
+            Ok(()) => writeln!(writer, "ok")?,
+            Err(reason) => writeln!(writer, "error: {}", reason)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_volume_and_mute_commands() {
+        assert_eq!(
+            ControlCommand::parse("set cc 0 80"),
+            Ok(ControlCommand::SetVolume {
+                target: ControlTarget::Cc(0),
+                percent: 80
+            })
+        );
+        // Boosted faders go past 100%
+        assert_eq!(
+            ControlCommand::parse("set sink master_sink 150%"),
+            Ok(ControlCommand::SetVolume {
+                target: ControlTarget::Sink("master_sink".to_string()),
+                percent: 150
+            })
+        );
+        assert_eq!(
+            ControlCommand::parse("  mute app Google Chrome "),
+            Ok(ControlCommand::SetMute {
+                target: ControlTarget::App("Google Chrome".to_string()),
+                muted: true
+            })
+        );
+        assert_eq!(
+            ControlCommand::parse("toggle sink master_sink"),
+            Ok(ControlCommand::ToggleMute(ControlTarget::Sink(
+                "master_sink".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        for bad in [
+            "",
+            "louder cc 0",
+            "set cc 0 256",
+            "set cc 300 50",
+            "set 50",
+            "mute app",
+            "mute cc 1 2",
+        ] {
+            assert!(ControlCommand::parse(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,993 @@
+use crate::config::BANK_B;
+use crate::midi::MidiMessage;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const MIDI_TO_PERCENT_FACTOR: f32 = 100.0 / 127.0;
+
+/// Largest value of a 14-bit MSB/LSB controller pair
+const MAX_14BIT: u16 = 16383;
+
+/// Default power of the exponential curve: a plain square
+pub const DEFAULT_CURVE_EXPONENT: f32 = 2.0;
+
+/// Response of volume to fader travel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VolumeCurve {
+    #[default]
+    Linear,
+    Exponential, // Fine control at low volumes
+    Logarithmic, // Fine control at high volumes
+}
+
+impl VolumeCurve {
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "exponential" | "exp" => VolumeCurve::Exponential,
+            "logarithmic" | "log" => VolumeCurve::Logarithmic,
+            _ => VolumeCurve::Linear,
+        }
+    }
+
+    // Fader position (0-1) to volume fraction (0-1); `exponent` shapes the exponential curve
+    fn apply(self, position: f32, exponent: f32) -> f32 {
+        match self {
+            VolumeCurve::Linear => position,
+            VolumeCurve::Exponential => position.powf(exponent),
+            VolumeCurve::Logarithmic => position.sqrt(),
+        }
+    }
+}
+
+/// Per-fader mapping from MIDI value (0-127) to volume percent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaderSettings {
+    pub min: u8, // Percent at the bottom of the fader
+    pub max: u8, // Percent at the top of the fader
+    pub curve: VolumeCurve,
+    pub default_level: Option<u8>, // Percent restored by double-clicking the on-screen fader
+    pub high_res: bool,            // 14-bit fader: MSB on its CC, LSB on CC + 32
+    pub invert: bool,              // Fader mounted upside down: top of travel is silent
+    pub trim_db: f32,              // Calibration gain added on top of the fader level
+    pub top: u8,                   // Percent a positive trim can't go past
+    pub exponent: f32,             // Power of the exponential curve
+}
+
+impl Default for FaderSettings {
+    fn default() -> Self {
+        FaderSettings {
+            min: 0,
+            max: 100,
+            curve: VolumeCurve::Linear,
+            default_level: None,
+            high_res: false,
+            invert: false,
+            trim_db: 0.0,
+            top: 100,
+            exponent: DEFAULT_CURVE_EXPONENT,
+        }
+    }
+}
+
+impl FaderSettings {
+    // Percent at a fader position (0-1). Percents follow PipeWire's cubic volume
+    // scale, where dB = 60 * log10(percent / 100), so the trim scales the percent,
+    // up to the fader's top
+    fn percent_at(self, position: f32) -> f32 {
+        let curve = self.curve.apply(position, self.exponent);
+        let percent = self.min as f32 + curve * (self.max - self.min) as f32;
+        (percent * 10f32.powf(self.trim_db / 60.0)).min(self.top as f32)
+    }
+
+    /// Unrounded percent for display; 127 steps don't map evenly onto 101 whole percents
+    pub fn percent_exact(&self, value: u8) -> f32 {
+        self.percent_at(value.min(127) as f32 * MIDI_TO_PERCENT_FACTOR / 100.0)
+    }
+
+    /// MIDI value to the nearest volume percent
+    pub fn to_percent(self, value: u8) -> u8 {
+        self.percent_exact(value).round() as u8
+    }
+
+    /// Percents the fader can reach, bottom to top, with its range and trim applied
+    pub fn percent_range(self) -> std::ops::RangeInclusive<u8> {
+        self.to_percent(0)..=self.to_percent(127)
+    }
+
+    /// Hardware MIDI value with the fader's direction applied
+    pub fn oriented(self, value: u8) -> u8 {
+        if self.invert {
+            127 - value.min(127)
+        } else {
+            value
+        }
+    }
+
+    // 14-bit counterpart of `oriented`
+    fn oriented_fine(self, value: u16) -> u16 {
+        if self.invert {
+            MAX_14BIT - value.min(MAX_14BIT)
+        } else {
+            value
+        }
+    }
+
+    /// 14-bit value (0-16383) to the nearest volume percent
+    pub fn to_percent_fine(self, value: u16) -> u8 {
+        self.percent_at(value.min(MAX_14BIT) as f32 / MAX_14BIT as f32)
+            .round() as u8
+    }
+
+    /// MIDI value whose volume is closest to `percent`. Searches `percent_exact`, the
+    /// same conversion live input goes through, so a percent survives
+    /// percent -> MIDI -> percent whenever the curve has a step for it
+    pub fn to_midi(self, percent: u8) -> u8 {
+        let target = percent as f32;
+        (0..=127)
+            .min_by(|&a, &b| {
+                let distance = |value| (self.percent_exact(value) - target).abs();
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// What a fader's target name refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Sink,
+    App,
+}
+
+impl TargetKind {
+    pub fn from_is_sink(is_sink: bool) -> Self {
+        if is_sink {
+            TargetKind::Sink
+        } else {
+            TargetKind::App
+        }
+    }
+}
+
+/// Suffix on a sink target that addresses a single channel, e.g. `sink#ch3`.
+/// The config adds it for mappings with `channel = N`
+pub const CHANNEL_SUFFIX: &str = "#ch";
+
+/// Split a sink target into the sink and the channel index it addresses, if any
+pub fn split_channel(target: &str) -> (&str, Option<usize>) {
+    target
+        .rsplit_once(CHANNEL_SUFFIX)
+        .and_then(|(sink, channel)| Some((sink, Some(channel.parse().ok()?))))
+        .unwrap_or((target, None))
+}
+
+/// Which field of the server's volume report a backend reads levels from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VolumeReadMode {
+    #[default]
+    Percent,
+    Decibels, // Finer than the rounded percent at low volumes
+}
+
+impl VolumeReadMode {
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "db" | "decibels" => VolumeReadMode::Decibels,
+            _ => VolumeReadMode::Percent,
+        }
+    }
+}
+
+/// What a scan found for an app mapping
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppStatus {
+    pub input_count: usize,
+    pub volume: Option<u8>, // Average over the app's streams, None if none are playing
+    pub muted: Option<bool>, // Whether every stream is muted, None if none are playing
+    pub display_name: Option<String>,
+}
+
+/// Audio server operations used by the engine and the app. `PipeWireController`
+/// implements it with pactl/wpctl; other backends (e.g. libpulse) can slot in
+pub trait AudioBackend: Send {
+    fn set_volume(&self, kind: TargetKind, target: &str, volume_percent: u8) -> Result<()>;
+    /// Current volume percent, None if the target isn't present or can't be read
+    fn get_volume(&self, kind: TargetKind, target: &str) -> Option<u8>;
+    fn set_mute(&self, kind: TargetKind, target: &str, muted: bool) -> Result<()>;
+    fn list_sinks(&self) -> Vec<String>;
+    fn list_apps(&self) -> Vec<String>;
+
+    /// Number of streams an app mapping currently controls. Called on the
+    /// `applications_sink_search` interval, so backends refresh cached stream lookups here
+    fn app_input_count(&self, _app_name: &str) -> usize {
+        0
+    }
+
+    /// Target for the focus fader: the app whose stream started most recently
+    fn newest_app(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether an app mapping's streams are muted on the server, None if unknown
+    /// or not playing
+    fn app_muted(&self, _app_name: &str) -> Option<bool> {
+        None
+    }
+
+    /// Friendly name of the stream an app mapping matches, if it is playing
+    fn app_display_name(&self, _app_name: &str) -> Option<String> {
+        None
+    }
+
+    /// Streams, level, mute and friendly name of each app mapping, for the
+    /// availability scan. Backends that list every stream at once should override
+    /// this to look at the server once per scan instead of several times per app
+    fn app_statuses(&self, app_names: &[String]) -> Vec<AppStatus> {
+        app_names
+            .iter()
+            .map(|app_name| {
+                let input_count = self.app_input_count(app_name);
+                AppStatus {
+                    input_count,
+                    volume: (input_count > 0)
+                        .then(|| self.get_volume(TargetKind::App, app_name))
+                        .flatten(),
+                    muted: self.app_muted(app_name),
+                    display_name: self.app_display_name(app_name),
+                }
+            })
+            .collect()
+    }
+
+    /// Called on the `applications_sink_search` interval before the availability
+    /// scan; backends drop cached lookups of the server's sinks here
+    fn rescan(&self) {}
+
+    /// Concrete sink name behind a sink mapping, e.g. to find its monitor source
+    fn sink_name(&self, target: &str) -> Option<String> {
+        Some(split_channel(target).0.to_string())
+    }
+
+    /// Read volumes from the percent or the dB field, for backends that report both
+    fn set_read_mode(&mut self, _mode: VolumeReadMode) {}
+
+    /// Drive app streams by PipeWire node id through wpctl, for backends that can
+    fn set_app_wpctl(&mut self, _enabled: bool) {}
+
+    /// Sink targets that stand in for the default sink while they aren't present
+    fn set_default_fallbacks(&mut self, _targets: HashSet<String>) {}
+}
+
+/// A fader move the engine dispatched, for the UI to mirror
+#[derive(Debug, Clone, Copy)]
+pub struct FaderChange {
+    pub cc: u8,
+    pub value: u8, // Raw MIDI value (0-127)
+    pub is_sink: bool,
+}
+
+/// Turns fader CCs into volume changes on the audio backend
+pub struct VolumeEngine {
+    pub backend: Arc<Mutex<dyn AudioBackend>>, // Shared with worker threads
+    pub cc_mapping: HashMap<u8, String>,       // Maps CC number to audio target name
+    pub cc_types: HashMap<u8, bool>,           // Maps CC to is_sink (true=sink, false=app)
+    pub fader_settings: HashMap<u8, FaderSettings>, // Per-CC volume range and curve
+    pub last_volume_values: HashMap<u8, u8>,   // Cache last sent volume for each CC
+    pub last_volume_time: HashMap<u8, Instant>, // Track last volume change time
+    pub debounce_ms: u32,
+    pub log_latency: bool, // Report MIDI receipt to volume applied times, see `try_recv_latency`
+    pub zero_guard_ms: u32, // Ignore a fader's 0 unless it sent nonzero this recently; 0 = off
+    last_nonzero: HashMap<u8, Instant>, // Last nonzero raw value time per fader, for the zero guard
+    high_res_msb: HashMap<u8, u8>, // Last MSB of each 14-bit fader, awaiting its LSB
+    pending: Vec<(u8, u8, Instant)>, // (cc, percent, received) changes waiting for `flush`
+    readback_tx: mpsc::Sender<(u8, u8, u8)>, // Workers report (cc, requested, applied) percent
+    readback_rx: mpsc::Receiver<(u8, u8, u8)>,
+    latency_tx: mpsc::Sender<Duration>,
+    latency_rx: mpsc::Receiver<Duration>,
+}
+
+impl VolumeEngine {
+    pub fn new(
+        backend: Arc<Mutex<dyn AudioBackend>>,
+        cc_mapping: HashMap<u8, String>,
+        cc_types: HashMap<u8, bool>,
+        fader_settings: HashMap<u8, FaderSettings>,
+        debounce_ms: u32,
+    ) -> Self {
+        let (readback_tx, readback_rx) = mpsc::channel();
+        let (latency_tx, latency_rx) = mpsc::channel();
+        VolumeEngine {
+            backend,
+            last_volume_values: HashMap::with_capacity(cc_mapping.len()),
+            last_volume_time: HashMap::with_capacity(cc_mapping.len()),
+            cc_mapping,
+            cc_types,
+            fader_settings,
+            debounce_ms,
+            log_latency: false,
+            zero_guard_ms: 0,
+            last_nonzero: HashMap::new(),
+            high_res_msb: HashMap::new(),
+            pending: Vec::new(),
+            readback_tx,
+            readback_rx,
+            latency_tx,
+            latency_rx,
+        }
+    }
+
+    pub fn is_sink(&self, cc: u8) -> bool {
+        self.cc_types.get(&cc).copied().unwrap_or(true)
+    }
+
+    pub fn kind(&self, cc: u8) -> TargetKind {
+        TargetKind::from_is_sink(self.is_sink(cc))
+    }
+
+    pub fn settings(&self, cc: u8) -> FaderSettings {
+        self.fader_settings.get(&cc).copied().unwrap_or_default()
+    }
+
+    /// CCs this engine consumes: every mapped fader plus the LSB CC of 14-bit ones
+    pub fn handled_ccs(&self) -> HashSet<u8> {
+        let mut ccs: HashSet<u8> = self.cc_mapping.keys().copied().collect();
+        for &cc in self.cc_mapping.keys() {
+            if cc & !BANK_B < 32 && self.settings(cc).high_res {
+                ccs.insert(cc + 32);
+            }
+        }
+        ccs
+    }
+
+    /// Volume percent for a fader's MIDI value, with its range and curve applied
+    pub fn to_percent(&self, cc: u8, value: u8) -> u8 {
+        self.settings(cc).to_percent(value)
+    }
+
+    /// MIDI value that puts a fader at the given volume percent
+    pub fn to_midi(&self, cc: u8, percent: u8) -> u8 {
+        self.settings(cc).to_midi(percent)
+    }
+
+    /// Apply a fader CC received at `received`. Returns None for unmapped CCs and
+    /// debounced repeats. The volume change is queued until `flush`, so faders moved
+    /// together go out as one batch
+    pub fn handle_message(&mut self, msg: MidiMessage, received: Instant) -> Option<FaderChange> {
+        let MidiMessage::ControlChange { cc, value } = msg;
+
+        // `value` becomes the fader position as shown in the UI, i.e. after inversion
+        let (cc, value, percent) = if let Some(msb_cc) = self.high_res_msb_cc(cc) {
+            // LSB of a 14-bit fader refines the MSB that came before it
+            let msb = *self.high_res_msb.get(&msb_cc)?;
+            let settings = self.settings(msb_cc);
+            let fine = settings.oriented_fine((msb as u16) << 7 | value as u16);
+            (msb_cc, (fine >> 7) as u8, settings.to_percent_fine(fine))
+        } else if self.cc_mapping.contains_key(&cc) {
+            if self.spurious_zero(cc, value, received) {
+                return None;
+            }
+            let settings = self.settings(cc);
+            if settings.high_res {
+                // A new MSB implies LSB 0 until the LSB arrives
+                self.high_res_msb.insert(cc, value);
+                let fine = settings.oriented_fine((value as u16) << 7);
+                (cc, (fine >> 7) as u8, settings.to_percent_fine(fine))
+            } else {
+                let value = settings.oriented(value);
+                (cc, value, settings.to_percent(value))
+            }
+        } else {
+            return None;
+        };
+
+        // Debounce: Skip if value hasn't changed or updated too recently
+        let now = received;
+        let should_update = if let Some(&last_val) = self.last_volume_values.get(&cc) {
+            if last_val == percent {
+                false // Same value, skip
+            } else if let Some(&last_time) = self.last_volume_time.get(&cc) {
+                now.duration_since(last_time).as_millis() >= self.debounce_ms as u128
+            } else {
+                true
+            }
+        } else {
+            true // First update
+        };
+
+        if !should_update {
+            return None;
+        }
+
+        // Cache the new value and time
+        self.last_volume_values.insert(cc, percent);
+        self.last_volume_time.insert(cc, now);
+
+        match self.pending.iter_mut().find(|(queued, _, _)| *queued == cc) {
+            Some(queued) => *queued = (cc, percent, received),
+            None => self.pending.push((cc, percent, received)),
+        }
+
+        Some(FaderChange {
+            cc,
+            value,
+            is_sink: self.is_sink(cc),
+        })
+    }
+
+    // Controllers can send a burst of zeros on connect; with the zero guard on, a raw 0
+    // only counts when the fader was seen moving shortly before
+    fn spurious_zero(&mut self, cc: u8, value: u8, received: Instant) -> bool {
+        if self.zero_guard_ms == 0 {
+            return false;
+        }
+        if value > 0 {
+            self.last_nonzero.insert(cc, received);
+            return false;
+        }
+        let guard = Duration::from_millis(self.zero_guard_ms as u64);
+        match self.last_nonzero.get(&cc) {
+            Some(&at) => received.saturating_duration_since(at) > guard,
+            None => true,
+        }
+    }
+
+    /// Forget the last values and times of every fader, so the next move of any
+    /// fader is applied no matter what was sent before
+    pub fn reset_state(&mut self) {
+        self.last_volume_values.clear();
+        self.last_volume_time.clear();
+        self.last_nonzero.clear();
+        self.high_res_msb.clear();
+    }
+
+    /// True if `cc` is a mapped fader or the LSB of a 14-bit one
+    pub fn handles(&self, cc: u8) -> bool {
+        self.cc_mapping.contains_key(&cc) || self.high_res_msb_cc(cc).is_some()
+    }
+
+    // Mapped 14-bit fader whose LSB arrives on this CC
+    fn high_res_msb_cc(&self, cc: u8) -> Option<u8> {
+        let msb_cc = cc.checked_sub(32).filter(|&msb_cc| msb_cc & !BANK_B < 32)?;
+        (self.cc_mapping.contains_key(&msb_cc) && self.settings(msb_cc).high_res).then_some(msb_cc)
+    }
+
+    /// Send a volume to a fader's target on a worker thread to avoid blocking the UI.
+    /// Each volume that is set is reported back, see `try_recv_readback`
+    pub fn set_volume(&self, cc: u8, percent: u8) {
+        self.dispatch(&[(cc, percent, None)]);
+    }
+
+    /// Send every volume queued by `handle_message` since the last flush to a single
+    /// worker, which applies them in order under one backend lock
+    pub fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            let pending: Vec<_> = self
+                .pending
+                .drain(..)
+                .map(|(cc, percent, received)| (cc, percent, Some(received)))
+                .collect();
+            self.dispatch(&pending);
+        }
+    }
+
+    fn dispatch(&self, changes: &[(u8, u8, Option<Instant>)]) {
+        let batch: Vec<_> = changes
+            .iter()
+            .filter_map(|&(cc, percent, received)| {
+                let target = self.cc_mapping.get(&cc)?;
+                Some((cc, self.kind(cc), target.clone(), percent, received))
+            })
+            .collect();
+        if batch.is_empty() {
+            return;
+        }
+        let backend = self.backend.clone();
+        let readback_tx = self.readback_tx.clone();
+        let latency_tx = self.log_latency.then(|| self.latency_tx.clone());
+
+        thread::spawn(move || {
+            if let Ok(backend) = backend.lock() {
+                for (cc, kind, target, percent, received) in batch {
+                    let result = backend.set_volume(kind, &target, percent);
+                    if let (Some(tx), Some(received)) = (&latency_tx, received) {
+                        let _ = tx.send(received.elapsed());
+                    }
+                    if let Err(e) = result {
+                        eprintln!("{}", e);
+                        continue;
+                    }
+                    // Report the level a sink actually ended up at, if it can be read;
+                    // apps are not read back and report the requested level
+                    let applied = match kind {
+                        TargetKind::Sink => backend.get_volume(kind, &target),
+                        TargetKind::App => Some(percent),
+                    };
+                    if let Some(applied) = applied {
+                        let _ = readback_tx.send((cc, percent, applied));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Next (cc, requested, applied) percent report from a volume worker
+    pub fn try_recv_readback(&self) -> Option<(u8, u8, u8)> {
+        self.readback_rx.try_recv().ok()
+    }
+
+    /// Next time from a MIDI message arriving to its volume command returning,
+    /// reported while `log_latency` is set
+    pub fn try_recv_latency(&self) -> Option<Duration> {
+        self.latency_rx.try_recv().ok()
+    }
+}
+
+/// CC lookups built from the sink and app labels, in the order the UI lists them
+#[derive(Debug, Default)]
+pub struct FaderIndex {
+    pub cc_types: HashMap<u8, bool>, // true = sink, false = app
+    pub cc_to_sink_index: HashMap<u8, usize>,
+    pub cc_to_app_index: HashMap<u8, usize>,
+}
+
+impl FaderIndex {
+    pub fn new(sink_labels: &[(u8, String)], app_labels: &[(u8, String)]) -> Self {
+        let mut index = FaderIndex::default();
+        for (i, (cc, _)) in sink_labels.iter().enumerate() {
+            index.cc_types.insert(*cc, true);
+            index.cc_to_sink_index.insert(*cc, i);
+        }
+        for (i, (cc, _)) in app_labels.iter().enumerate() {
+            index.cc_types.insert(*cc, false);
+            index.cc_to_app_index.insert(*cc, i);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[derive(Debug, PartialEq)]
+    enum Call {
+        Sink(String, u8),
+        App(String, u8),
+    }
+
+    impl Call {
+        fn new(kind: TargetKind, target: &str, percent: u8) -> Self {
+            match kind {
+                TargetKind::Sink => Call::Sink(target.to_string(), percent),
+                TargetKind::App => Call::App(target.to_string(), percent),
+            }
+        }
+    }
+
+    /// Records volume calls instead of shelling out to pactl. Sets on "unplugged"
+    /// and "stopped_app" fail and "unreadable" can't be read
+    struct MockBackend {
+        calls: mpsc::Sender<Call>,
+    }
+
+    impl AudioBackend for MockBackend {
+        fn set_volume(&self, kind: TargetKind, target: &str, volume_percent: u8) -> Result<()> {
+            let _ = self.calls.send(Call::new(kind, target, volume_percent));
+            if target == "unplugged" || target == "stopped_app" {
+                return Err(anyhow::anyhow!("Sink '{}' not found", target));
+            }
+            Ok(())
+        }
+
+        fn get_volume(&self, _kind: TargetKind, target: &str) -> Option<u8> {
+            (target != "unreadable").then_some(50)
+        }
+
+        fn set_mute(&self, _kind: TargetKind, _target: &str, _muted: bool) -> Result<()> {
+            Ok(())
+        }
+
+        fn list_sinks(&self) -> Vec<String> {
+            vec!["master_sink".to_string()]
+        }
+
+        fn list_apps(&self) -> Vec<String> {
+            vec!["firefox".to_string()]
+        }
+    }
+
+    fn engine_with_mock() -> (VolumeEngine, mpsc::Receiver<Call>) {
+        let (tx, rx) = mpsc::channel();
+        let backend = Arc::new(Mutex::new(MockBackend { calls: tx }));
+        let cc_mapping =
+            HashMap::from([(0, "master_sink".to_string()), (16, "firefox".to_string())]);
+        let cc_types = HashMap::from([(0, true), (16, false)]);
+        (
+            VolumeEngine::new(backend, cc_mapping, cc_types, HashMap::new(), 0),
+            rx,
+        )
+    }
+
+    #[test]
+    fn config_builds_engine_and_fader_index() {
+        let config: Config = toml::from_str(
+            r#"
+            [midi_controls.sinks]
+            cc_0 = "master_sink"
+            cc_1 = { target = "comms_sink", label = "Comms" }
+
+            [midi_controls.applications]
+            cc_16 = "firefox"
+            cc_17 = "spotify"
+
+            [midi_controls.mute_buttons]
+            cc_48 = 0
+            cc_65 = 17
+            "#,
+        )
+        .unwrap();
+
+        let sink_labels = config.get_sink_labels();
+        let app_labels = config.get_app_labels();
+        let index = FaderIndex::new(&sink_labels, &app_labels);
+        let (tx, _rx) = mpsc::channel();
+        let backend = Arc::new(Mutex::new(MockBackend { calls: tx }));
+        let engine = VolumeEngine::new(
+            backend,
+            config.get_cc_mapping(),
+            index.cc_types.clone(),
+            config.get_fader_settings(),
+            0,
+        );
+
+        assert_eq!(
+            engine.cc_mapping,
+            HashMap::from([
+                (0, "master_sink".to_string()),
+                (1, "comms_sink".to_string()),
+                (16, "firefox".to_string()),
+                (17, "spotify".to_string()),
+            ])
+        );
+        assert_eq!(
+            engine.cc_types,
+            HashMap::from([(0, true), (1, true), (16, false), (17, false)])
+        );
+        assert!(engine.is_sink(1) && !engine.is_sink(17));
+
+        // Sinks and apps are indexed separately, each from 0 in label order
+        assert_eq!(sink_labels[1], (1, "Comms".to_string()));
+        assert_eq!(index.cc_to_sink_index, HashMap::from([(0, 0), (1, 1)]));
+        assert_eq!(index.cc_to_app_index, HashMap::from([(16, 0), (17, 1)]));
+
+        // A mute button can point at an app fader as well as a sink
+        let mute_buttons = config.get_mute_button_mappings();
+        assert_eq!(mute_buttons, HashMap::from([(48, 0), (65, 17)]));
+        assert_eq!(index.cc_to_app_index.get(&mute_buttons[&65]), Some(&1));
+    }
+
+    #[test]
+    fn percent_round_trips_through_midi() {
+        let linear = FaderSettings::default();
+        for percent in 0..=100 {
+            assert_eq!(linear.to_percent(linear.to_midi(percent)), percent);
+        }
+        assert_eq!(linear.to_percent(0), 0);
+        assert_eq!(linear.to_percent(127), 100);
+        assert_eq!(linear.to_midi(100), 127);
+    }
+
+    #[test]
+    fn fader_initialized_from_volume_shows_that_volume() {
+        // A fader placed from a volume must map back to the same percent a live
+        // MIDI value would, for every curve, so it doesn't jump on first touch
+        for curve in [
+            VolumeCurve::Linear,
+            VolumeCurve::Exponential,
+            VolumeCurve::Logarithmic,
+        ] {
+            let settings = FaderSettings {
+                min: 10,
+                max: 90,
+                curve,
+                ..Default::default()
+            };
+            for value in 0..=127 {
+                let percent = settings.to_percent(value);
+                assert_eq!(
+                    settings.to_percent(settings.to_midi(percent)),
+                    percent,
+                    "{:?} at MIDI {}",
+                    curve,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fader_range_and_curve_shape_volume() {
+        let ranged = FaderSettings {
+            min: 20,
+            max: 80,
+            ..Default::default()
+        };
+        assert_eq!(ranged.to_percent(0), 20);
+        assert_eq!(ranged.to_percent(127), 80);
+        assert_eq!(ranged.percent_range(), 20..=80);
+        for percent in 20..=80 {
+            assert_eq!(ranged.to_percent(ranged.to_midi(percent)), percent);
+        }
+
+        let exponential = FaderSettings {
+            curve: VolumeCurve::Exponential,
+            ..Default::default()
+        };
+        assert_eq!(exponential.to_percent(64), 25);
+        assert_eq!(exponential.to_percent(127), 100);
+
+        let cubic = FaderSettings {
+            exponent: 3.0,
+            ..exponential
+        };
+        assert_eq!(cubic.to_percent(64), 13);
+        assert_eq!(cubic.to_percent(127), 100);
+        for value in 0..=127 {
+            let percent = cubic.to_percent(value);
+            assert_eq!(cubic.to_percent(cubic.to_midi(percent)), percent);
+        }
+    }
+
+    #[test]
+    fn trim_offsets_volume_in_decibels() {
+        let trimmed = FaderSettings {
+            trim_db: -6.0,
+            ..Default::default()
+        };
+        // -6 dB on the cubic scale is 100 * 10^(-0.1)
+        assert_eq!(trimmed.to_percent(127), 79);
+        assert_eq!(trimmed.to_percent(0), 0);
+        for percent in [10, 40, 79] {
+            assert_eq!(trimmed.to_percent(trimmed.to_midi(percent)), percent);
+        }
+
+        // A positive trim stops at the fader's top instead of passing the cap
+        let boosted = FaderSettings {
+            trim_db: 12.0,
+            ..Default::default()
+        };
+        assert_eq!(boosted.to_percent(127), 100);
+        assert_eq!(boosted.to_percent(0), 0);
+        let boosted = FaderSettings {
+            top: 150,
+            ..boosted
+        };
+        assert_eq!(boosted.to_percent(127), 150);
+    }
+
+    #[test]
+    fn full_sink_fader_sets_100_percent() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 127 },
+            Instant::now(),
+        );
+        engine.flush();
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::Sink("master_sink".to_string(), 100)
+        );
+    }
+
+    #[test]
+    fn only_applied_and_read_volumes_are_reported_back() {
+        let (mut engine, _calls) = engine_with_mock();
+        for (cc, target, is_sink) in [
+            (1, "unplugged", true),
+            (2, "unreadable", true),
+            (17, "stopped_app", false),
+        ] {
+            engine.cc_mapping.insert(cc, target.to_string());
+            engine.cc_types.insert(cc, is_sink);
+        }
+        engine.dispatch(&[
+            (0, 80, None),
+            (1, 80, None),
+            (2, 80, None),
+            (17, 40, None),
+            (16, 30, None),
+        ]);
+
+        // The batch runs in order, so the app's report comes last
+        let mut readbacks = Vec::new();
+        let deadline = Instant::now() + TIMEOUT;
+        while readbacks.last().map(|&(cc, _, _)| cc) != Some(16) && Instant::now() < deadline {
+            readbacks.extend(engine.try_recv_readback());
+        }
+        assert_eq!(readbacks, vec![(0, 80, 50), (16, 30, 30)]);
+    }
+
+    #[test]
+    fn app_fader_sets_app_volume() {
+        let (mut engine, calls) = engine_with_mock();
+        let change = engine.handle_message(
+            MidiMessage::ControlChange { cc: 16, value: 0 },
+            Instant::now(),
+        );
+        engine.flush();
+        assert!(!change.unwrap().is_sink);
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::App("firefox".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn high_res_fader_combines_msb_and_lsb() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.fader_settings.insert(
+            0,
+            FaderSettings {
+                high_res: true,
+                ..Default::default()
+            },
+        );
+
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 100 },
+            Instant::now(),
+        );
+        engine.flush();
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::Sink("master_sink".to_string(), 78)
+        );
+
+        // LSB on CC 32 moves the fader to 12927 / 16383
+        let change = engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 32, value: 127 },
+                Instant::now(),
+            )
+            .unwrap();
+        engine.flush();
+        assert_eq!((change.cc, change.value), (0, 100));
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::Sink("master_sink".to_string(), 79)
+        );
+    }
+
+    #[test]
+    fn inverted_fader_is_silent_at_the_top() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.fader_settings.insert(
+            0,
+            FaderSettings {
+                invert: true,
+                ..Default::default()
+            },
+        );
+
+        let change = engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 0, value: 127 },
+                Instant::now(),
+            )
+            .unwrap();
+        engine.flush();
+        assert_eq!(change.value, 0);
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::Sink("master_sink".to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn unmapped_cc_is_ignored() {
+        let (mut engine, calls) = engine_with_mock();
+        assert!(engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 7, value: 64 },
+                Instant::now()
+            )
+            .is_none());
+        engine.flush();
+        assert!(calls.recv_timeout(TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn repeated_value_is_debounced() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 64 },
+            Instant::now(),
+        );
+        engine.flush();
+        calls.recv_timeout(TIMEOUT).unwrap();
+        assert!(engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 0, value: 64 },
+                Instant::now()
+            )
+            .is_none());
+        engine.flush();
+        assert!(calls.recv_timeout(TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn zero_guard_ignores_zero_without_prior_motion() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.zero_guard_ms = 200;
+        let start = Instant::now();
+
+        // A lone 0, as sent on connect, is dropped
+        assert!(engine
+            .handle_message(MidiMessage::ControlChange { cc: 0, value: 0 }, start)
+            .is_none());
+
+        // Pulling the fader down reaches 0 shortly after a nonzero value
+        engine.handle_message(MidiMessage::ControlChange { cc: 0, value: 3 }, start);
+        assert!(engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 0, value: 0 },
+                start + Duration::from_millis(50)
+            )
+            .is_some());
+        engine.flush();
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::new(TargetKind::Sink, "master_sink", 0)
+        );
+
+        // Too long after the last motion it counts as spurious again
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 64 },
+            start + Duration::from_millis(100),
+        );
+        assert!(engine
+            .handle_message(
+                MidiMessage::ControlChange { cc: 0, value: 0 },
+                start + Duration::from_millis(400)
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn faders_moved_in_one_frame_are_sent_together() {
+        let (mut engine, calls) = engine_with_mock();
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 64 },
+            Instant::now(),
+        );
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 16, value: 127 },
+            Instant::now(),
+        );
+        engine.handle_message(
+            MidiMessage::ControlChange { cc: 0, value: 127 },
+            Instant::now(),
+        );
+        assert!(calls.recv_timeout(TIMEOUT).is_err());
+
+        // A fader that moved twice only sends its latest level
+        engine.flush();
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::Sink("master_sink".to_string(), 100)
+        );
+        assert_eq!(
+            calls.recv_timeout(TIMEOUT).unwrap(),
+            Call::App("firefox".to_string(), 100)
+        );
+        assert!(calls.recv_timeout(TIMEOUT).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use log::error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+/// Size at which the log file is moved to `<name>.1` and a new one started
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Copy of the console in a file, see `[logging] log_file`. Lines are written
+/// by a worker thread so a slow disk never blocks the UI
+pub struct LogFile {
+    tx: mpsc::Sender<String>,
+}
+
+impl LogFile {
+    /// Open `path` for appending and start the writer thread
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = open_append(path)?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+
+        let (tx, rx) = mpsc::channel::<String>();
+        let worker_path = path.to_path_buf();
+        thread::spawn(move || {
+            let mut writer = BufWriter::new(file);
+            let mut size = size;
+            // Ends once the LogFile is dropped, flushing what is left
+            while let Ok(line) = rx.recv() {
+                let mut result = writer.write_all(line.as_bytes());
+                size += line.len() as u64;
+                // Write out a whole burst before flushing
+                while let Ok(line) = rx.try_recv() {
+                    result = result.and_then(|_| writer.write_all(line.as_bytes()));
+                    size += line.len() as u64;
+                }
+                if let Err(e) = result.and_then(|_| writer.flush()) {
+                    error!("Failed to write log file {}: {}", worker_path.display(), e);
+                }
+
+                if size >= MAX_LOG_BYTES {
+                    match rotate(&worker_path) {
+                        Ok(file) => {
+                            writer = BufWriter::new(file);
+                            size = 0;
+                        }
+                        Err(e) => error!("{:#}", e),
+                    }
+                }
+            }
+        });
+
+        Ok(LogFile { tx })
+    }
+
+    /// Queue one console message, with its time and a level guessed from its marker
+    pub fn write(&self, timestamp: DateTime<Local>, message: &str) {
+        let line = format!(
+            "{} {:<5} {}\n",
+            timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+            level_of(message),
+            message
+        );
+        let _ = self.tx.send(line);
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
+
+// Keep one previous file next to the current one
+fn rotate(path: &Path) -> Result<File> {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    fs::rename(path, &rotated)
+        .with_context(|| format!("Failed to rotate log file {}", path.display()))?;
+    open_append(path)
+}
+
+// Console messages carry no level, but errors and warnings are marked
+fn level_of(message: &str) -> &'static str {
+    if message.starts_with('❌') || message.starts_with("Error") {
+        "ERROR"
+    } else if message.starts_with('⚠') {
+        "WARN"
+    } else {
+        "INFO"
+    }
+}
@@ -1,8 +1,12 @@
 mod app;
 mod config;
+mod control_socket;
+mod engine;
+mod log_file;
 mod midi;
 pub mod panels;
 mod pipewire_control;
+mod selftest;
 pub mod spectrum;
 mod ui;
 
@@ -21,9 +25,25 @@ fn main() -> Result<()> {
         primary_path.clone()
     };
 
-    // Load config with fallback
-    let config = Config::load_with_fallback(&primary_path, &fallback_path)
-        .unwrap_or_else(|_| Config::default());
+    if std::env::args().any(|arg| arg == "--selftest") {
+        let passed = selftest::run(&primary_path, &fallback_path);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
+    // Log volume and mute commands instead of running them, to check mappings safely
+    let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+
+    // Load config with fallback. A file that exists but fails to load is reported
+    // in the window; a missing file just means first run
+    let (config, config_error) = match Config::load_with_fallback(&primary_path, &fallback_path) {
+        Ok(config) => (config, None),
+        Err(e) => {
+            let error = std::path::Path::new(&config_path)
+                .exists()
+                .then(|| format!("{:#}", e));
+            (Config::default(), error)
+        }
+    };
 
     // Only initialize logging if enabled in config
     if config.logging.enabled.unwrap_or(true) {
@@ -49,7 +69,15 @@ fn main() -> Result<()> {
     let _ = eframe::run_native(
         "nanoKontrol2 Volume Controller",
         options,
-        Box::new(|cc| Ok(Box::new(MidiVolumeApp::new(cc, config, config_path)))),
+        Box::new(|cc| {
+            Ok(Box::new(MidiVolumeApp::new(
+                cc,
+                config,
+                config_path,
+                config_error,
+                dry_run,
+            )))
+        }),
     );
 
     Ok(())
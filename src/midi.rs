@@ -1,8 +1,10 @@
 use anyhow::{anyhow, Result};
 use log::error;
-use std::sync::{mpsc, Arc, Mutex};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 pub enum MidiMessage {
@@ -10,7 +12,96 @@ pub enum MidiMessage {
 }
 
 pub struct MidiListener {
-    _tx: mpsc::Sender<MidiMessage>,
+    _tx: mpsc::Sender<(MidiMessage, Instant)>,
+    stop_flag: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+/// How often the listener thread checks whether it should stop
+const STOP_POLL: Duration = Duration::from_millis(100);
+
+/// Which device port to use when several match. `name` picks ports with that exact
+/// name and `index` picks among identical names; unset uses the first match
+#[derive(Debug, Clone, Default)]
+pub struct PortSelection {
+    pub name: Option<String>,
+    pub index: usize,
+}
+
+/// Name of the virtual port `MidiThru` creates
+pub const THRU_PORT_NAME: &str = "nanoKontrol2 Thru";
+
+fn is_device_port(name: &str) -> bool {
+    // Our own thru port would otherwise look like a device
+    if name.contains(THRU_PORT_NAME) {
+        return false;
+    }
+    let lower = name.to_lowercase();
+    lower.contains("nanokontrol") || lower.contains("korg")
+}
+
+// Index into `names` of the port to connect to
+fn select_port(names: &[String], selection: &PortSelection) -> Option<usize> {
+    let matching: Vec<usize> = (0..names.len())
+        .filter(|&i| is_device_port(&names[i]))
+        .collect();
+
+    if let Some(ref wanted) = selection.name {
+        let same_name: Vec<usize> = matching
+            .iter()
+            .copied()
+            .filter(|&i| names[i] == *wanted)
+            .collect();
+        if let Some(&first) = same_name.first() {
+            return Some(same_name.get(selection.index).copied().unwrap_or(first));
+        }
+    }
+
+    matching.first().copied()
+}
+
+/// Names of all MIDI input ports midir can see
+pub fn list_input_ports() -> Vec<String> {
+    let Ok(input) = midir::MidiInput::new("nanoKontrol2 Probe") else {
+        return Vec::new();
+    };
+    input
+        .ports()
+        .iter()
+        .filter_map(|port| input.port_name(port).ok())
+        .collect()
+}
+
+/// Names of all MIDI output ports midir can see
+pub fn list_output_ports() -> Vec<String> {
+    let Ok(output) = midir::MidiOutput::new("nanoKontrol2 Probe") else {
+        return Vec::new();
+    };
+    output
+        .ports()
+        .iter()
+        .filter_map(|port| output.port_name(port).ok())
+        .collect()
+}
+
+/// Name of the input port `MidiListener` would connect to
+pub fn find_input_port(selection: &PortSelection) -> Option<String> {
+    let names = list_input_ports();
+    select_port(&names, selection).map(|i| names[i].clone())
+}
+
+/// Name of the output port `MidiOutput` would connect to
+pub fn find_output_port(selection: &PortSelection) -> Option<String> {
+    let names = list_output_ports();
+    select_port(&names, selection).map(|i| names[i].clone())
+}
+
+/// Names of the input ports that look like a nanoKontrol2, in port order
+pub fn matching_input_ports() -> Vec<String> {
+    list_input_ports()
+        .into_iter()
+        .filter(|name| is_device_port(name))
+        .collect()
 }
 
 // MIDI output controller for sending LED feedback to the device
@@ -19,23 +110,16 @@ pub struct MidiOutput {
 }
 
 impl MidiOutput {
-    pub fn new() -> Result<Self> {
+    pub fn new(selection: &PortSelection) -> Result<Self> {
         let output = midir::MidiOutput::new("nanoKontrol2 Output")
             .map_err(|e| anyhow!("Failed to create MIDI output: {}", e))?;
         let ports = output.ports();
-
-        let port_index = ports
+        let names: Vec<String> = ports
             .iter()
-            .position(|port| {
-                output
-                    .port_name(port)
-                    .ok()
-                    .map(|name| {
-                        let lower = name.to_lowercase();
-                        lower.contains("nanokontrol") || lower.contains("korg")
-                    })
-                    .unwrap_or(false)
-            })
+            .map(|port| output.port_name(port).unwrap_or_default())
+            .collect();
+
+        let port_index = select_port(&names, selection)
             .ok_or_else(|| anyhow!("nanoKontrol2 output not found"))?;
 
         let conn = output
@@ -47,6 +131,13 @@ impl MidiOutput {
         })
     }
 
+    /// Output that sends nothing, for when LED feedback is turned off
+    pub fn disabled() -> Self {
+        MidiOutput {
+            output: Arc::new(Mutex::new(None)),
+        }
+    }
+
     /// Send a Control Change message to light up a button LED
     /// value: 0 = LED off, 127 = LED on
     pub fn send_cc(&self, cc: u8, value: u8) {
@@ -70,75 +161,300 @@ impl MidiOutput {
     }
 }
 
+/// Virtual output port that re-emits the messages the app doesn't use, so a DAW
+/// can sit behind this app and still see the rest of the controller
+pub struct MidiThru {
+    conn: midir::MidiOutputConnection,
+    mapped_ccs: Arc<RwLock<HashSet<u8>>>,
+}
+
+impl MidiThru {
+    /// Create the virtual port; CCs in `mapped_ccs` stay with the app, the
+    /// caller keeps the set current when mappings change
+    pub fn new(mapped_ccs: Arc<RwLock<HashSet<u8>>>) -> Result<Self> {
+        use midir::os::unix::VirtualOutput;
+
+        let output = midir::MidiOutput::new(THRU_PORT_NAME)
+            .map_err(|e| anyhow!("Failed to create MIDI output: {}", e))?;
+        let conn = output
+            .create_virtual(THRU_PORT_NAME)
+            .map_err(|e| anyhow!("Failed to create virtual MIDI port: {}", e))?;
+        Ok(MidiThru { conn, mapped_ccs })
+    }
+
+    fn is_mapped(&self, msg: MidiMessage) -> bool {
+        let MidiMessage::ControlChange { cc, .. } = msg;
+        self.mapped_ccs
+            .read()
+            .map_or(true, |mapped| mapped.contains(&cc))
+    }
+
+    fn forward(&mut self, message: &[u8]) {
+        let _ = self.conn.send(message);
+    }
+}
+
 impl MidiListener {
-    pub fn start() -> Result<(Self, mpsc::Receiver<MidiMessage>)> {
+    /// Start listening; `notify` is called after each message is queued so the
+    /// UI can wake up without polling. Each message carries the time it arrived.
+    /// With `thru`, unmapped messages are forwarded to it instead of being dropped.
+    /// Everything received within `ignore_for` of connecting is discarded
+    pub fn start<F>(
+        selection: PortSelection,
+        thru: Option<MidiThru>,
+        ignore_for: Duration,
+        notify: F,
+    ) -> Result<(Self, mpsc::Receiver<(MidiMessage, Instant)>)>
+    where
+        F: Fn() + Send + 'static,
+    {
         let (tx, rx) = mpsc::channel();
         let tx_clone = tx.clone();
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let thread_stop_flag = Arc::clone(&stop_flag);
 
-        thread::spawn(move || {
-            if let Err(e) = Self::listen_loop(tx_clone) {
+        let handle = thread::spawn(move || {
+            if let Err(e) = Self::listen_loop(
+                tx_clone,
+                &selection,
+                thru,
+                ignore_for,
+                notify,
+                &thread_stop_flag,
+            ) {
                 error!("MIDI listener error: {}", e);
             }
         });
 
-        Ok((MidiListener { _tx: tx }, rx))
+        Ok((
+            MidiListener {
+                _tx: tx,
+                stop_flag,
+                handle: Some(handle),
+            },
+            rx,
+        ))
     }
 
-    fn listen_loop(tx: mpsc::Sender<MidiMessage>) -> Result<()> {
+    /// Close the MIDI connection and wait for the listener thread to finish
+    pub fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn listen_loop<F>(
+        tx: mpsc::Sender<(MidiMessage, Instant)>,
+        selection: &PortSelection,
+        mut thru: Option<MidiThru>,
+        ignore_for: Duration,
+        notify: F,
+        stop_flag: &AtomicBool,
+    ) -> Result<()>
+    where
+        F: Fn() + Send + 'static,
+    {
         let input = midir::MidiInput::new("nanoKontrol2 Input")?;
 
         // Find and connect to nanoKontrol2
         let ports = input.ports();
-
-        let port_index = ports
+        let names: Vec<String> = ports
             .iter()
-            .position(|port| {
-                input
-                    .port_name(port)
-                    .ok()
-                    .map(|name| {
-                        let lower = name.to_lowercase();
-                        lower.contains("nanokontrol") || lower.contains("korg")
-                    })
-                    .unwrap_or(false)
-            })
+            .map(|port| input.port_name(port).unwrap_or_default())
+            .collect();
+
+        let port_index = select_port(&names, selection)
             .ok_or_else(|| anyhow!("nanoKontrol2 device not found"))?;
 
-        // Create a simple callback that logs events
+        // Parse the byte stream; mapped Control Changes go to the app, the rest
+        // to the thru port if there is one
         let tx_clone = tx.clone();
-        let _conn = input
+        let mut parser = MidiParser::default();
+        // Controllers may dump their state on connect, which would jump every fader
+        let ignore_until = Instant::now() + ignore_for;
+        let conn = input
             .connect(
                 &ports[port_index],
                 "korg-volume",
                 move |_stamp: u64, data: &[u8], _: &mut ()| {
-                    if data.len() >= 3 {
-                        let _ = Self::parse_message(data, &tx_clone);
+                    let received = Instant::now();
+                    if received < ignore_until {
+                        return;
+                    }
+                    let mut queued = false;
+                    parser.feed_raw(data, |message| {
+                        let for_app = control_change(message).filter(|&msg| match thru {
+                            Some(ref thru) => thru.is_mapped(msg),
+                            None => true,
+                        });
+                        match (for_app, thru.as_mut()) {
+                            (Some(msg), _) => {
+                                let _ = tx_clone.send((msg, received));
+                                queued = true;
+                            }
+                            (None, Some(thru)) => thru.forward(message),
+                            (None, None) => {}
+                        }
+                    });
+                    if queued {
+                        notify();
                     }
                 },
                 (),
             )
             .map_err(|e| anyhow!("Failed to connect to MIDI: {:?}", e))?;
 
-        // Keep the connection alive indefinitely
-        loop {
-            thread::sleep(Duration::from_secs(1));
+        // Keep the connection alive until the listener is stopped
+        while !stop_flag.load(Ordering::Relaxed) {
+            thread::sleep(STOP_POLL);
         }
+        conn.close();
+        Ok(())
+    }
+}
+
+impl Drop for MidiListener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Incremental MIDI byte-stream parser. Handles running status (data bytes that
+/// reuse the last status byte), 2-byte messages, interleaved real-time bytes and
+/// messages split across callbacks
+#[derive(Debug, Default)]
+pub struct MidiParser {
+    running_status: Option<u8>,
+    data: Vec<u8>,
+    in_sysex: bool,
+}
+
+impl MidiParser {
+    /// Control Change messages on channel 0 completed by `bytes`
+    #[cfg(test)]
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<MidiMessage> {
+        let mut messages = Vec::new();
+        self.feed_raw(bytes, |message| messages.extend(control_change(message)));
+        messages
     }
 
-    fn parse_message(data: &[u8], tx: &mpsc::Sender<MidiMessage>) -> Result<()> {
-        let status = data[0];
-        let controller = data[1];
-        let value = data[2];
+    /// Hand every channel message completed by `bytes` to `on_message`, with its
+    /// status byte restored if it came in under running status
+    pub fn feed_raw(&mut self, bytes: &[u8], mut on_message: impl FnMut(&[u8])) {
+        for &byte in bytes {
+            match byte {
+                // Real-time bytes may appear anywhere and don't affect running status
+                0xF8..=0xFF => {}
+                0xF0 => {
+                    self.in_sysex = true;
+                    self.running_status = None;
+                }
+                0xF7 => self.in_sysex = false,
+                // System common messages cancel running status
+                0xF1..=0xF6 => {
+                    self.in_sysex = false;
+                    self.running_status = None;
+                    self.data.clear();
+                }
+                0x80..=0xEF => {
+                    self.in_sysex = false;
+                    self.running_status = Some(byte);
+                    self.data.clear();
+                }
+                _ => {
+                    if self.in_sysex {
+                        continue;
+                    }
+                    let Some(status) = self.running_status else {
+                        continue;
+                    };
+                    self.data.push(byte);
+                    if self.data.len() < data_length(status) {
+                        continue;
+                    }
 
-        if status == 0xB0 {
-            // Control Change on channel 0 - send all CC messages
-            let msg = MidiMessage::ControlChange {
-                cc: controller,
-                value,
-            };
-            let _ = tx.send(msg);
+                    let mut message = [status, 0, 0];
+                    message[1..=self.data.len()].copy_from_slice(&self.data);
+                    on_message(&message[..=self.data.len()]);
+                    self.data.clear();
+                }
+            }
         }
+    }
+}
 
-        Ok(())
+/// The Control Change a raw message carries, if it is one on channel 0
+pub fn control_change(message: &[u8]) -> Option<MidiMessage> {
+    match *message {
+        [0xB0, cc, value] => Some(MidiMessage::ControlChange { cc, value }),
+        _ => None,
+    }
+}
+
+// Data bytes following a channel status byte
+fn data_length(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1, // Program change, channel pressure
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cc_pairs(messages: Vec<MidiMessage>) -> Vec<(u8, u8)> {
+        messages
+            .into_iter()
+            .map(|MidiMessage::ControlChange { cc, value }| (cc, value))
+            .collect()
+    }
+
+    #[test]
+    fn running_status_reuses_last_status_byte() {
+        let mut parser = MidiParser::default();
+        // One status byte, then three CCs without it; an active-sensing byte in between
+        let stream = [0xB0, 0, 100, 1, 50, 0xFE, 2, 10];
+        assert_eq!(
+            cc_pairs(parser.feed(&stream)),
+            vec![(0, 100), (1, 50), (2, 10)]
+        );
+    }
+
+    #[test]
+    fn messages_split_across_callbacks() {
+        let mut parser = MidiParser::default();
+        assert!(parser.feed(&[0xB0, 7]).is_empty());
+        assert_eq!(cc_pairs(parser.feed(&[64, 8])), vec![(7, 64)]);
+        assert_eq!(cc_pairs(parser.feed(&[127])), vec![(8, 127)]);
+    }
+
+    #[test]
+    fn two_byte_messages_do_not_swallow_following_cc() {
+        let mut parser = MidiParser::default();
+        // Program change (one data byte) followed by a CC
+        let stream = [0xC0, 5, 0xB0, 16, 90];
+        assert_eq!(cc_pairs(parser.feed(&stream)), vec![(16, 90)]);
+    }
+
+    #[test]
+    fn raw_messages_restore_running_status() {
+        let mut parser = MidiParser::default();
+        let mut raw = Vec::new();
+        parser.feed_raw(&[0x91, 60, 100, 62, 90, 0xC0, 5], |message| {
+            raw.push(message.to_vec())
+        });
+        assert_eq!(
+            raw,
+            vec![vec![0x91, 60, 100], vec![0x91, 62, 90], vec![0xC0, 5]]
+        );
+    }
+
+    #[test]
+    fn sysex_and_other_channels_are_ignored() {
+        let mut parser = MidiParser::default();
+        let stream = [0xF0, 0x42, 0x40, 0xF7, 0xB1, 0, 10, 0xB0, 3, 30];
+        assert_eq!(cc_pairs(parser.feed(&stream)), vec![(3, 30)]);
     }
 }
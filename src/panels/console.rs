@@ -1,18 +1,26 @@
 use super::theme;
+use crate::midi::MidiMessage;
+use crate::ui::MidiEvent;
+use chrono::{DateTime, Local};
 use egui::*;
+use std::collections::VecDeque;
 
 pub fn render_console_tab(
-    console_output: &[(String, chrono::DateTime<chrono::Local>)],
+    console_output: &[(String, DateTime<Local>)],
+    midi_events: &VecDeque<MidiEvent>,
+    auto_scroll: &mut bool,
+    midi_table: &mut bool,
+    palette: &theme::Palette,
     ctx: &Context,
 ) {
     CentralPanel::default()
-        .frame(Frame::default().fill(theme::BG_PRIMARY))
+        .frame(Frame::default().fill(palette.bg))
         .show(ctx, |ui| {
             ScrollArea::vertical()
                 .auto_shrink([false; 2])
                 .show(ui, |ui| {
                     Frame::default()
-                        .fill(theme::BG_PRIMARY)
+                        .fill(palette.bg)
                         .inner_margin(Margin {
                             left: 20,
                             right: 20,
@@ -20,12 +28,32 @@ pub fn render_console_tab(
                             bottom: 8,
                         })
                         .show(ui, |ui| {
-                            ui.label(
-                                RichText::new("📋 Console Output")
-                                    .strong()
-                                    .size(16.0)
-                                    .color(theme::ACCENT_GREEN),
-                            );
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new("📋 Console Output")
+                                        .strong()
+                                        .size(16.0)
+                                        .color(theme::ACCENT_GREEN),
+                                );
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    ui.checkbox(
+                                        auto_scroll,
+                                        RichText::new("Auto-scroll")
+                                            .size(12.0)
+                                            .color(theme::TEXT_SECONDARY),
+                                    )
+                                    .on_hover_text("Keep the newest message in view");
+                                    ui.checkbox(
+                                        midi_table,
+                                        RichText::new("MIDI Table")
+                                            .size(12.0)
+                                            .color(theme::TEXT_SECONDARY),
+                                    )
+                                    .on_hover_text(
+                                        "Show received MIDI as a table of messages instead of the log",
+                                    );
+                                });
+                            });
                             ui.add_space(8.0);
                             ui.separator();
                             ui.add_space(8.0);
@@ -45,11 +73,16 @@ pub fn render_console_tab(
                                     // Vertical scroll for logs
                                     ScrollArea::vertical()
                                         .auto_shrink([false; 2])
-                                        .stick_to_bottom(true)
+                                        .stick_to_bottom(*auto_scroll)
                                         .show(ui, |ui| {
                                             ui.set_width(ui.available_width());
                                             ui.style_mut().spacing.item_spacing.y = 4.0;
 
+                                            if *midi_table {
+                                                render_midi_table(ui, midi_events, palette);
+                                                return;
+                                            }
+
                                             // Show all available messages vertically
                                             for (message, timestamp) in console_output {
                                                 ui.horizontal(|ui| {
@@ -58,13 +91,19 @@ pub fn render_console_tab(
                                                             "[{}]",
                                                             timestamp.format("%H:%M:%S")
                                                         ))
-                                                        .color(theme::ACCENT_BLUE)
+                                                        .color(palette.accent)
                                                         .size(10.0)
                                                         .monospace(),
                                                     );
+                                                    // Errors the user must see stand out in red
+                                                    let color = if message.starts_with('❌') {
+                                                        theme::ACCENT_RED
+                                                    } else {
+                                                        palette.text
+                                                    };
                                                     ui.label(
                                                         RichText::new(message)
-                                                            .color(theme::TEXT_PRIMARY)
+                                                            .color(color)
                                                             .size(11.0),
                                                     );
                                                 });
@@ -75,3 +114,65 @@ pub fn render_console_tab(
                 });
         });
 }
+
+/// Received MIDI, one row per message with the time since the one before it
+fn render_midi_table(ui: &mut Ui, events: &VecDeque<MidiEvent>, palette: &theme::Palette) {
+    if events.is_empty() {
+        ui.label(
+            RichText::new("No MIDI received yet")
+                .color(theme::TEXT_MUTED)
+                .size(11.0),
+        );
+        return;
+    }
+
+    let cell = |text: String| {
+        RichText::new(text)
+            .monospace()
+            .size(11.0)
+            .color(palette.text)
+    };
+    Grid::new("midi_events")
+        .num_columns(5)
+        .striped(true)
+        .spacing([24.0, 4.0])
+        .show(ui, |ui| {
+            for heading in ["Time", "+ms", "Type", "CC", "Value"] {
+                ui.label(
+                    RichText::new(heading)
+                        .strong()
+                        .size(11.0)
+                        .color(theme::TEXT_SECONDARY),
+                );
+            }
+            ui.end_row();
+
+            let mut previous: Option<std::time::Instant> = None;
+            for event in events {
+                let (kind, number, value) = match event.message {
+                    MidiMessage::ControlChange { cc, value } => ("Control Change", cc, value),
+                };
+                ui.label(
+                    RichText::new(event.time.format("%H:%M:%S%.3f").to_string())
+                        .monospace()
+                        .size(11.0)
+                        .color(palette.accent),
+                );
+                let delta = previous
+                    .map(|previous| {
+                        event
+                            .received
+                            .saturating_duration_since(previous)
+                            .as_millis()
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                ui.label(cell(delta));
+                ui.label(cell(kind.to_string()));
+                ui.label(cell(number.to_string()));
+                ui.label(cell(value.to_string()));
+                ui.end_row();
+                previous = Some(event.received);
+            }
+        });
+}
@@ -1,5 +1,8 @@
 use super::theme;
-use super::visualizer::render_spectrum_visualizer;
+use super::visualizer::render_spectrum_section;
+use crate::config::cc_label;
+use crate::engine::FaderSettings;
+use crate::ui::{FaderAction, Tab};
 use egui::*;
 
 pub fn render_faders_tab(
@@ -7,9 +10,12 @@ pub fn render_faders_tab(
     ctx: &Context,
 ) -> Vec<(bool, usize, u8)> {
     let mut changed_faders = Vec::new();
+    let mut menu_choice = None;
+    let mut animating = false; // A move highlight or confirm flash is fading
+    let palette = ui_state.palette;
 
     CentralPanel::default()
-        .frame(Frame::default().fill(theme::BG_PRIMARY))
+        .frame(Frame::default().fill(palette.bg))
         .show(ctx, |ui| {
             let total_ccs = ui_state.system_fader_values.len() + ui_state.app_fader_values.len();
 
@@ -17,7 +23,7 @@ pub fn render_faders_tab(
                 .auto_shrink([false; 2])
                 .show(ui, |ui| {
                     Frame::default()
-                        .fill(theme::BG_PRIMARY)
+                        .fill(palette.bg)
                         .inner_margin(Margin {
                             left: 20,
                             right: 20,
@@ -27,60 +33,196 @@ pub fn render_faders_tab(
                         .show(ui, |ui| {
                             ui.set_width(ui.available_width());
 
+                            // The config file failed to load and defaults are in use
+                            if let Some(error) = ui_state.config_error.clone() {
+                                let mut dismissed = false;
+                                ui.add_space(8.0);
+                                Frame::default()
+                                    .fill(theme::BG_SECONDARY)
+                                    .stroke(Stroke::new(1.0, theme::ACCENT_RED))
+                                    .inner_margin(Margin::same(8))
+                                    .corner_radius(CornerRadius::same(4))
+                                    .show(ui, |ui| {
+                                        ui.set_width(ui.available_width());
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                RichText::new(
+                                                    "⚠ Config could not be loaded, using defaults",
+                                                )
+                                                .strong()
+                                                .size(14.0)
+                                                .color(theme::ACCENT_RED),
+                                            );
+                                            ui.with_layout(
+                                                Layout::right_to_left(Align::Center),
+                                                |ui| {
+                                                    if ui
+                                                        .small_button("✖")
+                                                        .on_hover_text("Dismiss")
+                                                        .clicked()
+                                                    {
+                                                        dismissed = true;
+                                                    }
+                                                },
+                                            );
+                                        });
+                                        ui.label(
+                                            RichText::new(error)
+                                                .size(12.0)
+                                                .monospace()
+                                                .color(palette.text),
+                                        );
+                                        ui.label(
+                                            RichText::new(
+                                                "Saving settings replaces the file with the values shown.",
+                                            )
+                                            .size(11.0)
+                                            .color(theme::TEXT_MUTED),
+                                        );
+                                    });
+                                if dismissed {
+                                    ui_state.config_error = None;
+                                }
+                            }
+
                             // Spectrum Visualizer Section
                             if ui_state.cfg_show_spectrum {
                                 ui.add_space(16.0);
-                                render_spectrum_visualizer(
+                                render_spectrum_section(
                                     ui,
-                                    &ui_state.spectrum_data,
-                                    &mut ui_state.visualizer_state,
-                                    true,
-                                    ui_state.cfg_spectrum_stereo_mode,
-                                    ui_state.cfg_spectrum_show_waterfall,
-                                    ui_state.cfg_spectrum_show_labels,
+                                    ui_state,
+                                    "control_spectrum_source",
+                                    false,
                                 );
                                 ui.add_space(8.0);
                                 ui.separator();
                             }
 
-                            // System/Sink Controls Section
-                            if !ui_state.system_fader_values.is_empty() {
-                                ui.add_space(16.0);
-                                render_section_header(ui, "🔊 Audio Sinks", theme::ACCENT_BLUE);
-                                ui.add_space(8.0);
+                            // Fader filter
+                            ui.add_space(12.0);
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new("🔍").color(theme::TEXT_SECONDARY));
+                                ui.add(
+                                    TextEdit::singleline(&mut ui_state.fader_filter)
+                                        .hint_text("Filter faders")
+                                        .desired_width(200.0),
+                                );
+                                if !ui_state.fader_filter.is_empty()
+                                    && ui.small_button("✖").clicked()
+                                {
+                                    ui_state.fader_filter.clear();
+                                }
 
-                                for &display_idx in &ui_state.sink_display_order {
-                                    // Skip if not visible
-                                    if !ui_state
-                                        .sink_visibility
-                                        .get(display_idx)
-                                        .copied()
-                                        .unwrap_or(true)
-                                    {
-                                        continue;
-                                    }
+                                // Which fader bank the hardware currently drives
+                                if let Some(bank) = ui_state.active_bank {
+                                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                        ui.label(
+                                            RichText::new(format!("Bank {}", bank))
+                                                .strong()
+                                                .size(13.0)
+                                                .color(if bank == 'B' {
+                                                    palette.accent
+                                                } else {
+                                                    theme::TEXT_SECONDARY
+                                                }),
+                                        )
+                                        .on_hover_text("Switch banks with the bank button");
+                                    });
+                                }
+                            });
+
+                            let filter = ui_state.fader_filter.trim().to_lowercase();
+                            let matches_filter = |label: &str| {
+                                filter.is_empty() || label.to_lowercase().contains(&filter)
+                            };
 
-                                    let is_muted = ui_state.system_muted[display_idx];
-                                    let is_available = ui_state.system_available[display_idx];
-                                    let old_value = ui_state.system_fader_values[display_idx];
-                                    render_fader_with_mute(
-                                        ui,
-                                        &mut ui_state.system_fader_values[display_idx],
-                                        &ui_state.system_fader_labels[display_idx].1,
-                                        ui_state.system_fader_labels[display_idx].0,
-                                        theme::ACCENT_BLUE,
-                                        is_muted,
-                                        is_available,
-                                        None,
-                                    );
-                                    if old_value != ui_state.system_fader_values[display_idx] {
-                                        changed_faders.push((
-                                            true,
-                                            display_idx,
-                                            ui_state.system_fader_values[display_idx],
-                                        ));
+                            // System/Sink Controls Section
+                            if ui_state
+                                .system_fader_labels
+                                .iter()
+                                .any(|(_, label)| matches_filter(label))
+                            {
+                                ui.add_space(16.0);
+                                let section = CollapsingHeader::new(
+                                    RichText::new("🔊 Audio Sinks")
+                                        .strong()
+                                        .size(16.0)
+                                        .color(palette.accent),
+                                )
+                                .id_salt("sinks_section")
+                                .open(Some(!ui_state.sinks_collapsed))
+                                .show_unindented(ui, |ui| {
+                                    ui.add_space(8.0);
+
+                                    for &display_idx in &ui_state.sink_display_order {
+                                        // Skip if not visible
+                                        if !ui_state
+                                            .sink_visibility
+                                            .get(display_idx)
+                                            .copied()
+                                            .unwrap_or(true)
+                                        {
+                                            continue;
+                                        }
+
+                                        if !matches_filter(
+                                            &ui_state.system_fader_labels[display_idx].1,
+                                        ) {
+                                            continue;
+                                        }
+
+                                        let is_muted = ui_state.system_muted[display_idx];
+                                        let is_available = ui_state.system_available[display_idx];
+                                        if !is_available && ui_state.cfg_hide_unavailable {
+                                            continue;
+                                        }
+                                        let old_value = ui_state.system_fader_values[display_idx];
+                                        let cc = ui_state.system_fader_labels[display_idx].0;
+                                        let settings = ui_state
+                                            .fader_settings
+                                            .get(&cc)
+                                            .copied()
+                                            .unwrap_or_default();
+                                        let debug = midi_debug(ui_state, cc);
+                                        let view = FaderView {
+                                            label: &ui_state.system_fader_labels[display_idx].1,
+                                            cc_num: cc,
+                                            section_color: ui_state
+                                                .fader_colors
+                                                .get(&cc)
+                                                .copied()
+                                                .unwrap_or(palette.accent),
+                                            is_muted,
+                                            is_available,
+                                            settings,
+                                            moved_at: ui_state.fader_moved_at.get(&cc).copied(),
+                                            confirmed_at: ui_state.fader_confirmed_at.get(&cc).copied(),
+                                            decimal_percent: ui_state.cfg_decimal_percent,
+                                            color_by_level: ui_state.cfg_color_by_level,
+                                            input_count: None,
+                                            midi_debug: debug,
+                                        };
+                                        let choice = render_fader_with_mute(
+                                            ui,
+                                            &mut ui_state.system_fader_values[display_idx],
+                                            view,
+                                            &mut animating,
+                                        );
+                                        if let Some(choice) = choice {
+                                            menu_choice = Some((cc, choice));
+                                        }
+                                        if old_value != ui_state.system_fader_values[display_idx] {
+                                            changed_faders.push((
+                                                true,
+                                                display_idx,
+                                                ui_state.system_fader_values[display_idx],
+                                            ));
+                                        }
+                                        ui.add_space(2.0);
                                     }
-                                    ui.add_space(2.0);
+                                });
+                                if section.header_response.clicked() {
+                                    ui_state.sinks_collapsed = !ui_state.sinks_collapsed;
                                 }
 
                                 ui.add_space(8.0);
@@ -88,49 +230,98 @@ pub fn render_faders_tab(
                             }
 
                             // Applications Controls Section
-                            if !ui_state.app_fader_values.is_empty() {
+                            if ui_state
+                                .app_fader_labels
+                                .iter()
+                                .any(|(_, label)| matches_filter(label))
+                            {
                                 ui.add_space(16.0);
-                                render_section_header(ui, "🎵 Applications", theme::ACCENT_ORANGE);
-                                ui.add_space(8.0);
-
-                                for &display_idx in &ui_state.app_display_order {
-                                    // Skip if not visible
-                                    if !ui_state
-                                        .app_visibility
-                                        .get(display_idx)
-                                        .copied()
-                                        .unwrap_or(true)
-                                    {
-                                        continue;
-                                    }
-
-                                    let is_muted = ui_state.app_muted[display_idx];
-                                    let is_available = ui_state.app_available[display_idx];
-                                    let old_value = ui_state.app_fader_values[display_idx];
-                                    render_fader_with_mute(
-                                        ui,
-                                        &mut ui_state.app_fader_values[display_idx],
-                                        &ui_state.app_fader_labels[display_idx].1,
-                                        ui_state.app_fader_labels[display_idx].0,
-                                        theme::ACCENT_ORANGE,
-                                        is_muted,
-                                        is_available,
-                                        Some(
-                                            ui_state
-                                                .app_input_count
-                                                .get(display_idx)
+                                let section = CollapsingHeader::new(
+                                    RichText::new("🎵 Applications")
+                                        .strong()
+                                        .size(16.0)
+                                        .color(theme::ACCENT_ORANGE),
+                                )
+                                .id_salt("apps_section")
+                                .open(Some(!ui_state.apps_collapsed))
+                                .show_unindented(ui, |ui| {
+                                    ui.add_space(8.0);
+
+                                    for &display_idx in &ui_state.app_display_order {
+                                        // Skip if not visible
+                                        if !ui_state
+                                            .app_visibility
+                                            .get(display_idx)
+                                            .copied()
+                                            .unwrap_or(true)
+                                        {
+                                            continue;
+                                        }
+
+                                        if !matches_filter(
+                                            &ui_state.app_fader_labels[display_idx].1,
+                                        ) {
+                                            continue;
+                                        }
+
+                                        let is_muted = ui_state.app_muted[display_idx];
+                                        let is_available = ui_state.app_available[display_idx];
+                                        if !is_available && ui_state.cfg_hide_unavailable {
+                                            continue;
+                                        }
+                                        let old_value = ui_state.app_fader_values[display_idx];
+                                        let cc = ui_state.app_fader_labels[display_idx].0;
+                                        let settings = ui_state
+                                            .fader_settings
+                                            .get(&cc)
+                                            .copied()
+                                            .unwrap_or_default();
+                                        let debug = midi_debug(ui_state, cc);
+                                        let view = FaderView {
+                                            label: &ui_state.app_fader_labels[display_idx].1,
+                                            cc_num: cc,
+                                            section_color: ui_state
+                                                .fader_colors
+                                                .get(&cc)
                                                 .copied()
-                                                .unwrap_or(0),
-                                        ),
-                                    );
-                                    if old_value != ui_state.app_fader_values[display_idx] {
-                                        changed_faders.push((
-                                            false,
-                                            display_idx,
-                                            ui_state.app_fader_values[display_idx],
-                                        ));
+                                                .unwrap_or(theme::ACCENT_ORANGE),
+                                            is_muted,
+                                            is_available,
+                                            settings,
+                                            moved_at: ui_state.fader_moved_at.get(&cc).copied(),
+                                            confirmed_at: ui_state.fader_confirmed_at.get(&cc).copied(),
+                                            decimal_percent: ui_state.cfg_decimal_percent,
+                                            color_by_level: ui_state.cfg_color_by_level,
+                                            input_count: Some(
+                                                ui_state
+                                                    .app_input_count
+                                                    .get(display_idx)
+                                                    .copied()
+                                                    .unwrap_or(0),
+                                            ),
+                                            midi_debug: debug,
+                                        };
+                                        let choice = render_fader_with_mute(
+                                            ui,
+                                            &mut ui_state.app_fader_values[display_idx],
+                                            view,
+                                            &mut animating,
+                                        );
+                                        if let Some(choice) = choice {
+                                            menu_choice = Some((cc, choice));
+                                        }
+                                        if old_value != ui_state.app_fader_values[display_idx] {
+                                            changed_faders.push((
+                                                false,
+                                                display_idx,
+                                                ui_state.app_fader_values[display_idx],
+                                            ));
+                                        }
+                                        ui.add_space(12.0);
                                     }
-                                    ui.add_space(12.0);
+                                });
+                                if section.header_response.clicked() {
+                                    ui_state.apps_collapsed = !ui_state.apps_collapsed;
                                 }
 
                                 ui.add_space(8.0);
@@ -152,30 +343,338 @@ pub fn render_faders_tab(
                 }); // Close ScrollArea
         }); // Close CentralPanel
 
+    match menu_choice {
+        Some((cc, MenuChoice::Action(action))) => ui_state.fader_action = Some((cc, action)),
+        Some((cc, MenuChoice::EditMapping)) => {
+            ui_state.selected_tab = Tab::Settings;
+            ui_state.edit_mapping_cc = Some(cc);
+        }
+        Some((cc, MenuChoice::Remove)) => ui_state.remove_mapping_cc = Some(cc),
+        None => {}
+    }
+    confirm_remove(ui_state, ctx);
+    // The app schedules the next frame, within max_fps
+    ui_state.fader_animating = animating;
+
+    changed_faders
+}
+
+// Removing a mapping saves the config right away, so it waits for a confirmation
+fn confirm_remove(ui_state: &mut crate::ui::UiState, ctx: &Context) {
+    let Some(cc) = ui_state.remove_mapping_cc else {
+        return;
+    };
+    let label = ui_state
+        .system_fader_labels
+        .iter()
+        .chain(&ui_state.app_fader_labels)
+        .find(|(mapped, _)| *mapped == cc)
+        .map_or_else(String::new, |(_, label)| label.clone());
+
+    let mut answer = None;
+    Window::new("Remove mapping?")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Remove {} [{}] from the config? This saves the config.",
+                label,
+                cc_label(cc)
+            ));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("🗑 Remove").clicked() {
+                    answer = Some(true);
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                    answer = Some(false);
+                }
+            });
+        });
+
+    if let Some(remove) = answer {
+        ui_state.remove_mapping_cc = None;
+        if remove {
+            ui_state.fader_action = Some((cc, FaderAction::Remove));
+        }
+    }
+}
+
+/// Drag speed multiplier while Shift is held on a fader
+const FINE_ADJUST_FACTOR: f32 = 0.2;
+
+/// Fader steps per scroll-wheel notch, and with Shift held
+const SCROLL_STEP: u8 = 1;
+const SCROLL_STEP_COARSE: u8 = 5;
+
+/// How long a fader's border stays highlighted after a hardware move
+const MOVE_HIGHLIGHT_SECS: f32 = 0.5;
+
+/// How long a fader's bar pulses once the backend confirms its volume
+const CONFIRM_FLASH_SECS: f32 = 0.3;
+
+/// Width reserved for labels in the mini view
+const MINI_LABEL_WIDTH: f32 = 90.0;
+
+/// Condensed Control view for mini mode: one thin bar per visible fader, no headers
+pub fn render_mini_faders(
+    ui_state: &mut crate::ui::UiState,
+    ctx: &Context,
+) -> Vec<(bool, usize, u8)> {
+    let mut changed_faders = Vec::new();
+    let palette = ui_state.palette;
+
+    CentralPanel::default()
+        .frame(
+            Frame::default()
+                .fill(palette.bg)
+                .inner_margin(Margin::same(6)),
+        )
+        .show(ctx, |ui| {
+            ui.style_mut().spacing.item_spacing.y = 3.0;
+
+            for &display_idx in &ui_state.sink_display_order {
+                if !ui_state
+                    .sink_visibility
+                    .get(display_idx)
+                    .copied()
+                    .unwrap_or(true)
+                {
+                    continue;
+                }
+                let is_available = ui_state.system_available[display_idx];
+                if !is_available && ui_state.cfg_hide_unavailable {
+                    continue;
+                }
+
+                let color = if ui_state.system_muted[display_idx] || !is_available {
+                    theme::TEXT_MUTED
+                } else {
+                    level_color_or(ui_state, display_idx, true, palette.accent)
+                };
+                if render_mini_bar(
+                    ui,
+                    &mut ui_state.system_fader_values[display_idx],
+                    &ui_state.system_fader_labels[display_idx].1,
+                    color,
+                ) {
+                    changed_faders.push((
+                        true,
+                        display_idx,
+                        ui_state.system_fader_values[display_idx],
+                    ));
+                }
+            }
+
+            for &display_idx in &ui_state.app_display_order {
+                if !ui_state
+                    .app_visibility
+                    .get(display_idx)
+                    .copied()
+                    .unwrap_or(true)
+                {
+                    continue;
+                }
+                let is_available = ui_state.app_available[display_idx];
+                if !is_available && ui_state.cfg_hide_unavailable {
+                    continue;
+                }
+
+                let color = if ui_state.app_muted[display_idx] || !is_available {
+                    theme::TEXT_MUTED
+                } else {
+                    level_color_or(ui_state, display_idx, false, theme::ACCENT_ORANGE)
+                };
+                if render_mini_bar(
+                    ui,
+                    &mut ui_state.app_fader_values[display_idx],
+                    &ui_state.app_fader_labels[display_idx].1,
+                    color,
+                ) {
+                    changed_faders.push((
+                        false,
+                        display_idx,
+                        ui_state.app_fader_values[display_idx],
+                    ));
+                }
+            }
+        });
+
     changed_faders
 }
 
-fn render_section_header(ui: &mut Ui, title: &str, color: Color32) {
+// Level color of a mini bar when `color_by_level` is on, otherwise its mapping's
+// color or else its section color
+fn level_color_or(
+    ui_state: &crate::ui::UiState,
+    display_idx: usize,
+    is_sink: bool,
+    section_color: Color32,
+) -> Color32 {
+    let (labels, values) = if is_sink {
+        (&ui_state.system_fader_labels, &ui_state.system_fader_values)
+    } else {
+        (&ui_state.app_fader_labels, &ui_state.app_fader_values)
+    };
+    let cc = labels[display_idx].0;
+    if !ui_state.cfg_color_by_level {
+        return ui_state
+            .fader_colors
+            .get(&cc)
+            .copied()
+            .unwrap_or(section_color);
+    }
+    let settings = ui_state
+        .fader_settings
+        .get(&cc)
+        .copied()
+        .unwrap_or_default();
+    theme::level_color(settings.to_percent(values[display_idx]))
+}
+
+/// Thin label + bar row; click or drag on the bar to set the value
+fn render_mini_bar(ui: &mut Ui, fader_value: &mut u8, label: &str, color: Color32) -> bool {
+    let old_value = *fader_value;
+
     ui.horizontal(|ui| {
-        ui.add_space(4.0);
-        ui.label(RichText::new(title).strong().size(16.0).color(color));
+        ui.add_sized(
+            [MINI_LABEL_WIDTH, 14.0],
+            Label::new(RichText::new(label).size(10.0).color(color)).truncate(),
+        );
+
+        let (rect, response) =
+            ui.allocate_exact_size(vec2(ui.available_width(), 8.0), Sense::click_and_drag());
+        if let Some(pos) = response.interact_pointer_pos() {
+            let t = ((pos.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+            *fader_value = (t * 127.0).round() as u8;
+        }
+
+        ui.painter().rect_filled(rect, 3.0, theme::BG_TERTIARY);
+        let filled_width = rect.width() * (*fader_value as f32 / 127.0);
+        if filled_width > 0.5 {
+            let filled_rect = Rect::from_min_size(rect.min, vec2(filled_width, rect.height()));
+            ui.painter().rect_filled(filled_rect, 3.0, color);
+        }
     });
+
+    old_value != *fader_value
 }
 
-fn render_fader_with_mute(
+/// Choice made in a fader's right-click menu. Edit mapping opens Settings and
+/// Remove asks for a confirmation here; actions go to the app through
+/// `UiState::fader_action`
+enum MenuChoice {
+    Action(FaderAction),
+    EditMapping,
+    Remove,
+}
+
+// Right-click menu of a fader. Reset to default is applied to the fader directly
+fn fader_menu(
     ui: &mut Ui,
     fader_value: &mut u8,
-    label: &str,
+    settings: FaderSettings,
+    is_muted: bool,
+    is_app: bool,
+    choice: &mut Option<MenuChoice>,
+) {
+    if ui
+        .button(if is_muted { "🔊 Unmute" } else { "🔇 Mute" })
+        .clicked()
+    {
+        *choice = Some(MenuChoice::Action(FaderAction::ToggleMute));
+    }
+    if is_app && ui.button("🎧 Solo").clicked() {
+        *choice = Some(MenuChoice::Action(FaderAction::Solo));
+    }
+    if ui
+        .add_enabled(
+            settings.default_level.is_some(),
+            Button::new("↺ Reset to default"),
+        )
+        .on_disabled_hover_text("Set default_level on the mapping to enable")
+        .clicked()
+    {
+        if let Some(level) = settings.default_level {
+            *fader_value = settings.to_midi(level);
+        }
+    }
+    if ui.button("✏ Edit mapping…").clicked() {
+        *choice = Some(MenuChoice::EditMapping);
+    }
+    ui.separator();
+    if ui.button("🗑 Remove…").clicked() {
+        *choice = Some(MenuChoice::Remove);
+    }
+}
+
+// Last raw value and applied percent for a fader, when the debug overlay is on
+fn midi_debug(ui_state: &crate::ui::UiState, cc: u8) -> Option<(Option<u8>, Option<u8>)> {
+    ui_state.cfg_show_midi_debug.then(|| {
+        (
+            ui_state.raw_midi_values.get(&cc).copied(),
+            ui_state.applied_percents.get(&cc).copied(),
+        )
+    })
+}
+
+// Everything a fader card shows besides its value
+struct FaderView<'a> {
+    label: &'a str,
     cc_num: u8,
     section_color: Color32,
     is_muted: bool,
     is_available: bool,
-    input_count: Option<usize>,
-) {
+    settings: FaderSettings,
+    moved_at: Option<std::time::Instant>,
+    confirmed_at: Option<std::time::Instant>,
+    decimal_percent: bool,
+    color_by_level: bool,
+    input_count: Option<usize>, // Only set for app faders
+    midi_debug: Option<(Option<u8>, Option<u8>)>, // Raw CC value and applied percent
+}
+
+fn render_fader_with_mute(
+    ui: &mut Ui,
+    fader_value: &mut u8,
+    view: FaderView,
+    animating: &mut bool,
+) -> Option<MenuChoice> {
+    let FaderView {
+        label,
+        cc_num,
+        section_color,
+        is_muted,
+        is_available,
+        settings,
+        moved_at,
+        confirmed_at,
+        decimal_percent,
+        color_by_level,
+        input_count,
+        midi_debug,
+    } = view;
+
+    // Level colors replace the section color, warning before a target gets too loud
+    let section_color = if color_by_level {
+        theme::level_color(settings.to_percent(*fader_value))
+    } else {
+        section_color
+    };
+
+    // Brighten the border after a hardware move, fading back over MOVE_HIGHLIGHT_SECS
+    let highlight = moved_at
+        .map(|at| 1.0 - at.elapsed().as_secs_f32() / MOVE_HIGHLIGHT_SECS)
+        .unwrap_or(0.0)
+        .max(0.0);
+    *animating |= highlight > 0.0;
+    let border_color = theme::BORDER.lerp_to_gamma(section_color, highlight);
+
     // Container for each fader
-    Frame::default()
+    let frame = Frame::default()
         .fill(theme::BG_SECONDARY)
-        .stroke(Stroke::new(1.0, theme::BORDER))
+        .stroke(Stroke::new(1.0 + highlight, border_color))
         .inner_margin(Margin {
             left: 20,
             right: 20,
@@ -186,7 +685,7 @@ fn render_fader_with_mute(
         .show(ui, |ui| {
             ui.vertical(|ui| {
                 // Header with label and mute icon
-                ui.horizontal(|ui| {
+                let header = ui.horizontal(|ui| {
                     let mute_icon = if is_muted { "🔇" } else { "🔊" };
                     let label_color = if !is_available {
                         theme::TEXT_MUTED
@@ -200,13 +699,15 @@ fn render_fader_with_mute(
                     ui.label(RichText::new(label).strong().size(13.0).color(label_color));
 
                     ui.add_space(4.0);
-                    ui.label(RichText::new(format!("[CC{}]", cc_num)).size(10.0).color(
-                        if is_available {
-                            theme::TEXT_MUTED
-                        } else {
-                            Color32::from_rgb(60, 60, 70)
-                        },
-                    ));
+                    ui.label(
+                        RichText::new(format!("[{}]", cc_label(cc_num)))
+                            .size(10.0)
+                            .color(if is_available {
+                                theme::TEXT_MUTED
+                            } else {
+                                Color32::from_rgb(60, 60, 70)
+                            }),
+                    );
 
                     if let Some(count) = input_count {
                         if count > 0 {
@@ -217,27 +718,41 @@ fn render_fader_with_mute(
                             );
                         }
                     }
+
+                    if let Some((raw, applied)) = midi_debug {
+                        let show =
+                            |value: Option<u8>| value.map_or("–".to_string(), |v| v.to_string());
+                        ui.label(
+                            RichText::new(format!("raw {} → {}%", show(raw), show(applied)))
+                                .monospace()
+                                .size(10.0)
+                                .color(theme::TEXT_MUTED),
+                        );
+                    }
                 });
 
                 ui.add_space(2.0);
 
                 // Fader slider
                 let value = *fader_value;
-                let percent = (value as f32 / 127.0 * 100.0) as u8;
+                let percent = settings.to_percent(value);
+                let percent_text = if decimal_percent {
+                    format!("{:.1}%", settings.percent_exact(value))
+                } else {
+                    format!("{}%", percent)
+                };
 
                 let fader_color = if is_muted {
                     theme::TEXT_MUTED
+                } else if percent > 100 {
+                    theme::ACCENT_RED
                 } else {
                     section_color
                 };
 
                 // Volume percentage display at the front
                 ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(format!("{}%", percent))
-                            .color(fader_color)
-                            .size(11.0),
-                    );
+                    ui.label(RichText::new(percent_text).color(fader_color).size(11.0));
 
                     if is_muted {
                         ui.add_space(4.0);
@@ -278,7 +793,48 @@ fn render_fader_with_mute(
                     ui.style_mut().visuals.widgets.hovered.bg_stroke =
                         Stroke::new(2.0, slider_handle_color);
 
-                    ui.add(Slider::new(fader_value, 0..=127).show_value(false).text(""));
+                    let value_before = *fader_value;
+                    let slider_response =
+                        ui.add(Slider::new(fader_value, 0..=127).show_value(false).text(""));
+
+                    // Outline the bar for a moment once its volume was applied
+                    let flash = confirmed_at
+                        .map(|at| 1.0 - at.elapsed().as_secs_f32() / CONFIRM_FLASH_SECS)
+                        .unwrap_or(0.0)
+                        .max(0.0);
+                    if flash > 0.0 {
+                        ui.painter().rect_stroke(
+                            slider_response.rect.expand(2.0),
+                            CornerRadius::same(3),
+                            Stroke::new(1.5, theme::ACCENT_GREEN.gamma_multiply(flash)),
+                            StrokeKind::Outside,
+                        );
+                        *animating = true;
+                    }
+
+                    // Double-click snaps back to the mapping's default level
+                    if slider_response.double_clicked() {
+                        if let Some(level) = settings.default_level {
+                            *fader_value = settings.to_midi(level);
+                        }
+                    }
+
+                    // Shift + drag: move relative to the pointer at a fraction of the speed.
+                    // The sub-step position is kept in temp memory between frames
+                    let fine_id = slider_response.id.with("fine_adjust");
+                    if slider_response.dragged() && ui.input(|i| i.modifiers.shift) {
+                        let steps_per_px =
+                            127.0 / slider_response.rect.width().max(1.0) * FINE_ADJUST_FACTOR;
+                        let position: f32 = ui
+                            .data(|d| d.get_temp(fine_id))
+                            .unwrap_or(value_before as f32);
+                        let position = (position + slider_response.drag_delta().x * steps_per_px)
+                            .clamp(0.0, 127.0);
+                        ui.data_mut(|d| d.insert_temp(fine_id, position));
+                        *fader_value = position.round() as u8;
+                    } else {
+                        ui.data_mut(|d| d.remove::<f32>(fine_id));
+                    }
 
                     ui.add_space(4.0);
 
@@ -290,22 +846,91 @@ fn render_fader_with_mute(
 
                 ui.add_space(4.0);
 
-                // Visual bar display
+                // Visual bar display, spanning up to the boost limit on boosted faders
                 let bar_width = ui.available_width();
                 let bar_height = 7.0;
-                let filled_width = bar_width * (percent as f32 / 100.0);
+                let full_scale = settings.max.max(100) as f32;
+                let filled_width = bar_width * (percent as f32 / full_scale);
+                let unity_x = bar_width * (100.0 / full_scale);
 
-                let (rect, _response) =
-                    ui.allocate_exact_size(vec2(bar_width, bar_height), Sense::hover());
+                let (rect, bar_response) =
+                    ui.allocate_exact_size(vec2(bar_width, bar_height), Sense::click());
 
                 // Background bar
                 ui.painter().rect_filled(rect, 3.0, theme::BG_TERTIARY);
 
-                // Filled bar
+                // Filled bar, red past 100%
                 if filled_width > 0.5 {
-                    let filled_rect = Rect::from_min_size(rect.min, vec2(filled_width, bar_height));
-                    ui.painter().rect_filled(filled_rect, 3.0, fader_color);
+                    let unity_width = filled_width.min(unity_x);
+                    let bar_color = if is_muted {
+                        theme::TEXT_MUTED
+                    } else {
+                        section_color
+                    };
+                    let filled_rect = Rect::from_min_size(rect.min, vec2(unity_width, bar_height));
+                    ui.painter().rect_filled(filled_rect, 3.0, bar_color);
+                    if filled_width > unity_x {
+                        let boost_rect = Rect::from_min_max(
+                            rect.min + vec2(unity_x, 0.0),
+                            rect.min + vec2(filled_width, bar_height),
+                        );
+                        ui.painter().rect_filled(boost_rect, 3.0, fader_color);
+                    }
                 }
-            });
+
+                // 100% mark on boosted faders
+                if settings.max > 100 {
+                    let x = rect.min.x + unity_x;
+                    ui.painter()
+                        .vline(x, rect.y_range(), Stroke::new(1.0, theme::TEXT_SECONDARY));
+                }
+
+                (header.response, bar_response)
+            })
+            .inner
         });
+    let frame_response = frame.response;
+
+    // Right-click on the header or the bar opens the fader's actions
+    let (header_response, bar_response) = frame.inner;
+    let mut choice = None;
+    for response in [header_response.interact(Sense::click()), bar_response] {
+        response.context_menu(|ui| {
+            fader_menu(
+                ui,
+                fader_value,
+                settings,
+                is_muted,
+                input_count.is_some(),
+                &mut choice,
+            );
+        });
+    }
+
+    // Scroll wheel over the fader nudges it by 1 (5 with Shift)
+    if frame_response.contains_pointer() {
+        let (delta, shift) = ui.input(|i| (i.raw_scroll_delta, i.modifiers.shift));
+        // egui turns Shift + wheel into horizontal scrolling
+        let scroll = if shift { delta.x + delta.y } else { delta.y };
+        if scroll != 0.0 {
+            let step = if shift {
+                SCROLL_STEP_COARSE
+            } else {
+                SCROLL_STEP
+            };
+            *fader_value = if scroll > 0.0 {
+                fader_value.saturating_add(step).min(127)
+            } else {
+                fader_value.saturating_sub(step)
+            };
+        }
+
+        // Keep the surrounding ScrollArea from scrolling as well
+        ui.input_mut(|i| {
+            i.raw_scroll_delta = Vec2::ZERO;
+            i.smooth_scroll_delta = Vec2::ZERO;
+        });
+    }
+
+    choice
 }
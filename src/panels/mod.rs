@@ -5,7 +5,7 @@ pub mod theme;
 pub mod visualizer;
 
 pub use console::render_console_tab;
-pub use control::render_faders_tab;
+pub use control::{render_faders_tab, render_mini_faders};
 pub use settings::{render_midi_ui_modal, render_settings_tab};
 pub use theme::*;
-pub use visualizer::{render_spectrum_visualizer, VisualizerState};
+pub use visualizer::{render_spectrum_tab, render_spectrum_visualizer, VisualizerState};
@@ -1,14 +1,23 @@
 use super::theme;
+use super::visualizer::render_spectrum_source_picker;
+use crate::config::{
+    cc_label, parse_cc_input, MappingConfig, MuteBehavior, MuteButtonConfig, TRIM_DB_RANGE,
+};
+use crate::pipewire_control::ImportCandidate;
 use crate::ui::UiState;
 use egui::{
-    CentralPanel, Color32, Context, CornerRadius, Frame, Margin, RichText, ScrollArea, Stroke,
+    Align, CentralPanel, Color32, Context, CornerRadius, Frame, Margin, RichText, ScrollArea,
+    Stroke,
 };
 
-pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_functional: bool) -> bool {
+pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, tray_functional: bool) -> bool {
     let mut settings_changed = false;
+    let palette = ui_state.palette;
+    // Mapping row picked with "Edit mapping…" on the Control tab
+    let edit_cc = ui_state.edit_mapping_cc.take();
 
     CentralPanel::default()
-        .frame(Frame::default().fill(theme::BG_PRIMARY))
+        .frame(Frame::default().fill(palette.bg))
         .show(ctx, |ui| {
             ScrollArea::vertical()
                 .auto_shrink([false; 2])
@@ -20,7 +29,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                             top: 8,
                             bottom: 8,
                         })
-                        .fill(theme::BG_PRIMARY)
+                        .fill(palette.bg)
                         .show(ui, |ui| {
                             // Show save message if present
                             if let Some((msg, instant)) = &ui_state.settings_save_message {
@@ -37,7 +46,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                             // ===== MIDI CONTROLS SECTION =====
                             ui.add_space(8.0);
-                            render_section_header(ui, "MIDI Controls", theme::ACCENT_BLUE);
+                            render_section_header(ui, "MIDI Controls", palette.accent);
                             ui.add_space(8.0);
 
                             // --- Sink Mappings ---
@@ -56,29 +65,56 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                     ui.label(
                                         RichText::new("Audio Sinks (CC -> Sink Name)")
                                             .size(14.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     ui.add_space(8.0);
 
                                     let mut to_remove_sink: Option<usize> = None;
-                                    for (idx, (cc, name)) in
+                                    for (idx, (cc, mapping)) in
                                         ui_state.cfg_sinks.iter_mut().enumerate()
                                     {
                                         ui.horizontal(|ui| {
                                             ui.label(
-                                                RichText::new(format!("CC {}:", cc))
+                                                RichText::new(format!("{}:", cc_label(*cc)))
                                                     .size(12.0)
                                                     .color(theme::TEXT_SECONDARY),
                                             );
-                                            let old_name = name.clone();
-                                            ui.add(
-                                                egui::TextEdit::singleline(name)
+                                            let old_mapping = mapping.clone();
+                                            let target_response = ui.add(
+                                                egui::TextEdit::singleline(&mut mapping.target)
                                                     .desired_width(200.0),
                                             );
-                                            if *name != old_name {
+                                            if edit_cc == Some(*cc) {
+                                                target_response.request_focus();
+                                                ui.scroll_to_cursor(Some(Align::Center));
+                                            }
+
+                                            // Optional display label; empty means use the target
+                                            let mut label =
+                                                mapping.label.clone().unwrap_or_default();
+                                            ui.add(
+                                                egui::TextEdit::singleline(&mut label)
+                                                    .desired_width(110.0)
+                                                    .hint_text("Label"),
+                                            );
+                                            mapping.label =
+                                                (!label.trim().is_empty()).then_some(label);
+                                            render_fader_options(ui, mapping, true);
+
+                                            if *mapping != old_mapping {
                                                 ui_state.settings_dirty = true;
                                                 settings_changed = true;
                                             }
+                                            if ui
+                                                .small_button("🔊")
+                                                .on_hover_text(
+                                                    "Identify: play a test tone on this sink",
+                                                )
+                                                .clicked()
+                                            {
+                                                ui_state.identify_sink =
+                                                    Some(mapping.target.clone());
+                                            }
                                             if ui.small_button("🗑").clicked() {
                                                 to_remove_sink = Some(idx);
                                                 ui_state.settings_dirty = true;
@@ -103,18 +139,22 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                             egui::TextEdit::singleline(&mut ui_state.new_sink_cc)
                                                 .desired_width(40.0)
                                                 .hint_text("CC"),
-                                        );
+                                        )
+                                        .on_hover_text("Prefix with b for bank B, e.g. b0");
                                         ui.add(
                                             egui::TextEdit::singleline(&mut ui_state.new_sink_name)
                                                 .desired_width(150.0)
                                                 .hint_text("Sink name"),
                                         );
                                         if ui.button("➕ Add").clicked() {
-                                            if let Ok(cc) = ui_state.new_sink_cc.parse::<u8>() {
+                                            if let Some(cc) = parse_cc_input(&ui_state.new_sink_cc) {
                                                 if !ui_state.new_sink_name.is_empty() {
-                                                    ui_state
-                                                        .cfg_sinks
-                                                        .push((cc, ui_state.new_sink_name.clone()));
+                                                    ui_state.cfg_sinks.push((
+                                                        cc,
+                                                        MappingConfig::new(
+                                                            ui_state.new_sink_name.clone(),
+                                                        ),
+                                                    ));
                                                     ui_state.cfg_sinks.sort_by_key(|(cc, _)| *cc);
                                                     ui_state.new_sink_cc.clear();
                                                     ui_state.new_sink_name.clear();
@@ -144,26 +184,43 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                     ui.label(
                                         RichText::new("Applications (CC -> App Name)")
                                             .size(14.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     ui.add_space(8.0);
 
                                     let mut to_remove_app: Option<usize> = None;
-                                    for (idx, (cc, name)) in
+                                    for (idx, (cc, mapping)) in
                                         ui_state.cfg_applications.iter_mut().enumerate()
                                     {
                                         ui.horizontal(|ui| {
                                             ui.label(
-                                                RichText::new(format!("CC {}:", cc))
+                                                RichText::new(format!("{}:", cc_label(*cc)))
                                                     .size(12.0)
                                                     .color(theme::TEXT_SECONDARY),
                                             );
-                                            let old_name = name.clone();
-                                            ui.add(
-                                                egui::TextEdit::singleline(name)
+                                            let old_mapping = mapping.clone();
+                                            let target_response = ui.add(
+                                                egui::TextEdit::singleline(&mut mapping.target)
                                                     .desired_width(200.0),
                                             );
-                                            if *name != old_name {
+                                            if edit_cc == Some(*cc) {
+                                                target_response.request_focus();
+                                                ui.scroll_to_cursor(Some(Align::Center));
+                                            }
+
+                                            // Optional display label; empty means use the target
+                                            let mut label =
+                                                mapping.label.clone().unwrap_or_default();
+                                            ui.add(
+                                                egui::TextEdit::singleline(&mut label)
+                                                    .desired_width(110.0)
+                                                    .hint_text("Label"),
+                                            );
+                                            mapping.label =
+                                                (!label.trim().is_empty()).then_some(label);
+                                            render_fader_options(ui, mapping, false);
+
+                                            if *mapping != old_mapping {
                                                 ui_state.settings_dirty = true;
                                                 settings_changed = true;
                                             }
@@ -191,18 +248,22 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                             egui::TextEdit::singleline(&mut ui_state.new_app_cc)
                                                 .desired_width(40.0)
                                                 .hint_text("CC"),
-                                        );
+                                        )
+                                        .on_hover_text("Prefix with b for bank B, e.g. b0");
                                         ui.add(
                                             egui::TextEdit::singleline(&mut ui_state.new_app_name)
                                                 .desired_width(150.0)
                                                 .hint_text("App name"),
                                         );
                                         if ui.button("➕ Add").clicked() {
-                                            if let Ok(cc) = ui_state.new_app_cc.parse::<u8>() {
+                                            if let Some(cc) = parse_cc_input(&ui_state.new_app_cc) {
                                                 if !ui_state.new_app_name.is_empty() {
-                                                    ui_state
-                                                        .cfg_applications
-                                                        .push((cc, ui_state.new_app_name.clone()));
+                                                    ui_state.cfg_applications.push((
+                                                        cc,
+                                                        MappingConfig::new(
+                                                            ui_state.new_app_name.clone(),
+                                                        ),
+                                                    ));
                                                     ui_state
                                                         .cfg_applications
                                                         .sort_by_key(|(cc, _)| *cc);
@@ -218,6 +279,14 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                             ui.add_space(8.0);
 
+                            // --- Import from pavucontrol names ---
+                            if render_import_mappings(ui, ui_state) {
+                                ui_state.settings_dirty = true;
+                                settings_changed = true;
+                            }
+
+                            ui.add_space(8.0);
+
                             // --- Mute Button Mappings ---
                             Frame::default()
                                 .fill(theme::BG_SECONDARY)
@@ -234,23 +303,50 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                     ui.label(
                                         RichText::new("Mute Buttons (Button CC -> Fader CC)")
                                             .size(14.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     ui.add_space(8.0);
 
+                                    for warning in &ui_state.mute_button_warnings {
+                                        ui.label(
+                                            RichText::new(format!("⚠ {}", warning))
+                                                .size(12.0)
+                                                .color(theme::ACCENT_YELLOW),
+                                        );
+                                    }
+                                    if !ui_state.mute_button_warnings.is_empty() {
+                                        ui.add_space(4.0);
+                                    }
+
                                     let mut to_remove_mute: Option<usize> = None;
-                                    for (idx, (button_cc, fader_cc)) in
-                                        ui_state.cfg_mute_buttons.iter().enumerate()
+                                    for (idx, (button_cc, button)) in
+                                        ui_state.cfg_mute_buttons.iter_mut().enumerate()
                                     {
                                         ui.horizontal(|ui| {
                                             ui.label(
                                                 RichText::new(format!(
                                                     "CC {} -> CC {}",
-                                                    button_cc, fader_cc
+                                                    button_cc, button.fader
                                                 ))
                                                 .size(12.0)
                                                 .color(theme::TEXT_SECONDARY),
                                             );
+                                            let mut momentary = button.is_momentary();
+                                            if ui
+                                                .checkbox(
+                                                    &mut momentary,
+                                                    RichText::new("Momentary")
+                                                        .size(12.0)
+                                                        .color(theme::TEXT_MUTED),
+                                                )
+                                                .on_hover_text("Mute only while the button is held")
+                                                .changed()
+                                            {
+                                                button.behavior =
+                                                    momentary.then_some(MuteBehavior::Momentary);
+                                                ui_state.settings_dirty = true;
+                                                settings_changed = true;
+                                            }
                                             if ui.small_button("🗑").clicked() {
                                                 to_remove_mute = Some(idx);
                                                 ui_state.settings_dirty = true;
@@ -291,7 +387,9 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                                 ui_state.new_mute_button_cc.parse::<u8>(),
                                                 ui_state.new_mute_fader_cc.parse::<u8>(),
                                             ) {
-                                                ui_state.cfg_mute_buttons.push((btn_cc, fader_cc));
+                                                ui_state
+                                                    .cfg_mute_buttons
+                                                    .push((btn_cc, MuteButtonConfig::new(fader_cc)));
                                                 ui_state
                                                     .cfg_mute_buttons
                                                     .sort_by_key(|(cc, _)| *cc);
@@ -302,6 +400,77 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                             }
                                         }
                                     });
+
+                                    ui.add_space(12.0);
+                                    ui.label(
+                                        RichText::new("Solo Buttons (Button CC -> App Fader CC)")
+                                            .size(14.0)
+                                            .color(palette.text),
+                                    );
+                                    ui.add_space(8.0);
+
+                                    let mut to_remove_solo: Option<usize> = None;
+                                    for (idx, (button_cc, fader_cc)) in
+                                        ui_state.cfg_solo_buttons.iter().enumerate()
+                                    {
+                                        ui.horizontal(|ui| {
+                                            ui.label(
+                                                RichText::new(format!(
+                                                    "CC {} -> CC {}",
+                                                    button_cc, fader_cc
+                                                ))
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                            );
+                                            if ui.small_button("🗑").clicked() {
+                                                to_remove_solo = Some(idx);
+                                                ui_state.settings_dirty = true;
+                                                settings_changed = true;
+                                            }
+                                        });
+                                    }
+                                    if let Some(idx) = to_remove_solo {
+                                        ui_state.cfg_solo_buttons.remove(idx);
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Add:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_MUTED),
+                                        );
+                                        ui.add(
+                                            egui::TextEdit::singleline(
+                                                &mut ui_state.new_solo_button_cc,
+                                            )
+                                            .desired_width(50.0)
+                                            .hint_text("Btn CC"),
+                                        );
+                                        ui.label(RichText::new("->").color(theme::TEXT_MUTED));
+                                        ui.add(
+                                            egui::TextEdit::singleline(
+                                                &mut ui_state.new_solo_fader_cc,
+                                            )
+                                            .desired_width(50.0)
+                                            .hint_text("App CC"),
+                                        );
+                                        if ui.button("➕ Add").clicked() {
+                                            if let (Ok(btn_cc), Ok(fader_cc)) = (
+                                                ui_state.new_solo_button_cc.parse::<u8>(),
+                                                ui_state.new_solo_fader_cc.parse::<u8>(),
+                                            ) {
+                                                ui_state.cfg_solo_buttons.retain(|(cc, _)| *cc != btn_cc);
+                                                ui_state.cfg_solo_buttons.push((btn_cc, fader_cc));
+                                                ui_state.cfg_solo_buttons.sort();
+                                                ui_state.new_solo_button_cc.clear();
+                                                ui_state.new_solo_fader_cc.clear();
+                                                ui_state.settings_dirty = true;
+                                                settings_changed = true;
+                                            }
+                                        }
+                                    });
                                 });
 
                             ui.add_space(8.0);
@@ -313,13 +482,205 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                 .button(
                                     RichText::new("Show MIDI UI Layout")
                                         .size(13.0)
-                                        .color(theme::TEXT_PRIMARY),
+                                        .color(palette.text),
                                 )
                                 .clicked()
                             {
                                 ui_state.show_midi_ui_modal = true;
                             }
 
+                            ui.add_space(8.0);
+                            let old_thru = ui_state.cfg_midi_thru;
+                            ui.checkbox(
+                                &mut ui_state.cfg_midi_thru,
+                                RichText::new("MIDI Thru (restart to apply)")
+                                    .size(13.0)
+                                    .color(palette.text),
+                            )
+                            .on_hover_text(
+                                "Re-emit unmapped controls on a virtual \"nanoKontrol2 Thru\" port for a DAW",
+                            );
+                            if old_thru != ui_state.cfg_midi_thru {
+                                ui_state.settings_dirty = true;
+                                settings_changed = true;
+                            }
+
+                            ui.add_space(8.0);
+                            let old_led = ui_state.cfg_led_feedback;
+                            ui.checkbox(
+                                &mut ui_state.cfg_led_feedback,
+                                RichText::new("LED Feedback (restart to apply)")
+                                    .size(13.0)
+                                    .color(palette.text),
+                            )
+                            .on_hover_text(
+                                "Light mute button LEDs; turn off for devices without a MIDI output port",
+                            );
+                            if old_led != ui_state.cfg_led_feedback {
+                                ui_state.settings_dirty = true;
+                                settings_changed = true;
+                            }
+
+                            let old_inverted = ui_state.cfg_led_inverted;
+                            ui.add_enabled(
+                                ui_state.cfg_led_feedback,
+                                egui::Checkbox::new(
+                                    &mut ui_state.cfg_led_inverted,
+                                    RichText::new("Invert Mute LEDs")
+                                        .size(13.0)
+                                        .color(palette.text),
+                                ),
+                            )
+                            .on_hover_text("Light a mute button while its fader is active, not muted");
+                            if old_inverted != ui_state.cfg_led_inverted {
+                                ui_state.settings_dirty = true;
+                                settings_changed = true;
+                            }
+
+                            // Filters for the bursts some controllers send when connected
+                            ui.add_space(8.0);
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new("Ignore MIDI After Connect (ms, restart to apply):")
+                                        .size(12.0)
+                                        .color(theme::TEXT_SECONDARY),
+                                );
+                                let old_ignore = ui_state.cfg_startup_ignore_ms;
+                                ui.add(
+                                    egui::DragValue::new(&mut ui_state.cfg_startup_ignore_ms)
+                                        .range(0..=5000),
+                                );
+                                if old_ignore != ui_state.cfg_startup_ignore_ms {
+                                    ui_state.settings_dirty = true;
+                                    settings_changed = true;
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new("Zero Guard (ms):")
+                                        .size(12.0)
+                                        .color(theme::TEXT_SECONDARY),
+                                )
+                                .on_hover_text(
+                                    "Ignore a fader's 0 unless it sent a higher value this recently (0 = off)",
+                                );
+                                let old_guard = ui_state.cfg_zero_guard_ms;
+                                ui.add(
+                                    egui::DragValue::new(&mut ui_state.cfg_zero_guard_ms)
+                                        .range(0..=2000),
+                                );
+                                if old_guard != ui_state.cfg_zero_guard_ms {
+                                    ui_state.settings_dirty = true;
+                                    settings_changed = true;
+                                }
+                            });
+
+                            // Button that flips faders to their b_cc_N mappings
+                            ui.horizontal(|ui| {
+                                let old_bank = ui_state.cfg_bank_button_cc;
+                                let mut enabled = old_bank.is_some();
+                                ui.checkbox(
+                                    &mut enabled,
+                                    RichText::new("Bank Button CC:")
+                                        .size(12.0)
+                                        .color(theme::TEXT_SECONDARY),
+                                )
+                                .on_hover_text(
+                                    "Each press switches faders between bank A and their bank B mappings",
+                                );
+                                ui_state.cfg_bank_button_cc = if enabled {
+                                    let mut cc = old_bank.unwrap_or(58);
+                                    ui.add(egui::DragValue::new(&mut cc).range(0..=127));
+                                    Some(cc)
+                                } else {
+                                    None
+                                };
+                                if old_bank != ui_state.cfg_bank_button_cc {
+                                    ui_state.settings_dirty = true;
+                                    settings_changed = true;
+                                }
+                            });
+
+                            // Device port choice, only needed with several matching ports
+                            if ui_state.midi_ports.len() > 1 {
+                                ui.add_space(8.0);
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new("MIDI Device:")
+                                            .size(12.0)
+                                            .color(theme::TEXT_SECONDARY),
+                                    );
+
+                                    // (name, index among ports with that name)
+                                    let mut choices: Vec<(String, usize)> = Vec::new();
+                                    for name in &ui_state.midi_ports {
+                                        let index =
+                                            choices.iter().filter(|(n, _)| n == name).count();
+                                        choices.push((name.clone(), index));
+                                    }
+                                    let choice_text = |(name, index): &(String, usize)| {
+                                        if *index == 0 {
+                                            name.clone()
+                                        } else {
+                                            format!("{} (#{})", name, index + 1)
+                                        }
+                                    };
+
+                                    let before = (
+                                        ui_state.cfg_midi_port.clone(),
+                                        ui_state.cfg_midi_port_index,
+                                    );
+                                    let selected_text = match ui_state.cfg_midi_port {
+                                        Some(ref name) => choice_text(&(
+                                            name.clone(),
+                                            ui_state.cfg_midi_port_index,
+                                        )),
+                                        None => "First found".to_string(),
+                                    };
+                                    egui::ComboBox::from_id_salt("midi_port")
+                                        .selected_text(selected_text)
+                                        .show_ui(ui, |ui| {
+                                            if ui
+                                                .selectable_label(
+                                                    ui_state.cfg_midi_port.is_none(),
+                                                    "First found",
+                                                )
+                                                .clicked()
+                                            {
+                                                ui_state.cfg_midi_port = None;
+                                                ui_state.cfg_midi_port_index = 0;
+                                            }
+                                            for choice in &choices {
+                                                let selected = ui_state.cfg_midi_port.as_ref()
+                                                    == Some(&choice.0)
+                                                    && ui_state.cfg_midi_port_index == choice.1;
+                                                if ui
+                                                    .selectable_label(selected, choice_text(choice))
+                                                    .clicked()
+                                                {
+                                                    ui_state.cfg_midi_port = Some(choice.0.clone());
+                                                    ui_state.cfg_midi_port_index = choice.1;
+                                                }
+                                            }
+                                        });
+                                    if before
+                                        != (
+                                            ui_state.cfg_midi_port.clone(),
+                                            ui_state.cfg_midi_port_index,
+                                        )
+                                    {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.label(
+                                        RichText::new("(applies after restart)")
+                                            .size(11.0)
+                                            .color(theme::TEXT_MUTED),
+                                    );
+                                });
+                            }
+
                             ui.add_space(8.0);
                             ui.separator();
                             ui.add_space(8.0);
@@ -345,7 +706,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                     ui.label(
                                         RichText::new("🔊 Audio Sinks")
                                             .size(13.0)
-                                            .color(theme::ACCENT_BLUE)
+                                            .color(palette.accent)
                                             .strong(),
                                     );
                                     ui.add_space(8.0);
@@ -515,13 +876,76 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                 .corner_radius(CornerRadius::same(4))
                                 .show(ui, |ui| {
                                     ui.set_width(ui.available_width());
+
+                                    // Audio server details, read-only
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Audio Server")
+                                                .size(13.0)
+                                                .color(palette.text),
+                                        );
+                                        if ui
+                                            .small_button("↻")
+                                            .on_hover_text("Query pactl info again")
+                                            .clicked()
+                                        {
+                                            ui_state.refresh_server_info = true;
+                                        }
+                                    });
+                                    match ui_state.server_info {
+                                        Some(Ok(ref info)) => {
+                                            egui::Grid::new("server_info")
+                                                .num_columns(2)
+                                                .spacing([12.0, 2.0])
+                                                .show(ui, |ui| {
+                                                    let kind = if info.is_pipewire() {
+                                                        "PipeWire (pipewire-pulse)"
+                                                    } else {
+                                                        "PulseAudio"
+                                                    };
+                                                    for (key, value) in [
+                                                        ("Server:", info.name.as_str()),
+                                                        ("Type:", kind),
+                                                        ("Version:", info.version.as_str()),
+                                                        ("Default Sink:", info.default_sink.as_str()),
+                                                        (
+                                                            "Default Source:",
+                                                            info.default_source.as_str(),
+                                                        ),
+                                                    ] {
+                                                        ui.label(
+                                                            RichText::new(key)
+                                                                .size(12.0)
+                                                                .color(theme::TEXT_SECONDARY),
+                                                        );
+                                                        ui.label(
+                                                            RichText::new(value)
+                                                                .size(12.0)
+                                                                .color(theme::TEXT_MUTED),
+                                                        );
+                                                        ui.end_row();
+                                                    }
+                                                });
+                                        }
+                                        Some(Err(ref e)) => {
+                                            ui.label(
+                                                RichText::new(e)
+                                                    .size(12.0)
+                                                    .color(theme::ACCENT_RED),
+                                            );
+                                        }
+                                        None => {}
+                                    }
+
+                                    ui.add_space(8.0);
+
                                     // Use PipeWire
                                     let old_use_pipewire = ui_state.cfg_use_pipewire;
                                     ui.checkbox(
                                         &mut ui_state.cfg_use_pipewire,
                                         RichText::new("Use PipeWire")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_use_pipewire != ui_state.cfg_use_pipewire {
                                         ui_state.settings_dirty = true;
@@ -573,6 +997,14 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                                     "pw-volume".to_string(),
                                                     "pw-volume",
                                                 );
+                                                ui.selectable_value(
+                                                    &mut ui_state.cfg_volume_control_mode,
+                                                    "wpctl".to_string(),
+                                                    "wpctl",
+                                                )
+                                                .on_hover_text(
+                                                    "Set app volumes by PipeWire node id through wpctl",
+                                                );
                                             });
                                         if mode_before != ui_state.cfg_volume_control_mode {
                                             ui_state.settings_dirty = true;
@@ -608,6 +1040,62 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                             ui_state.settings_dirty = true;
                                             settings_changed = true;
                                         }
+
+                                        // Steepness of the exponential curve
+                                        if ui_state.cfg_volume_curve == "exponential" {
+                                            ui.add_space(16.0);
+                                            ui.label(
+                                                RichText::new("Exponent:")
+                                                    .size(12.0)
+                                                    .color(theme::TEXT_SECONDARY),
+                                            );
+                                            let old_exponent = ui_state.cfg_volume_curve_exponent;
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut ui_state.cfg_volume_curve_exponent,
+                                                )
+                                                .range(1.0..=4.0)
+                                                .speed(0.05),
+                                            )
+                                            .on_hover_text(
+                                                "Volume follows fader position to this power; higher gives finer control at low volumes",
+                                            );
+                                            if old_exponent != ui_state.cfg_volume_curve_exponent {
+                                                ui_state.settings_dirty = true;
+                                                settings_changed = true;
+                                            }
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Volume Read Mode
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Read Volume As:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        let read_mode_before =
+                                            ui_state.cfg_volume_read_mode.clone();
+                                        egui::ComboBox::from_id_salt("volume_read_mode")
+                                            .selected_text(&ui_state.cfg_volume_read_mode)
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(
+                                                    &mut ui_state.cfg_volume_read_mode,
+                                                    "percent".to_string(),
+                                                    "percent",
+                                                );
+                                                ui.selectable_value(
+                                                    &mut ui_state.cfg_volume_read_mode,
+                                                    "db".to_string(),
+                                                    "db",
+                                                );
+                                            });
+                                        if read_mode_before != ui_state.cfg_volume_read_mode {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
                                     });
 
                                     ui.add_space(8.0);
@@ -651,45 +1139,22 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                             settings_changed = true;
                                         }
                                     });
-                                });
-
-                            ui.add_space(8.0);
-                            ui.separator();
-                            ui.add_space(8.0);
 
-                            // ===== UI SECTION =====
-                            ui.add_space(8.0);
-                            render_section_header(ui, "UI Settings", theme::ACCENT_GREEN);
-                            ui.add_space(8.0);
+                                    ui.add_space(8.0);
 
-                            Frame::default()
-                                .fill(theme::BG_SECONDARY)
-                                .stroke(Stroke::new(1.0, theme::BORDER))
-                                .inner_margin(Margin {
-                                    left: 20,
-                                    right: 20,
-                                    top: 8,
-                                    bottom: 8,
-                                })
-                                .corner_radius(CornerRadius::same(4))
-                                .show(ui, |ui| {
-                                    ui.set_width(ui.available_width());
+                                    // Mute button hold-to-fade
                                     ui.horizontal(|ui| {
                                         ui.label(
-                                            RichText::new("Window Width:")
+                                            RichText::new("Mute Hold to Fade (ms):")
                                                 .size(12.0)
                                                 .color(theme::TEXT_SECONDARY),
                                         );
-                                        if ui
-                                            .text_edit_singleline(&mut ui_state.window_width_str)
-                                            .changed()
-                                        {
-                                            // Try to parse and update the value
-                                            if let Ok(val) =
-                                                ui_state.window_width_str.parse::<u32>()
-                                            {
-                                                ui_state.cfg_window_width = val.max(400).min(3000);
-                                            }
+                                        let old_hold = ui_state.cfg_mute_hold_ms;
+                                        ui.add(
+                                            egui::DragValue::new(&mut ui_state.cfg_mute_hold_ms)
+                                                .range(0..=5000),
+                                        );
+                                        if old_hold != ui_state.cfg_mute_hold_ms {
                                             ui_state.settings_dirty = true;
                                             settings_changed = true;
                                         }
@@ -697,7 +1162,233 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         ui.add_space(16.0);
 
                                         ui.label(
-                                            RichText::new("Height:")
+                                            RichText::new("Fade Duration (ms):")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        let old_fade = ui_state.cfg_mute_fade_ms;
+                                        ui.add(
+                                            egui::DragValue::new(&mut ui_state.cfg_mute_fade_ms)
+                                                .range(0..=10000),
+                                        );
+                                        if old_fade != ui_state.cfg_mute_fade_ms {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Fader that follows the most recently started app
+                                    ui.horizontal(|ui| {
+                                        let old_focus = ui_state.cfg_focus_fader_cc;
+                                        let mut enabled = old_focus.is_some();
+                                        ui.checkbox(
+                                            &mut enabled,
+                                            RichText::new("Focus Fader CC:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        )
+                                        .on_hover_text(
+                                            "Bind this fader to whichever app started playing most recently",
+                                        );
+                                        ui_state.cfg_focus_fader_cc = if enabled {
+                                            let mut cc = old_focus.unwrap_or(7);
+                                            ui.add(egui::DragValue::new(&mut cc).range(0..=127));
+                                            Some(cc)
+                                        } else {
+                                            None
+                                        };
+                                        if old_focus != ui_state.cfg_focus_fader_cc {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Hold-to-duck button that lowers every other sink
+                                    ui.horizontal(|ui| {
+                                        let old_duck = (
+                                            ui_state.cfg_duck_button_cc,
+                                            ui_state.cfg_duck_keep_cc,
+                                            ui_state.cfg_duck_level,
+                                        );
+                                        let mut enabled = old_duck.0.is_some();
+                                        ui.checkbox(
+                                            &mut enabled,
+                                            RichText::new("Duck Button CC:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        )
+                                        .on_hover_text(
+                                            "While held, lower every sink except the kept one, then restore",
+                                        );
+                                        ui_state.cfg_duck_button_cc = if enabled {
+                                            let mut cc = old_duck.0.unwrap_or(41);
+                                            ui.add(egui::DragValue::new(&mut cc).range(0..=127));
+
+                                            ui.add_space(16.0);
+                                            ui.label(
+                                                RichText::new("Keep Sink CC:")
+                                                    .size(12.0)
+                                                    .color(theme::TEXT_SECONDARY),
+                                            );
+                                            let mut keep = ui_state.cfg_duck_keep_cc.unwrap_or(1);
+                                            ui.add(egui::DragValue::new(&mut keep).range(0..=127));
+                                            ui_state.cfg_duck_keep_cc = Some(keep);
+
+                                            ui.add_space(16.0);
+                                            ui.label(
+                                                RichText::new("Level (%):")
+                                                    .size(12.0)
+                                                    .color(theme::TEXT_SECONDARY),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(&mut ui_state.cfg_duck_level)
+                                                    .range(0..=100),
+                                            );
+                                            Some(cc)
+                                        } else {
+                                            None
+                                        };
+                                        let new_duck = (
+                                            ui_state.cfg_duck_button_cc,
+                                            ui_state.cfg_duck_keep_cc,
+                                            ui_state.cfg_duck_level,
+                                        );
+                                        if old_duck != new_duck {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Knob that switches one fader between several apps
+                                    ui.horizontal(|ui| {
+                                        let old_selector = (
+                                            ui_state.cfg_selector_cc,
+                                            ui_state.cfg_selector_fader_cc,
+                                            ui_state.cfg_selector_targets.clone(),
+                                        );
+                                        let mut enabled = old_selector.0.is_some();
+                                        ui.checkbox(
+                                            &mut enabled,
+                                            RichText::new("Selector Knob CC:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        )
+                                        .on_hover_text(
+                                            "Turning this knob switches the selector fader between the listed apps",
+                                        );
+                                        ui_state.cfg_selector_cc = if enabled {
+                                            let mut cc = old_selector.0.unwrap_or(23);
+                                            ui.add(egui::DragValue::new(&mut cc).range(0..=127));
+
+                                            ui.add_space(16.0);
+                                            ui.label(
+                                                RichText::new("Fader CC:")
+                                                    .size(12.0)
+                                                    .color(theme::TEXT_SECONDARY),
+                                            );
+                                            let mut fader =
+                                                ui_state.cfg_selector_fader_cc.unwrap_or(6);
+                                            ui.add(egui::DragValue::new(&mut fader).range(0..=127));
+                                            ui_state.cfg_selector_fader_cc = Some(fader);
+
+                                            ui.add_space(16.0);
+                                            ui.label(
+                                                RichText::new("Apps:")
+                                                    .size(12.0)
+                                                    .color(theme::TEXT_SECONDARY),
+                                            );
+                                            ui.add(
+                                                egui::TextEdit::singleline(
+                                                    &mut ui_state.cfg_selector_targets,
+                                                )
+                                                .hint_text("firefox, spotify, mpv")
+                                                .desired_width(220.0),
+                                            );
+                                            Some(cc)
+                                        } else {
+                                            None
+                                        };
+                                        let new_selector = (
+                                            ui_state.cfg_selector_cc,
+                                            ui_state.cfg_selector_fader_cc,
+                                            ui_state.cfg_selector_targets.clone(),
+                                        );
+                                        if old_selector != new_selector {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Control socket for scripts; bound once at startup
+                                    let old_control = ui_state.cfg_control_socket;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_control_socket,
+                                        RichText::new("Control Socket (restart to apply)")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    )
+                                    .on_hover_text(
+                                        "Accept commands like \"set cc 0 80\" or \"mute app firefox\" on a local Unix socket",
+                                    );
+                                    if old_control != ui_state.cfg_control_socket {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+                                });
+
+                            ui.add_space(8.0);
+                            ui.separator();
+                            ui.add_space(8.0);
+
+                            // ===== UI SECTION =====
+                            ui.add_space(8.0);
+                            render_section_header(ui, "UI Settings", theme::ACCENT_GREEN);
+                            ui.add_space(8.0);
+
+                            Frame::default()
+                                .fill(theme::BG_SECONDARY)
+                                .stroke(Stroke::new(1.0, theme::BORDER))
+                                .inner_margin(Margin {
+                                    left: 20,
+                                    right: 20,
+                                    top: 8,
+                                    bottom: 8,
+                                })
+                                .corner_radius(CornerRadius::same(4))
+                                .show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Window Width:")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        if ui
+                                            .text_edit_singleline(&mut ui_state.window_width_str)
+                                            .changed()
+                                        {
+                                            // Try to parse and update the value
+                                            if let Ok(val) =
+                                                ui_state.window_width_str.parse::<u32>()
+                                            {
+                                                ui_state.cfg_window_width = val.max(400).min(3000);
+                                            }
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+
+                                        ui.add_space(16.0);
+
+                                        ui.label(
+                                            RichText::new("Height:")
                                                 .size(12.0)
                                                 .color(theme::TEXT_SECONDARY),
                                         );
@@ -759,7 +1450,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         &mut ui_state.cfg_show_console,
                                         RichText::new("Show Console by Default")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_show_console != ui_state.cfg_show_console {
                                         ui_state.settings_dirty = true;
@@ -768,13 +1459,137 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                                     ui.add_space(8.0);
 
+                                    // Hide unavailable faders
+                                    let old_hide_unavailable = ui_state.cfg_hide_unavailable;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_hide_unavailable,
+                                        RichText::new("Hide Unavailable Faders")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    );
+                                    if old_hide_unavailable != ui_state.cfg_hide_unavailable {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // One-decimal fader percentages
+                                    let old_decimal_percent = ui_state.cfg_decimal_percent;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_decimal_percent,
+                                        RichText::new("Show Decimal Percentages")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    );
+                                    if old_decimal_percent != ui_state.cfg_decimal_percent {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Green-to-red fader bars
+                                    let old_color_by_level = ui_state.cfg_color_by_level;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_color_by_level,
+                                        RichText::new("Color Faders by Level")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    )
+                                    .on_hover_text("Shade fader bars from green through yellow to red near 100%");
+                                    if old_color_by_level != ui_state.cfg_color_by_level {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Pulse a fader bar once its volume is applied
+                                    let old_confirm_flash = ui_state.cfg_confirm_flash;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_confirm_flash,
+                                        RichText::new("Flash on Confirmed Volume")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    )
+                                    .on_hover_text("Briefly outline a fader bar when the backend confirms its new volume");
+                                    if old_confirm_flash != ui_state.cfg_confirm_flash {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Raw MIDI value and applied percent on each fader
+                                    let old_show_midi_debug = ui_state.cfg_show_midi_debug;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_show_midi_debug,
+                                        RichText::new("Show MIDI Debug Overlay")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    );
+                                    if old_show_midi_debug != ui_state.cfg_show_midi_debug {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Start hidden in the tray
+                                    let old_start_minimized = ui_state.start_minimized;
+                                    ui.add_enabled(
+                                        tray_functional,
+                                        egui::Checkbox::new(
+                                            &mut ui_state.start_minimized,
+                                            RichText::new("Start Minimized to Tray")
+                                                .size(13.0)
+                                                .color(palette.text),
+                                        ),
+                                    )
+                                    .on_disabled_hover_text("Tray support is not available");
+                                    if old_start_minimized != ui_state.start_minimized {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Hardware button summoning and dismissing the window
+                                    ui.horizontal(|ui| {
+                                        let old_toggle = ui_state.cfg_window_toggle_cc;
+                                        let mut enabled = old_toggle.is_some();
+                                        ui.checkbox(
+                                            &mut enabled,
+                                            RichText::new("Window Toggle CC:")
+                                                .size(13.0)
+                                                .color(palette.text),
+                                        )
+                                        .on_hover_text(
+                                            "Each press minimizes the window, or restores and raises it",
+                                        );
+                                        ui_state.cfg_window_toggle_cc = if enabled {
+                                            let mut cc = old_toggle.unwrap_or(60);
+                                            ui.add(egui::DragValue::new(&mut cc).range(0..=127));
+                                            Some(cc)
+                                        } else {
+                                            None
+                                        };
+                                        if old_toggle != ui_state.cfg_window_toggle_cc {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
                                     // Show spectrum
                                     let old_show_spectrum = ui_state.cfg_show_spectrum;
                                     ui.checkbox(
                                         &mut ui_state.cfg_show_spectrum,
                                         RichText::new("Show Spectrum Analyzer")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_show_spectrum != ui_state.cfg_show_spectrum {
                                         ui_state.settings_dirty = true;
@@ -828,37 +1643,190 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                                             ui.add_space(8.0);
 
-                                            // Select sink to monitor
+                                            // Band weighting
                                             ui.horizontal(|ui| {
                                                 ui.label(
-                                                    RichText::new("Monitor Sink:")
+                                                    RichText::new("Weighting:")
                                                         .size(11.0)
                                                         .color(theme::TEXT_SECONDARY),
                                                 );
-                                                let sink_before =
-                                                    ui_state.cfg_spectrum_sink_name.clone();
-
-                                                // Create a list of available sinks
-                                                let sink_names: Vec<String> = ui_state
-                                                    .system_fader_labels
-                                                    .iter()
-                                                    .map(|(_, name)| name.clone())
-                                                    .collect();
-
-                                                egui::ComboBox::from_id_salt("spectrum_sink")
-                                                    .selected_text(&ui_state.cfg_spectrum_sink_name)
+                                                let weighting_before =
+                                                    ui_state.cfg_spectrum_weighting.clone();
+                                                egui::ComboBox::from_id_salt("spectrum_weighting")
+                                                    .selected_text(
+                                                        if ui_state.cfg_spectrum_weighting == "a" {
+                                                            "A-weighting"
+                                                        } else {
+                                                            "None"
+                                                        },
+                                                    )
                                                     .show_ui(ui, |ui| {
-                                                        for sink_name in sink_names {
-                                                            ui.selectable_value(
-                                                                &mut ui_state
-                                                                    .cfg_spectrum_sink_name,
-                                                                sink_name.clone(),
-                                                                sink_name,
-                                                            );
-                                                        }
+                                                        ui.selectable_value(
+                                                            &mut ui_state.cfg_spectrum_weighting,
+                                                            "none".to_string(),
+                                                            "None",
+                                                        );
+                                                        ui.selectable_value(
+                                                            &mut ui_state.cfg_spectrum_weighting,
+                                                            "a".to_string(),
+                                                            "A-weighting",
+                                                        );
                                                     });
+                                                if weighting_before
+                                                    != ui_state.cfg_spectrum_weighting
+                                                {
+                                                    ui_state.settings_dirty = true;
+                                                    settings_changed = true;
+                                                }
+                                            });
+
+                                            // Frequency axis
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    RichText::new("Frequency Axis:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                );
+                                                let scale_before =
+                                                    ui_state.cfg_spectrum_freq_scale.clone();
+                                                egui::ComboBox::from_id_salt("spectrum_freq_scale")
+                                                    .selected_text(
+                                                        &ui_state.cfg_spectrum_freq_scale,
+                                                    )
+                                                    .show_ui(ui, |ui| {
+                                                        ui.selectable_value(
+                                                            &mut ui_state.cfg_spectrum_freq_scale,
+                                                            "log".to_string(),
+                                                            "log",
+                                                        );
+                                                        ui.selectable_value(
+                                                            &mut ui_state.cfg_spectrum_freq_scale,
+                                                            "linear".to_string(),
+                                                            "linear",
+                                                        );
+                                                    });
+                                                if scale_before != ui_state.cfg_spectrum_freq_scale
+                                                {
+                                                    ui_state.settings_dirty = true;
+                                                    settings_changed = true;
+                                                }
+                                            });
+
+                                            // Bar and peak animation speed
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    RichText::new("Bar Response:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                );
+                                                let old_smoothing = ui_state.cfg_spectrum_smoothing;
+                                                ui.add(
+                                                    egui::DragValue::new(
+                                                        &mut ui_state.cfg_spectrum_smoothing,
+                                                    )
+                                                    .range(1.0..=60.0)
+                                                    .speed(0.5),
+                                                )
+                                                .on_hover_text("Higher is snappier, lower is more fluid");
+
+                                                ui.add_space(16.0);
+
+                                                ui.label(
+                                                    RichText::new("Peak Decay:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                );
+                                                let old_decay = ui_state.cfg_spectrum_peak_decay_ui;
+                                                ui.add(
+                                                    egui::DragValue::new(
+                                                        &mut ui_state.cfg_spectrum_peak_decay_ui,
+                                                    )
+                                                    .range(0.1..=20.0)
+                                                    .speed(0.1),
+                                                );
+                                                if old_smoothing != ui_state.cfg_spectrum_smoothing
+                                                    || old_decay
+                                                        != ui_state.cfg_spectrum_peak_decay_ui
+                                                {
+                                                    ui_state.settings_dirty = true;
+                                                    settings_changed = true;
+                                                }
+                                            });
+
+                                            ui.add_space(8.0);
+
+                                            // Hand-painted sizes, which egui's scaling leaves small
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    RichText::new("Detail Size:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                );
+                                                let old_scale = ui_state.cfg_spectrum_scale;
+                                                ui.add(
+                                                    egui::DragValue::new(
+                                                        &mut ui_state.cfg_spectrum_scale,
+                                                    )
+                                                    .range(
+                                                        crate::panels::visualizer::SCALE_RANGE,
+                                                    )
+                                                    .speed(0.05)
+                                                    .suffix("×"),
+                                                )
+                                                .on_hover_text(
+                                                    "Scale the spectrum's labels, bar gaps and lines, e.g. 2.0 on a 4K display",
+                                                );
+                                                if old_scale != ui_state.cfg_spectrum_scale {
+                                                    ui_state.settings_dirty = true;
+                                                    settings_changed = true;
+                                                }
+                                            });
+
+                                            ui.add_space(8.0);
+
+                                            // Select sink to monitor
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    RichText::new("Monitor Sink:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                );
+                                                if render_spectrum_source_picker(
+                                                    ui,
+                                                    ui_state,
+                                                    "spectrum_sink",
+                                                ) {
+                                                    ui_state.settings_dirty = true;
+                                                    settings_changed = true;
+                                                }
+                                            });
+
+                                            ui.add_space(8.0);
 
-                                                if sink_before != ui_state.cfg_spectrum_sink_name {
+                                            // Hardware button stepping through the sinks
+                                            ui.horizontal(|ui| {
+                                                let old_cycle = ui_state.cfg_spectrum_cycle_cc;
+                                                let mut enabled = old_cycle.is_some();
+                                                ui.checkbox(
+                                                    &mut enabled,
+                                                    RichText::new("Cycle Source CC:")
+                                                        .size(11.0)
+                                                        .color(theme::TEXT_SECONDARY),
+                                                )
+                                                .on_hover_text(
+                                                    "Each press switches the spectrum to the next sink's monitor",
+                                                );
+                                                ui_state.cfg_spectrum_cycle_cc = if enabled {
+                                                    let mut cc = old_cycle.unwrap_or(46);
+                                                    ui.add(
+                                                        egui::DragValue::new(&mut cc)
+                                                            .range(0..=127),
+                                                    );
+                                                    Some(cc)
+                                                } else {
+                                                    None
+                                                };
+                                                if old_cycle != ui_state.cfg_spectrum_cycle_cc {
                                                     ui_state.settings_dirty = true;
                                                     settings_changed = true;
                                                 }
@@ -868,6 +1836,56 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                                     ui.add_space(8.0);
 
+                                    // Idle redraw rate
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Idle Frame Rate (fps):")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        let old_fps = ui_state.cfg_max_idle_fps;
+                                        ui.add(
+                                            egui::DragValue::new(&mut ui_state.cfg_max_idle_fps)
+                                                .range(1..=60),
+                                        );
+                                        if old_fps != ui_state.cfg_max_idle_fps {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
+                                    // Redraw rate cap while animating
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Max Frame Rate (fps):")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        let old_fps = ui_state.cfg_max_fps;
+                                        ui.add(
+                                            egui::DragValue::new(&mut ui_state.cfg_max_fps)
+                                                .range(0..=240)
+                                                .custom_formatter(|fps, _| {
+                                                    if fps == 0.0 {
+                                                        "Unlimited".to_string()
+                                                    } else {
+                                                        format!("{}", fps)
+                                                    }
+                                                }),
+                                        )
+                                        .on_hover_text(
+                                            "Caps redraws while the spectrum runs, to save power",
+                                        );
+                                        if old_fps != ui_state.cfg_max_fps {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
+
+                                    ui.add_space(8.0);
+
                                     // Max console lines
                                     ui.horizontal(|ui| {
                                         ui.label(
@@ -894,7 +1912,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
 
                             // ===== LOGGING SECTION =====
                             ui.add_space(8.0);
-                            render_section_header(ui, "Logging Settings", theme::ACCENT_BLUE);
+                            render_section_header(ui, "Logging Settings", palette.accent);
                             ui.add_space(8.0);
 
                             Frame::default()
@@ -914,7 +1932,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         &mut ui_state.cfg_logging_enabled,
                                         RichText::new("Enable Logging")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_logging != ui_state.cfg_logging_enabled {
                                         ui_state.settings_dirty = true;
@@ -979,7 +1997,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         &mut ui_state.cfg_timestamps,
                                         RichText::new("Show Timestamps")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_timestamps != ui_state.cfg_timestamps {
                                         ui_state.settings_dirty = true;
@@ -994,7 +2012,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         &mut ui_state.cfg_log_fader_events,
                                         RichText::new("Log Fader Events")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_fader_events != ui_state.cfg_log_fader_events {
                                         ui_state.settings_dirty = true;
@@ -1009,12 +2027,56 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                         &mut ui_state.cfg_log_device_info,
                                         RichText::new("Log Device Info")
                                             .size(13.0)
-                                            .color(theme::TEXT_PRIMARY),
+                                            .color(palette.text),
                                     );
                                     if old_device_info != ui_state.cfg_log_device_info {
                                         ui_state.settings_dirty = true;
                                         settings_changed = true;
                                     }
+
+                                    ui.add_space(8.0);
+
+                                    // Log MIDI-to-volume latency
+                                    let old_latency = ui_state.cfg_log_latency;
+                                    ui.checkbox(
+                                        &mut ui_state.cfg_log_latency,
+                                        RichText::new("Log Volume Latency")
+                                            .size(13.0)
+                                            .color(palette.text),
+                                    )
+                                    .on_hover_text(
+                                        "Average time from a fader move arriving to its volume command finishing",
+                                    );
+                                    if old_latency != ui_state.cfg_log_latency {
+                                        ui_state.settings_dirty = true;
+                                        settings_changed = true;
+                                    }
+
+                                    ui.add_space(8.0);
+
+                                    // Copy of the console on disk
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new("Log File (restart to apply):")
+                                                .size(12.0)
+                                                .color(theme::TEXT_SECONDARY),
+                                        );
+                                        let response = ui
+                                            .add(
+                                                egui::TextEdit::singleline(
+                                                    &mut ui_state.cfg_log_file,
+                                                )
+                                                .desired_width(250.0)
+                                                .hint_text("Off"),
+                                            )
+                                            .on_hover_text(
+                                                "Also write console messages to this file, e.g. ~/korg-midi.log",
+                                            );
+                                        if response.changed() {
+                                            ui_state.settings_dirty = true;
+                                            settings_changed = true;
+                                        }
+                                    });
                                 });
 
                             ui.add_space(8.0);
@@ -1030,7 +2092,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                                 .size(14.0)
                                                 .color(Color32::WHITE),
                                         )
-                                        .fill(theme::ACCENT_BLUE),
+                                        .fill(palette.accent),
                                     )
                                     .clicked()
                                 {
@@ -1080,7 +2142,7 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
                                 ui.label(
                                     RichText::new(&ui_state.config_path)
                                         .size(12.0)
-                                        .color(theme::ACCENT_BLUE),
+                                        .color(palette.accent),
                                 );
                             });
 
@@ -1095,6 +2157,213 @@ pub fn render_settings_tab(ui_state: &mut UiState, ctx: &Context, _tray_function
     settings_changed
 }
 
+// Per-fader calibration, startup level, color and sink fallback, tucked away in a popover
+// next to the mapping
+fn render_fader_options(ui: &mut egui::Ui, mapping: &mut MappingConfig, is_sink: bool) {
+    ui.menu_button("⚙", |ui| {
+        let mut trim = mapping.trim_db.unwrap_or(0.0);
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new("Trim (dB):")
+                    .size(12.0)
+                    .color(theme::TEXT_SECONDARY),
+            );
+            ui.add(
+                egui::DragValue::new(&mut trim)
+                    .range(TRIM_DB_RANGE)
+                    .speed(0.1)
+                    .fixed_decimals(1),
+            )
+            .on_hover_text("Gain added to every level this fader sets, to match outputs");
+        });
+        mapping.trim_db = (trim != 0.0).then_some(trim);
+
+        ui.horizontal(|ui| {
+            let mut enabled = mapping.startup_level.is_some();
+            ui.checkbox(
+                &mut enabled,
+                RichText::new("Startup level (%):")
+                    .size(12.0)
+                    .color(theme::TEXT_SECONDARY),
+            )
+            .on_hover_text("Volume this fader is set to on every launch");
+            mapping.startup_level = if enabled {
+                let mut level = mapping.startup_level.unwrap_or(50);
+                ui.add(egui::DragValue::new(&mut level).range(0..=100));
+                Some(level)
+            } else {
+                None
+            };
+        });
+
+        ui.horizontal(|ui| {
+            let mut enabled = mapping.color.is_some();
+            ui.checkbox(
+                &mut enabled,
+                RichText::new("Color:")
+                    .size(12.0)
+                    .color(theme::TEXT_SECONDARY),
+            )
+            .on_hover_text("Accent for this fader instead of its section color");
+            if !enabled {
+                mapping.color = None;
+                return;
+            }
+            // An unparseable color is kept as written until a new one is picked
+            let color = mapping
+                .color
+                .as_deref()
+                .and_then(theme::parse_hex_color)
+                .unwrap_or(theme::ACCENT_BLUE);
+            let mut rgb = [color.r(), color.g(), color.b()];
+            if ui.color_edit_button_srgb(&mut rgb).changed() || mapping.color.is_none() {
+                mapping.color = Some(format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]));
+            }
+        });
+
+        if is_sink {
+            let mut fallback = mapping.fallback_to_default.unwrap_or(false);
+            ui.checkbox(
+                &mut fallback,
+                RichText::new("Fall back to default sink")
+                    .size(12.0)
+                    .color(theme::TEXT_SECONDARY),
+            )
+            .on_hover_text("While this sink is missing (e.g. unplugged), control the default sink");
+            mapping.fallback_to_default = fallback.then_some(true);
+        }
+    })
+    .response
+    .on_hover_text("Fader options");
+}
+
+// Sinks and apps by the names pavucontrol shows, picked to become mappings. The
+// app fills `import_rows` when asked. Returns true if mappings were added
+fn render_import_mappings(ui: &mut egui::Ui, ui_state: &mut UiState) -> bool {
+    let Some(rows) = ui_state.import_rows.as_mut() else {
+        if ui
+            .button("📥 Import Sinks and Apps…")
+            .on_hover_text("Pick from the names pavucontrol shows instead of typing raw names")
+            .clicked()
+        {
+            ui_state.import_requested = true;
+        }
+        return false;
+    };
+
+    let mut imported = false;
+    let mut close = false;
+    Frame::default()
+        .fill(theme::BG_SECONDARY)
+        .stroke(Stroke::new(1.0, theme::BORDER))
+        .inner_margin(Margin::same(8))
+        .corner_radius(CornerRadius::same(4))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(
+                RichText::new("Import (names as shown in pavucontrol)")
+                    .size(14.0)
+                    .color(ui_state.palette.text),
+            );
+            ui.add_space(8.0);
+
+            if rows.is_empty() {
+                ui.label(
+                    RichText::new("Every sink and playing app is mapped already")
+                        .size(12.0)
+                        .color(theme::TEXT_MUTED),
+                );
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("🔍").color(theme::TEXT_SECONDARY));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut ui_state.import_filter)
+                            .hint_text("Filter")
+                            .desired_width(200.0),
+                    );
+                    if !ui_state.import_filter.is_empty() && ui.small_button("✖").clicked() {
+                        ui_state.import_filter.clear();
+                    }
+                });
+                ui.add_space(4.0);
+            }
+
+            // Selected entries stay selected while the filter hides them
+            let filter = ui_state.import_filter.trim().to_lowercase();
+            let matches_filter = |candidate: &ImportCandidate| {
+                filter.is_empty()
+                    || candidate.friendly.to_lowercase().contains(&filter)
+                    || candidate.target.to_lowercase().contains(&filter)
+            };
+            for row in rows.iter_mut() {
+                if !matches_filter(&row.candidate) {
+                    continue;
+                }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut row.cc)
+                            .desired_width(40.0)
+                            .hint_text("CC"),
+                    );
+                    let kind = if row.candidate.is_sink {
+                        "🔊"
+                    } else {
+                        "🎵"
+                    };
+                    ui.checkbox(
+                        &mut row.selected,
+                        RichText::new(format!("{} {}", kind, row.candidate.friendly))
+                            .size(12.0)
+                            .color(theme::TEXT_SECONDARY),
+                    )
+                    .on_hover_text(format!("Matches '{}'", row.candidate.target));
+                });
+            }
+
+            ui.add_space(8.0);
+            let ready = rows
+                .iter()
+                .filter(|row| row.selected)
+                .all(|row| parse_cc_input(&row.cc).is_some());
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(ready, egui::Button::new("➕ Import Selected"))
+                    .on_disabled_hover_text("Give each selected entry a CC")
+                    .clicked()
+                {
+                    for row in rows.iter().filter(|row| row.selected) {
+                        let Some(cc) = parse_cc_input(&row.cc) else {
+                            continue;
+                        };
+                        let candidate = &row.candidate;
+                        let mut mapping = MappingConfig::new(candidate.target.clone());
+                        mapping.label = (candidate.friendly != candidate.target)
+                            .then(|| candidate.friendly.clone());
+                        let list = if candidate.is_sink {
+                            &mut ui_state.cfg_sinks
+                        } else {
+                            &mut ui_state.cfg_applications
+                        };
+                        list.retain(|(mapped, _)| *mapped != cc);
+                        list.push((cc, mapping));
+                        list.sort_by_key(|(cc, _)| *cc);
+                        imported = true;
+                    }
+                    close = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if close {
+        ui_state.import_rows = None;
+        ui_state.import_filter.clear();
+    }
+    imported
+}
+
 fn render_section_header(ui: &mut egui::Ui, text: &str, color: Color32) {
     ui.label(
         RichText::new(format!("[{}]", text.to_uppercase()))
@@ -13,5 +13,79 @@ pub const ACCENT_ORANGE: Color32 = Color32::from_rgb(220, 140, 80);
 pub const ACCENT_RED: Color32 = Color32::from_rgb(220, 100, 100);
 pub const ACCENT_GREEN: Color32 = Color32::from_rgb(100, 200, 150);
 pub const ACCENT_CYAN: Color32 = Color32::from_rgb(80, 200, 220);
+pub const ACCENT_YELLOW: Color32 = Color32::from_rgb(230, 200, 80);
 
 pub const BORDER: Color32 = Color32::from_rgb(50, 50, 60);
+
+/// Colors the user can override from the `[theme]` config section
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub bg: Color32,
+    pub accent: Color32,
+    pub text: Color32,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            bg: BG_PRIMARY,
+            accent: ACCENT_BLUE,
+            text: TEXT_PRIMARY,
+        }
+    }
+}
+
+/// Fader color for `color_by_level`: green up to 60%, then through yellow at 80%
+/// to red at 100%
+pub fn level_color(percent: u8) -> Color32 {
+    let t = (percent.min(100) as f32 - 60.0) / 40.0;
+    if t <= 0.0 {
+        ACCENT_GREEN
+    } else if t < 0.5 {
+        ACCENT_GREEN.lerp_to_gamma(ACCENT_YELLOW, t * 2.0)
+    } else {
+        ACCENT_YELLOW.lerp_to_gamma(ACCENT_RED, (t - 0.5) * 2.0)
+    }
+}
+
+/// Parse a `#rrggbb` (or `rrggbb`) hex color
+pub fn parse_hex_color(hex: &str) -> Option<Color32> {
+    let digits = hex.trim().strip_prefix('#').unwrap_or(hex.trim());
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_colors_parse_with_or_without_hash() {
+        assert_eq!(
+            parse_hex_color("#1e2030"),
+            Some(Color32::from_rgb(0x1e, 0x20, 0x30))
+        );
+        assert_eq!(
+            parse_hex_color(" FFaa00 "),
+            Some(Color32::from_rgb(255, 170, 0))
+        );
+    }
+
+    #[test]
+    fn level_colors_run_from_green_to_red() {
+        assert_eq!(level_color(0), ACCENT_GREEN);
+        assert_eq!(level_color(60), ACCENT_GREEN);
+        assert_eq!(level_color(80), ACCENT_YELLOW);
+        assert_eq!(level_color(100), ACCENT_RED);
+    }
+
+    #[test]
+    fn malformed_hex_colors_are_rejected() {
+        for bad in ["", "#fff", "#12345", "#1234567", "#gg0000", "blue"] {
+            assert_eq!(parse_hex_color(bad), None, "{:?}", bad);
+        }
+    }
+}
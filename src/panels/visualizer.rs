@@ -1,10 +1,26 @@
 use super::theme;
-use crate::spectrum::{frequency_to_note, get_band_frequency, SpectrumData, NUM_BANDS};
+use crate::spectrum::{
+    frequency_to_note, get_band_frequency, FreqScale, SpectrumData, DB_CEILING, DB_FLOOR, NUM_BANDS,
+};
 use egui::*;
 
 /// Maximum waterfall history (rows)
 pub const WATERFALL_HISTORY: usize = 128;
 
+/// dB between spectrum grid lines
+const DB_GRID_STEP: f32 = 20.0;
+
+/// Longest frame time the smoothing steps over; a longer frame (e.g. after
+/// restoring the window) would make the lerps overshoot
+const MAX_DT: f32 = 0.1;
+
+/// A pause in updates this long (seconds) clears the waterfall, so rows from
+/// before the pause don't run straight into the new ones
+const WATERFALL_RESET_SECS: f64 = 1.0;
+
+/// Evenly spaced frequency labels under the bars, before the last band's label
+const MAX_FREQUENCY_LABELS: usize = 8;
+
 /// Smooth display state for the visualizer (stored in UI to persist between frames)
 #[derive(Clone)]
 pub struct VisualizerState {
@@ -15,8 +31,35 @@ pub struct VisualizerState {
     /// Waterfall history (circular buffer): each row contains band magnitudes
     pub waterfall_history: Vec<[f32; NUM_BANDS]>,
     pub waterfall_pos: usize,
+    /// Frequency axis of the latest data, so labels match the bands
+    pub freq_scale: FreqScale,
+    /// How fast bars move towards new levels, per second
+    pub attack_speed: f32,
+    /// How fast peak markers fall back, per second
+    pub peak_decay: f32,
+    /// Take all the height left in the parent instead of the inline size
+    pub fill: bool,
+    /// egui time of the last update, to notice pauses
+    pub last_update: Option<f64>,
+    /// Loudest level seen per band while capturing, drawn as an outline until reset
+    pub capture_max: Vec<f32>,
+    /// Whether `capture_max` is following new data
+    pub capturing: bool,
+    /// `clip_count` when the clip light was last reset; it stays lit while below
+    pub clips_cleared: u64,
+    /// Multiplier for the painted label fonts, gaps and lines, for high-DPI displays
+    pub scale: f32,
 }
 
+/// Default `attack_speed`: responsive but visually smooth
+pub const DEFAULT_ATTACK_SPEED: f32 = 20.0;
+
+/// Default `peak_decay`
+pub const DEFAULT_PEAK_DECAY: f32 = 2.0;
+
+/// Sizes `scale` may take, as offered in Settings
+pub const SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
 impl Default for VisualizerState {
     fn default() -> Self {
         Self {
@@ -26,15 +69,34 @@ impl Default for VisualizerState {
             display_peaks_right: [0.0; NUM_BANDS],
             waterfall_history: vec![[0.0; NUM_BANDS]; WATERFALL_HISTORY],
             waterfall_pos: 0,
+            freq_scale: FreqScale::default(),
+            attack_speed: DEFAULT_ATTACK_SPEED,
+            peak_decay: DEFAULT_PEAK_DECAY,
+            fill: false,
+            last_update: None,
+            capture_max: vec![0.0; NUM_BANDS],
+            capturing: false,
+            clips_cleared: 0,
+            scale: 1.0,
         }
     }
 }
 
 impl VisualizerState {
-    /// Smoothly interpolate towards target values
-    pub fn update(&mut self, target: &SpectrumData, dt: f32) {
-        // Fast interpolation - responsive but visually smooth
-        let speed = 20.0 * dt; // Fast lerp for smooth animation
+    /// Smoothly interpolate towards target values. `now` is the egui time in seconds
+    pub fn update(&mut self, target: &SpectrumData, dt: f32, now: f64) {
+        let dt = dt.min(MAX_DT);
+        if self
+            .last_update
+            .is_some_and(|last| now - last > WATERFALL_RESET_SECS)
+        {
+            self.waterfall_history.fill([0.0; NUM_BANDS]);
+        }
+        self.last_update = Some(now);
+
+        let speed = self.attack_speed * dt;
+        let decay = (self.peak_decay * dt).min(1.0);
+        self.freq_scale = target.freq_scale;
 
         for i in 0..NUM_BANDS {
             self.display_bands[i] = lerp(self.display_bands[i], target.bands[i], speed.min(1.0));
@@ -48,14 +110,21 @@ impl VisualizerState {
             if target.peaks[i] > self.display_peaks[i] {
                 self.display_peaks[i] = target.peaks[i];
             } else {
-                self.display_peaks[i] = lerp(self.display_peaks[i], target.peaks[i], 2.0 * dt);
+                self.display_peaks[i] = lerp(self.display_peaks[i], target.peaks[i], decay);
             }
 
             if target.peaks_right[i] > self.display_peaks_right[i] {
                 self.display_peaks_right[i] = target.peaks_right[i];
             } else {
                 self.display_peaks_right[i] =
-                    lerp(self.display_peaks_right[i], target.peaks_right[i], 2.0 * dt);
+                    lerp(self.display_peaks_right[i], target.peaks_right[i], decay);
+            }
+        }
+
+        // Hold the loudest channel of each band; unlike the peaks this never decays
+        if self.capturing {
+            for (i, max) in self.capture_max.iter_mut().enumerate() {
+                *max = max.max(target.bands[i]).max(target.bands_right[i]);
             }
         }
 
@@ -70,6 +139,102 @@ impl VisualizerState {
     }
 }
 
+/// Combo box choosing which sink feeds the visualizer. Returns true if the selection changed
+pub fn render_spectrum_source_picker(
+    ui: &mut Ui,
+    ui_state: &mut crate::ui::UiState,
+    id_salt: &str,
+) -> bool {
+    let sources = ui_state.spectrum_sources();
+    let selected_text = sources
+        .iter()
+        .find(|(target, _)| *target == ui_state.cfg_spectrum_sink_name)
+        .map(|(_, label)| label.clone())
+        .unwrap_or_else(|| ui_state.cfg_spectrum_sink_name.clone());
+    let before = ui_state.cfg_spectrum_sink_name.clone();
+
+    let response = ComboBox::from_id_salt(id_salt)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            for (target, label) in sources {
+                ui.selectable_value(&mut ui_state.cfg_spectrum_sink_name, target, label);
+            }
+        })
+        .response;
+    if let Some(cc) = ui_state.cfg_spectrum_cycle_cc {
+        response.on_hover_text(format!(
+            "CC {} on the controller steps to the next source",
+            cc
+        ));
+    }
+
+    before != ui_state.cfg_spectrum_sink_name
+}
+
+/// Spectrum source and display toggles above the visualizer, then the visualizer
+/// itself. With `fill` it takes all the height left in `ui`
+pub fn render_spectrum_section(
+    ui: &mut Ui,
+    ui_state: &mut crate::ui::UiState,
+    id_salt: &str,
+    fill: bool,
+) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new("Spectrum source:")
+                .size(11.0)
+                .color(theme::TEXT_SECONDARY),
+        );
+        // Saved with the other settings on the next save or exit
+        let mut changed = render_spectrum_source_picker(ui, ui_state, id_salt);
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            changed |= ui
+                .toggle_value(&mut ui_state.cfg_spectrum_show_labels, "Labels")
+                .changed();
+            changed |= ui
+                .toggle_value(&mut ui_state.cfg_spectrum_show_waterfall, "Waterfall")
+                .changed();
+            changed |= ui
+                .toggle_value(&mut ui_state.cfg_spectrum_stereo_mode, "Stereo")
+                .changed();
+        });
+
+        if changed {
+            ui_state.settings_dirty = true;
+        }
+    });
+    ui.add_space(4.0);
+    ui_state.visualizer_state.attack_speed = ui_state.cfg_spectrum_smoothing;
+    ui_state.visualizer_state.peak_decay = ui_state.cfg_spectrum_peak_decay_ui;
+    ui_state.visualizer_state.scale = ui_state.cfg_spectrum_scale;
+    ui_state.visualizer_state.fill = fill;
+    render_spectrum_visualizer(
+        ui,
+        &ui_state.spectrum_data,
+        &mut ui_state.visualizer_state,
+        true,
+        ui_state.cfg_spectrum_stereo_mode,
+        ui_state.cfg_spectrum_show_waterfall,
+        ui_state.cfg_spectrum_show_labels,
+    );
+}
+
+/// Spectrum tab: the visualizer scaled to the whole central panel
+pub fn render_spectrum_tab(ui_state: &mut crate::ui::UiState, ctx: &Context) {
+    let palette = ui_state.palette;
+    CentralPanel::default()
+        .frame(Frame::default().fill(palette.bg).inner_margin(Margin {
+            left: 20,
+            right: 20,
+            top: 12,
+            bottom: 12,
+        }))
+        .show(ctx, |ui| {
+            render_spectrum_section(ui, ui_state, "tab_spectrum_source", true);
+        });
+}
+
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t.clamp(0.0, 1.0)
 }
@@ -84,11 +249,17 @@ pub fn render_spectrum_visualizer(
     show_waterfall: bool,
     show_labels: bool,
 ) {
-    // Update with smoothing
-    let dt = ui.ctx().input(|i| i.predicted_dt);
-    if enabled {
-        state.update(spectrum, dt);
-    } else {
+    // Update with smoothing, unless the window is minimized and nothing is seen
+    let (dt, now, minimized) = ui.ctx().input(|i| {
+        (
+            i.predicted_dt.min(MAX_DT),
+            i.time,
+            i.viewport().minimized == Some(true),
+        )
+    });
+    if enabled && !minimized {
+        state.update(spectrum, dt, now);
+    } else if !minimized {
         // Fade out when disabled
         for i in 0..NUM_BANDS {
             state.display_bands[i] = lerp(state.display_bands[i], 0.0, 8.0 * dt);
@@ -116,15 +287,65 @@ pub fn render_spectrum_visualizer(
                 );
 
                 ui.add_space(8.0);
+
+                // Sample peak in dBFS and a clip light that stays lit until clicked
+                let readout = if spectrum.sample_peak > 0.0 {
+                    format!("{:>6.1} dBFS", 20.0 * spectrum.sample_peak.log10())
+                } else {
+                    "  -inf dBFS".to_string()
+                };
+                ui.label(
+                    RichText::new(readout)
+                        .monospace()
+                        .size(11.0)
+                        .color(theme::TEXT_SECONDARY),
+                );
+                let (text_color, fill) = if spectrum.clip_count > state.clips_cleared {
+                    (Color32::WHITE, theme::ACCENT_RED)
+                } else {
+                    (theme::TEXT_MUTED, theme::BG_TERTIARY)
+                };
+                let clip_light =
+                    Button::new(RichText::new("CLIP").size(10.0).strong().color(text_color))
+                        .fill(fill)
+                        .small();
+                if ui
+                    .add(clip_light)
+                    .on_hover_text("Lights when a sample reaches full scale; click to reset")
+                    .clicked()
+                {
+                    state.clips_cleared = spectrum.clip_count;
+                }
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let has_capture = state.capture_max.iter().any(|&max| max > 0.0);
+                    if ui
+                        .add_enabled(has_capture, Button::new("Reset").small())
+                        .on_hover_text("Clear the captured maximum")
+                        .clicked()
+                    {
+                        state.capture_max.fill(0.0);
+                    }
+                    ui.toggle_value(&mut state.capturing, "⏺ Capture")
+                        .on_hover_text(
+                            "Hold the loudest level of each band as an outline; \
+                             turn off to freeze it",
+                        );
+                });
             });
 
             ui.add_space(8.0);
 
-            // Main visualizer
-            let height = if show_waterfall { 150.0 } else { 120.0 };
+            // Main visualizer, inline size unless it fills the remaining space
+            let inline_height = state.scale * if show_waterfall { 150.0 } else { 120.0 };
+            let height = if state.fill {
+                ui.available_height().max(inline_height)
+            } else {
+                inline_height
+            };
             let width = ui.available_width();
 
-            let (rect, _response) = ui.allocate_exact_size(vec2(width, height), Sense::hover());
+            let (rect, response) = ui.allocate_exact_size(vec2(width, height), Sense::hover());
 
             if show_waterfall {
                 render_spectrum_with_waterfall(
@@ -138,9 +359,43 @@ pub fn render_spectrum_visualizer(
             } else {
                 render_spectrum_bars(ui.painter(), rect, state, enabled, stereo_mode, show_labels);
             }
+
+            // Frequency cursor: mark the hovered band and show its frequency and note
+            if let Some(band) = response.hover_pos().and_then(|pos| band_at(rect, pos.x)) {
+                let bar_width = (rect.width() - 4.0) / NUM_BANDS as f32;
+                let x = rect.min.x + 2.0 + (band as f32 + 0.5) * bar_width;
+                ui.painter().line_segment(
+                    [pos2(x, rect.min.y), pos2(x, rect.max.y)],
+                    Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 60)),
+                );
+
+                let freq = get_band_frequency(band, state.freq_scale);
+                let freq_text = if freq >= 1000.0 {
+                    format!("{:.2} kHz", freq / 1000.0)
+                } else {
+                    format!("{:.0} Hz", freq)
+                };
+                response.on_hover_ui_at_pointer(|ui| {
+                    ui.label(
+                        RichText::new(format!("{}  ({})", freq_text, frequency_to_note(freq)))
+                            .size(11.0),
+                    );
+                });
+            }
         });
 }
 
+/// Band under a horizontal position in the spectrum area, using the bar layout
+fn band_at(rect: Rect, x: f32) -> Option<usize> {
+    let bar_width = (rect.width() - 4.0) / NUM_BANDS as f32;
+    let offset = x - rect.min.x - 2.0;
+    if bar_width <= 0.0 || offset < 0.0 {
+        return None;
+    }
+    let band = (offset / bar_width) as usize;
+    (band < NUM_BANDS).then_some(band)
+}
+
 fn render_spectrum_with_waterfall(
     painter: &Painter,
     rect: Rect,
@@ -207,13 +462,13 @@ fn render_spectrum_bars(
     painter.rect_filled(rect, 4.0, theme::BG_TERTIARY);
 
     // Reserve space at bottom for labels if enabled
-    let label_height = if show_labels { 14.0 } else { 0.0 };
+    let label_height = if show_labels { 14.0 * state.scale } else { 0.0 };
     let bars_bottom = rect.max.y - label_height;
     let available_height = (bars_bottom - rect.min.y) - 4.0;
 
-    // Grid lines
-    for i in 1..4 {
-        let y = rect.min.y + (available_height * i as f32 / 4.0);
+    // Grid lines every DB_GRID_STEP, at the same heights the bars use
+    let db_grid = db_grid_lines(bars_bottom, available_height);
+    for &(_, y) in &db_grid[1..db_grid.len() - 1] {
         painter.line_segment(
             [pos2(rect.min.x, y), pos2(rect.max.x, y)],
             Stroke::new(0.5, Color32::from_rgba_unmultiplied(80, 80, 90, 40)),
@@ -221,7 +476,7 @@ fn render_spectrum_bars(
     }
 
     let bar_width = (rect.width() - 4.0) / NUM_BANDS as f32;
-    let gap = 2.0;
+    let gap = 2.0 * state.scale;
 
     if stereo_mode {
         // Split bar display: left half for left channel, right half for right channel
@@ -261,7 +516,7 @@ fn render_spectrum_bars(
                         pos2(x_left, peak_y_left),
                         pos2(x_left + effective_bar_width, peak_y_left),
                     ],
-                    Stroke::new(2.0, Color32::WHITE),
+                    Stroke::new(2.0 * state.scale, Color32::WHITE),
                 );
             }
 
@@ -296,7 +551,7 @@ fn render_spectrum_bars(
                         pos2(x_right, peak_y_right),
                         pos2(x_right + effective_bar_width, peak_y_right),
                     ],
-                    Stroke::new(2.0, Color32::WHITE),
+                    Stroke::new(2.0 * state.scale, Color32::WHITE),
                 );
             }
         }
@@ -336,18 +591,70 @@ fn render_spectrum_bars(
             if enabled && peak_value > 0.01 {
                 painter.line_segment(
                     [pos2(x, peak_y), pos2(x + effective_bar_width, peak_y)],
-                    Stroke::new(2.0, Color32::WHITE),
+                    Stroke::new(2.0 * state.scale, Color32::WHITE),
                 );
             }
         }
     }
 
-    // Frequency labels with note names
+    // Captured maximum as a stepped outline over all bands
+    if state.capture_max.iter().any(|&max| max > 0.0) {
+        let points: Vec<Pos2> = state
+            .capture_max
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &max)| {
+                let x = rect.min.x + 2.0 + i as f32 * bar_width;
+                let y = bars_bottom - 2.0 - max.min(1.0) * available_height;
+                [pos2(x, y), pos2(x + bar_width - gap, y)]
+            })
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5 * state.scale, theme::ACCENT_ORANGE),
+        ));
+    }
+
+    // Frequency labels with note names, dB scale on the left edge
     if show_labels {
-        render_frequency_labels(painter, rect);
+        render_frequency_labels(
+            painter,
+            rect,
+            state.freq_scale,
+            state.display_bands.len(),
+            state.scale,
+        );
+        let half_font = 4.0 * state.scale;
+        for (db, y) in db_grid {
+            // Kept inside the background at the top and bottom edges; max/min rather
+            // than clamp, which panics when the area is too short for a label
+            let label_y = y
+                .max(rect.min.y + half_font + 2.0)
+                .min(bars_bottom - half_font - 2.0);
+            painter.text(
+                pos2(rect.min.x + 3.0, label_y),
+                Align2::LEFT_CENTER,
+                format!("{:.0}", db),
+                FontId::proportional(8.0 * state.scale),
+                theme::TEXT_MUTED,
+            );
+        }
     }
 }
 
+/// Level and y position of each dB grid step, from DB_CEILING down to DB_FLOOR
+fn db_grid_lines(bars_bottom: f32, available_height: f32) -> Vec<(f32, f32)> {
+    let db_range = DB_CEILING - DB_FLOOR;
+    let steps = (db_range / DB_GRID_STEP) as i32;
+    (0..=steps)
+        .map(|step| {
+            let db = DB_CEILING - step as f32 * DB_GRID_STEP;
+            let y = bars_bottom - 2.0 - (db - DB_FLOOR) / db_range * available_height;
+            (db, y)
+        })
+        .collect()
+}
+
 /// Draw a subtle shadow effect below the bar
 fn draw_bar_shadow(painter: &Painter, rect: Rect, shadow_offset: f32) {
     let shadow_rect = Rect::from_min_max(
@@ -379,33 +686,48 @@ fn draw_bar_glow(painter: &Painter, rect: Rect, color: Color32, value: f32) {
     painter.rect_filled(glow_rect, 3.0, glow_color);
 }
 
-fn render_frequency_labels(painter: &Painter, rect: Rect) {
-    let label_positions = [0, 4, 8, 12, 16, 20, 24, 28, 31]; // Band indices
-
-    // Position labels in the bottom 14px area of the rect
+fn render_frequency_labels(
+    painter: &Painter,
+    rect: Rect,
+    scale: FreqScale,
+    band_count: usize,
+    size_scale: f32,
+) {
+    // Position labels in the bottom label area (14 points at size_scale 1.0) of the rect
     let label_y = rect.max.y - 2.0;
+    let bar_width = (rect.width() - 4.0) / band_count.max(1) as f32;
 
-    for &band_idx in &label_positions {
-        if band_idx >= NUM_BANDS {
-            continue;
-        }
-
-        let freq = get_band_frequency(band_idx);
+    for band_idx in label_bands(band_count) {
+        let freq = get_band_frequency(band_idx, scale);
         let note = frequency_to_note(freq);
 
-        let bar_width = (rect.width() - 4.0) / NUM_BANDS as f32;
         let x = rect.min.x + 2.0 + band_idx as f32 * bar_width + bar_width * 0.5;
 
         painter.text(
             pos2(x, label_y),
             Align2::CENTER_BOTTOM,
             note,
-            FontId::proportional(8.0),
+            FontId::proportional(8.0 * size_scale),
             theme::TEXT_MUTED,
         );
     }
 }
 
+/// Bands that get a frequency label: about 8 evenly spaced ones plus the last band,
+/// which is skipped only when it would crowd the label before it
+fn label_bands(band_count: usize) -> Vec<usize> {
+    let Some(last) = band_count.checked_sub(1) else {
+        return Vec::new();
+    };
+    let step = last / MAX_FREQUENCY_LABELS + 1;
+    let mut bands: Vec<usize> = (0..last).step_by(step).collect();
+    if bands.last().is_some_and(|&band| (last - band) * 2 < step) {
+        bands.pop();
+    }
+    bands.push(last);
+    bands
+}
+
 /// Get color for a bar based on its band index and value
 fn get_bar_color(band_index: usize, value: f32) -> Color32 {
     // Color gradient from blue (low) to cyan (mid) to green (high freq)
@@ -442,3 +764,17 @@ fn get_bar_color(band_index: usize, value: f32) -> Color32 {
 
     Color32::from_rgb(r as u8, g as u8, b as u8)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_labels_follow_the_band_count() {
+        assert_eq!(label_bands(32), vec![0, 4, 8, 12, 16, 20, 24, 28, 31]);
+        assert_eq!(label_bands(8), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(label_bands(64), vec![0, 8, 16, 24, 32, 40, 48, 56, 63]);
+        assert_eq!(label_bands(1), vec![0]);
+        assert!(label_bands(0).is_empty());
+    }
+}
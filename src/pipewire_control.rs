@@ -1,7 +1,12 @@
-use anyhow::Result;
-use std::collections::HashMap;
-use std::process::Command;
-use std::sync::{Arc, Mutex};
+use crate::engine::{
+    split_channel, AppStatus, AudioBackend, TargetKind, VolumeReadMode, CHANNEL_SUFFIX,
+};
+use anyhow::{anyhow, Result};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
 
 // Cache entry for volume lookups with TTL
@@ -13,137 +18,505 @@ struct CachedVolume {
 pub struct PipeWireController {
     sink_volume_cache: Arc<Mutex<HashMap<String, CachedVolume>>>,
     app_volume_cache: Arc<Mutex<HashMap<String, CachedVolume>>>,
+    app_input_cache: Arc<Mutex<HashMap<String, Vec<u32>>>>, // Sink-input indices per app mapping
     default_sink_name: String,
+    read_mode: VolumeReadMode,
+    app_wpctl: bool, // volume_control_mode = "wpctl": app streams are node ids set through wpctl
+    dry_run: Option<mpsc::Sender<String>>, // --dry-run: volume and mute commands are sent here instead
+    default_fallbacks: HashSet<String>,    // Sink targets replaced by the default sink while absent
+    sink_lookups: Arc<Mutex<SinkLookups>>,
+}
+
+// Sink listings kept between applications_sink_search rescans, so volume changes
+// don't list the server's sinks on every set
+#[derive(Default)]
+struct SinkLookups {
+    short: Option<Vec<(u32, String)>>, // `pactl list short sinks`
+    by_description: HashMap<String, Option<SinkInfo>>,
+}
+
+// A sink input matched against a configured application name
+struct AppInput {
+    index: u32,
+    volume: u8,
+    muted: bool,
+    display_name: Option<String>, // application.name as reported by PipeWire
 }
 
 const VOLUME_CACHE_TTL: Duration = Duration::from_secs(1);
 
+/// Mapping prefix that addresses a sink by PipeWire node id instead of name
+const NODE_PREFIX: &str = "node:";
+
+/// Mapping prefix that matches a sink by `device.description` substring
+const DESC_PREFIX: &str = "desc:";
+
+/// Mapping prefix that matches app streams by `media.role` (e.g. `role:phone`)
+const ROLE_PREFIX: &str = "role:";
+
+/// pactl's name for the default sink, used by `fallback_to_default` mappings
+const DEFAULT_SINK: &str = "@DEFAULT_SINK@";
+
 impl PipeWireController {
     pub fn new(_use_api: bool, default_sink_name: &str) -> Self {
         PipeWireController {
             sink_volume_cache: Arc::new(Mutex::new(HashMap::new())),
             app_volume_cache: Arc::new(Mutex::new(HashMap::new())),
+            app_input_cache: Arc::new(Mutex::new(HashMap::new())),
             default_sink_name: default_sink_name.to_string(),
+            read_mode: VolumeReadMode::default(),
+            app_wpctl: false,
+            dry_run: None,
+            default_fallbacks: HashSet::new(),
+            sink_lookups: Arc::new(Mutex::new(SinkLookups::default())),
         }
     }
 
-    fn get_sink_index(&self, sink_name: &str) -> Option<u32> {
-        if let Ok(output) = Command::new("pactl").args(&["list", "sinks"]).output() {
-            if output.status.success() {
-                let text = String::from_utf8_lossy(&output.stdout);
-                let lines: Vec<&str> = text.lines().collect();
-
-                let mut current_index: Option<u32> = None;
-                for line in &lines {
-                    if line.starts_with("Sink #") {
-                        if let Some(idx_str) = line
-                            .strip_prefix("Sink #")
-                            .and_then(|s| s.split_whitespace().next())
-                        {
-                            current_index = idx_str.parse::<u32>().ok();
-                        }
-                    }
-                    if let Some(idx) = current_index {
-                        if line.trim().starts_with("Name:") && line.contains(sink_name) {
-                            return Some(idx);
-                        }
-                    }
-                }
-            }
+    // Sinks on the server as `pactl list short sinks` reports them, cached until the
+    // next rescan
+    fn short_sinks(&self) -> Vec<(u32, String)> {
+        match self.sink_lookups.lock() {
+            Ok(mut lookups) => lookups.short.get_or_insert_with(list_short_sinks).clone(),
+            Err(_) => list_short_sinks(),
+        }
+    }
+
+    // find_sink_by_description, cached until the next rescan
+    fn sink_by_description(&self, description: &str) -> Option<SinkInfo> {
+        match self.sink_lookups.lock() {
+            Ok(mut lookups) => lookups
+                .by_description
+                .entry(description.to_string())
+                .or_insert_with(|| find_sink_by_description(description))
+                .clone(),
+            Err(_) => find_sink_by_description(description),
+        }
+    }
+
+    /// `@DEFAULT_SINK@`, on the same channel, in place of a `fallback_to_default`
+    /// sink that isn't present; any other target as it is
+    fn with_default_fallback<'a>(&self, target: &'a str) -> Cow<'a, str> {
+        if !self.default_fallbacks.contains(target) {
+            return Cow::Borrowed(target);
+        }
+        let (sink, channel) = split_channel(target);
+        if self.sink_present(sink) {
+            return Cow::Borrowed(target);
         }
-        None
+        match channel {
+            Some(channel) => Cow::Owned(format!("{}{}{}", DEFAULT_SINK, CHANNEL_SUFFIX, channel)),
+            None => Cow::Borrowed(DEFAULT_SINK),
+        }
+    }
+
+    // Whether a sink mapping, without channel, matches a sink on the server
+    fn sink_present(&self, sink: &str) -> bool {
+        if let Some(node) = sink.strip_prefix(NODE_PREFIX) {
+            return match node.trim().parse::<u32>() {
+                // Node ids double as sink indices under pipewire-pulse
+                Ok(id) => self.short_sinks().iter().any(|(index, _)| *index == id),
+                Err(_) => self.sink_by_description(node.trim()).is_some(),
+            };
+        }
+        if sink.starts_with(DESC_PREFIX) {
+            return self.resolve_sink_name(sink).is_some();
+        }
+        self.short_sinks().iter().any(|(_, name)| name == sink)
+    }
+
+    /// Stop running volume and mute commands and send each one, as a shell line, to
+    /// `log` instead. Reads still run so targets resolve as usual
+    pub fn set_dry_run(&mut self, log: mpsc::Sender<String>) {
+        self.dry_run = Some(log);
+    }
+
+    // Run a command that changes audio state, or only report it in dry-run mode.
+    // A non-zero exit is an error carrying the command's stderr
+    fn run_change<S: AsRef<str>>(&self, program: &str, args: &[S]) -> Result<()> {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        if let Some(ref log) = self.dry_run {
+            let _ = log.send(format!("{} {}", program, args.join(" ")));
+            return Ok(());
+        }
+        let output = Command::new(program).args(&args).output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn get_sink_index(&self, sink_name: &str) -> Option<u32> {
+        let sink_name = self.resolve_sink_name(sink_name)?;
+        self.short_sinks()
+            .into_iter()
+            .find_map(|(index, name)| name.contains(&*sink_name).then_some(index))
     }
 
-    fn get_matching_app_inputs(&self, app_name: &str) -> Vec<(u32, u8)> {
+    fn get_matching_app_inputs(&self, app_name: &str) -> Vec<AppInput> {
         let target_sink_index = match self.get_sink_index(&self.default_sink_name) {
             Some(idx) => idx,
             None => return Vec::new(),
         };
 
-        let app_name_lower = app_name.to_lowercase();
-        let normalized_config = normalize_app_name(&app_name_lower);
-
-        let mut results = Vec::new();
-
-        if let Ok(output) = Command::new("pactl")
+        match Command::new("pactl")
             .args(&["list", "sink-inputs"])
             .output()
         {
-            if output.status.success() {
-                let text = String::from_utf8_lossy(&output.stdout);
-                let lines: Vec<&str> = text.lines().collect();
-
-                let mut current_input_index: Option<u32> = None;
-                let mut current_sink: Option<u32> = None;
-                let mut matched_app: bool = false;
-                let mut matched_volume: Option<u8> = None;
-
-                for line in &lines {
-                    if line.starts_with("Sink Input #") {
-                        if let Some(idx) = current_input_index {
-                            if matched_app && matched_sink(current_sink, target_sink_index) {
-                                if let Some(vol) = matched_volume {
-                                    results.push((idx, vol));
-                                }
-                            }
-                        }
-                        if let Some(idx_str) = line
-                            .strip_prefix("Sink Input #")
-                            .and_then(|s| s.split_whitespace().next())
-                        {
-                            current_input_index = idx_str.parse::<u32>().ok();
-                        }
-                        current_sink = None;
-                        matched_app = false;
-                        matched_volume = None;
-                        continue;
-                    }
+            Ok(output) if output.status.success() => parse_app_inputs(
+                &String::from_utf8_lossy(&output.stdout),
+                app_name,
+                target_sink_index,
+                self.read_mode,
+            ),
+            _ => Vec::new(),
+        }
+    }
 
-                    if let Some(_) = current_input_index {
-                        if line.trim().starts_with("Sink:") {
-                            if let Some(val_str) =
-                                line.trim().strip_prefix("Sink:").map(|s| s.trim())
-                            {
-                                current_sink = val_str.parse::<u32>().ok();
-                            }
-                        }
-
-                        if !matched_app {
-                            let line_lower = line.to_lowercase();
-                            let normalized_line = normalize_app_name(&line_lower);
-                            if (line_lower.contains("application.name")
-                                && normalized_line.contains(&normalized_config))
-                                || (line_lower.contains("application.process.binary")
-                                    && normalized_line.contains(&normalized_config))
-                            {
-                                matched_app = true;
-                            }
-                        }
-
-                        if matched_volume.is_none() && line.contains("Volume:") {
-                            for part in line.split('/') {
-                                if let Some(pct) = part.trim().strip_suffix('%') {
-                                    if let Ok(vol) = pct.trim().parse::<u8>() {
-                                        matched_volume = Some(vol);
-                                        break;
-                                    }
-                                }
-                            }
-                        }
+    /// `application.name` of the newest sink input on the default sink. Sink input
+    /// indices only grow, so the highest one is the most recently started stream
+    pub fn newest_app_name(&self) -> Option<String> {
+        let target_sink_index = self.get_sink_index(&self.default_sink_name)?;
+        let output = Command::new("pactl")
+            .args(["list", "sink-inputs"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+
+        let mut newest: Option<(u32, String)> = None;
+        let mut current: Option<(u32, Option<u32>)> = None; // (input index, sink index)
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("Sink Input #") {
+                current = rest.parse().ok().map(|index| (index, None));
+            } else if let Some(sink) = line.strip_prefix("Sink:") {
+                if let Some((_, ref mut current_sink)) = current {
+                    *current_sink = sink.trim().parse().ok();
+                }
+            } else if let Some(name) = line.strip_prefix("application.name = ") {
+                if let Some((index, current_sink)) = current {
+                    let is_newer = match newest {
+                        Some((newest_index, _)) => index > newest_index,
+                        None => true,
+                    };
+                    if matched_sink(current_sink, target_sink_index) && is_newer {
+                        newest = Some((index, name.trim_matches('"').to_string()));
                     }
                 }
+            }
+        }
+        newest.map(|(_, name)| name)
+    }
+
+    // Current percent of a sink, None if it isn't present or can't be read
+    #[inline]
+    fn fetch_sink_volume(&self, sink_name: &str) -> Option<u8> {
+        let target = self.with_default_fallback(sink_name);
+        let sink_name: &str = &target;
+        if let (sink, Some(channel)) = split_channel(sink_name) {
+            return self
+                .fetch_channel_volumes(sink)
+                .and_then(|volumes| volumes.get(channel).copied())
+                .map(|volume| volume.min(u8::MAX as u32) as u8);
+        }
+
+        if sink_name.starts_with(NODE_PREFIX) {
+            return self
+                .resolve_sink_node_id(sink_name)
+                .and_then(fetch_node_volume);
+        }
+
+        let resolved_name = self.resolve_sink_name(sink_name)?;
+        let output = Command::new("pactl")
+            .args(&["get-sink-volume", &resolved_name])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        // Parse output like "Volume: front-left: 65536 /  100% / 0.00 dB"
+        parse_volume_field(&String::from_utf8_lossy(&output.stdout), self.read_mode)
+            .map(|vol| vol.min(u8::MAX as u32) as u8)
+    }
 
-                if let Some(idx) = current_input_index {
-                    if matched_app && matched_sink(current_sink, target_sink_index) {
-                        if let Some(vol) = matched_volume {
-                            results.push((idx, vol));
-                        }
+    // Average percent of an app's streams, None if none are playing
+    fn fetch_app_volume(&self, app_name: &str) -> Option<u8> {
+        let levels: Vec<u32> = if self.app_wpctl {
+            self.app_input_indices(app_name)
+                .into_iter()
+                .filter_map(fetch_node_level)
+                .map(|(volume, _)| volume as u32)
+                .collect()
+        } else {
+            self.get_matching_app_inputs(app_name)
+                .iter()
+                .map(|input| input.volume as u32)
+                .collect()
+        };
+        if levels.is_empty() {
+            return None;
+        }
+        Some((levels.iter().sum::<u32>() / levels.len() as u32) as u8)
+    }
+
+    /// Whether every stream of an app is muted, None if none are playing
+    pub fn get_mute_for_app(&self, app_name: &str) -> Option<bool> {
+        if self.app_wpctl {
+            let muted: Vec<bool> = self
+                .app_input_indices(app_name)
+                .into_iter()
+                .filter_map(fetch_node_level)
+                .map(|(_, muted)| muted)
+                .collect();
+            return (!muted.is_empty()).then(|| muted.iter().all(|&muted| muted));
+        }
+
+        let inputs = self.get_matching_app_inputs(app_name);
+        (!inputs.is_empty()).then(|| inputs.iter().all(|input| input.muted))
+    }
+
+    pub fn get_app_input_count(&self, app_name: &str) -> usize {
+        self.rescan_app_inputs(app_name).len()
+    }
+
+    /// Node ids of an app's playback streams as `wpctl status` lists them, which
+    /// also shows PipeWire-native streams that pactl's introspection can miss
+    fn app_node_ids(&self, app_name: &str) -> Vec<u32> {
+        // wpctl status shows no roles; pactl's matches are node ids under pipewire-pulse
+        if app_name.starts_with(ROLE_PREFIX) {
+            return Vec::new();
+        }
+        matching_stream_nodes(&list_stream_nodes(), app_name)
+    }
+
+    /// Status of every app mapping from a single `pactl list sink-inputs` (plus one
+    /// `wpctl status` in wpctl mode), refreshing the cached stream indices and volumes
+    pub fn scan_app_statuses(&self, app_names: &[String]) -> Vec<AppStatus> {
+        let sink_inputs = self
+            .get_sink_index(&self.default_sink_name)
+            .and_then(|sink| {
+                let output = Command::new("pactl")
+                    .args(["list", "sink-inputs"])
+                    .output()
+                    .ok()?;
+                output
+                    .status
+                    .success()
+                    .then(|| (sink, String::from_utf8_lossy(&output.stdout).into_owned()))
+            });
+        let stream_nodes = if self.app_wpctl {
+            list_stream_nodes()
+        } else {
+            Vec::new()
+        };
+
+        app_names
+            .iter()
+            .map(|app_name| {
+                let inputs = sink_inputs.as_ref().map_or_else(Vec::new, |(sink, text)| {
+                    parse_app_inputs(text, app_name, *sink, self.read_mode)
+                });
+
+                // Same lookups as rescan_app_inputs and fetch_app_volume, on the snapshot
+                let mut indices = if self.app_wpctl && !app_name.starts_with(ROLE_PREFIX) {
+                    matching_stream_nodes(&stream_nodes, app_name)
+                } else {
+                    Vec::new()
+                };
+                if indices.is_empty() {
+                    indices = inputs.iter().map(|input| input.index).collect();
+                }
+                let levels: Vec<(u8, bool)> = if self.app_wpctl {
+                    indices
+                        .iter()
+                        .copied()
+                        .filter_map(fetch_node_level)
+                        .collect()
+                } else {
+                    inputs
+                        .iter()
+                        .map(|input| (input.volume, input.muted))
+                        .collect()
+                };
+
+                let volume = (!levels.is_empty()).then(|| {
+                    let sum: u32 = levels.iter().map(|&(volume, _)| volume as u32).sum();
+                    (sum / levels.len() as u32) as u8
+                });
+                if let Some(value) = volume {
+                    if let Ok(mut cache) = self.app_volume_cache.lock() {
+                        cache.insert(
+                            app_name.to_string(),
+                            CachedVolume {
+                                value,
+                                timestamp: Instant::now(),
+                            },
+                        );
                     }
                 }
+                let input_count = indices.len();
+                if let Ok(mut cache) = self.app_input_cache.lock() {
+                    cache.insert(app_name.to_string(), indices);
+                }
+
+                AppStatus {
+                    input_count,
+                    volume,
+                    muted: (!levels.is_empty()).then(|| levels.iter().all(|&(_, muted)| muted)),
+                    display_name: if app_name.starts_with(ROLE_PREFIX) {
+                        None
+                    } else {
+                        inputs
+                            .into_iter()
+                            .find_map(|input| input.display_name)
+                            .filter(|name| !name.is_empty())
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Look up an app's sink inputs again and replace its cached indices. In wpctl
+    /// mode these are node ids, from `wpctl status` first; sink-input indices are
+    /// node ids under pipewire-pulse, so pactl's matches still work as a fallback
+    fn rescan_app_inputs(&self, app_name: &str) -> Vec<u32> {
+        let mut indices = if self.app_wpctl {
+            self.app_node_ids(app_name)
+        } else {
+            Vec::new()
+        };
+        if indices.is_empty() {
+            indices = self
+                .get_matching_app_inputs(app_name)
+                .iter()
+                .map(|input| input.index)
+                .collect();
+        }
+        if let Ok(mut cache) = self.app_input_cache.lock() {
+            cache.insert(app_name.to_string(), indices.clone());
+        }
+        indices
+    }
+
+    // Cached sink-input indices, scanning only when the app has none cached
+    fn app_input_indices(&self, app_name: &str) -> Vec<u32> {
+        if let Ok(cache) = self.app_input_cache.lock() {
+            if let Some(indices) = cache.get(app_name).filter(|indices| !indices.is_empty()) {
+                return indices.clone();
             }
         }
+        self.rescan_app_inputs(app_name)
+    }
+
+    /// Run `pactl <command> <index> <value>`, or `wpctl <wpctl_command> <id> <value>`
+    /// in wpctl mode, for every stream of an app. Returns false if the app has no streams
+    fn run_on_app_inputs(
+        &self,
+        app_name: &str,
+        command: &str,
+        wpctl_command: &str,
+        value: &str,
+    ) -> bool {
+        let (program, command) = if self.app_wpctl {
+            ("wpctl", wpctl_command)
+        } else {
+            ("pactl", command)
+        };
+        let run = |indices: &[u32]| {
+            indices.iter().all(|index| {
+                self.run_change(program, &[command, &index.to_string(), value])
+                    .is_ok()
+            })
+        };
+
+        let indices = self.app_input_indices(app_name);
+        if !indices.is_empty() && run(&indices) {
+            return true;
+        }
+        // A stream went away or appeared since the last rescan
+        let indices = self.rescan_app_inputs(app_name);
+        !indices.is_empty() && run(&indices)
+    }
+
+    // Sink argument for pactl; node ids double as sink indices under pipewire-pulse
+    fn pactl_sink(&self, sink: &str) -> Option<String> {
+        if sink.starts_with(NODE_PREFIX) {
+            self.resolve_sink_node_id(sink).map(|id| id.to_string())
+        } else {
+            self.resolve_sink_name(sink).map(Cow::into_owned)
+        }
+    }
+
+    /// Volume percent of each channel of a sink, in channel order
+    fn fetch_channel_volumes(&self, sink: &str) -> Option<Vec<u32>> {
+        let output = Command::new("pactl")
+            .args(["get-sink-volume", &self.pactl_sink(sink)?])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(parse_channel_volumes(
+            &String::from_utf8_lossy(&output.stdout),
+            self.read_mode,
+        ))
+    }
+
+    // Set one channel, leaving the sink's other channels as they are
+    fn set_channel_volume(&self, sink: &str, channel: usize, volume_percent: u8) -> Result<()> {
+        let (Some(pactl_sink), Some(mut volumes)) =
+            (self.pactl_sink(sink), self.fetch_channel_volumes(sink))
+        else {
+            return Err(anyhow!("Sink '{}' not found", sink));
+        };
+        let Some(volume) = volumes.get_mut(channel) else {
+            return Err(anyhow!("Sink '{}' has no channel {}", sink, channel));
+        };
+        *volume = volume_percent as u32;
+
+        let mut args = vec!["set-sink-volume".to_string(), pactl_sink];
+        args.extend(volumes.iter().map(|volume| format!("{}%", volume)));
+        self.run_change("pactl", &args)
+    }
+
+    /// Node id for a `node:<id>` mapping. A non-numeric value is matched as a
+    /// `device.description` substring, so the mapping survives USB re-enumeration
+    pub fn resolve_sink_node_id(&self, mapping: &str) -> Option<u32> {
+        let node = mapping.strip_prefix(NODE_PREFIX)?.trim();
+        node.parse::<u32>()
+            .ok()
+            .or_else(|| self.sink_by_description(node).and_then(|sink| sink.node_id))
+    }
+
+    /// Sink name to pass to pactl. `desc:<text>` opts into matching the sink's
+    /// `device.description` instead of its exact node name
+    fn resolve_sink_name<'a>(&self, mapping: &'a str) -> Option<Cow<'a, str>> {
+        match mapping.strip_prefix(DESC_PREFIX) {
+            Some(description) => self
+                .sink_by_description(description.trim())
+                .and_then(|sink| sink.name)
+                .map(Cow::Owned),
+            None => Some(Cow::Borrowed(mapping)),
+        }
+    }
 
-        results
+    /// Friendly `application.name` of the first stream matching a mapping, if it is
+    /// playing. Role mappings cover several apps, so they keep their own label
+    pub fn resolve_app_display_name(&self, mapping: &str) -> Option<String> {
+        if mapping.starts_with(ROLE_PREFIX) {
+            return None;
+        }
+        self.get_matching_app_inputs(mapping)
+            .into_iter()
+            .find_map(|input| input.display_name)
+            .filter(|name| !name.is_empty())
     }
 
     pub fn set_volume_for_sink(&self, sink_name: &str, volume_percent: u8) -> Result<()> {
@@ -152,28 +525,53 @@ impl PipeWireController {
             cache.remove(sink_name);
         }
 
+        let target = self.with_default_fallback(sink_name);
+        let sink_name: &str = &target;
+        if let (sink, Some(channel)) = split_channel(sink_name) {
+            return self.set_channel_volume(sink, channel, volume_percent);
+        }
+
+        // node:<id> mappings go straight to wpctl by node id
+        if sink_name.starts_with(NODE_PREFIX) {
+            let node_id = self
+                .resolve_sink_node_id(sink_name)
+                .ok_or_else(|| anyhow!("Sink '{}' did not resolve to a node id", sink_name))?;
+            return self.run_change(
+                "wpctl",
+                &[
+                    "set-volume",
+                    &node_id.to_string(),
+                    &format!("{}%", volume_percent),
+                ],
+            );
+        }
+
+        let resolved_name = self
+            .resolve_sink_name(sink_name)
+            .ok_or_else(|| anyhow!("No sink description matches '{}'", sink_name))?;
+
         // Use pactl to set sink volume directly
-        Command::new("pactl")
-            .args(&[
+        self.run_change(
+            "pactl",
+            &[
                 "set-sink-volume",
-                sink_name,
+                &resolved_name,
                 &format!("{}%", volume_percent),
-            ])
-            .output()?;
-        Ok(())
+            ],
+        )
     }
 
-    pub fn get_volume_for_sink(&self, sink_name: &str) -> u8 {
+    pub fn get_volume_for_sink(&self, sink_name: &str) -> Option<u8> {
         // Check cache first
         if let Ok(cache) = self.sink_volume_cache.lock() {
             if let Some(cached) = cache.get(sink_name) {
                 if cached.timestamp.elapsed() < VOLUME_CACHE_TTL {
-                    return cached.value;
+                    return Some(cached.value);
                 }
             }
         }
 
-        let result = Self::fetch_sink_volume(sink_name);
+        let result = self.fetch_sink_volume(sink_name)?;
 
         // Update cache
         if let Ok(mut cache) = self.sink_volume_cache.lock() {
@@ -186,28 +584,7 @@ impl PipeWireController {
             );
         }
 
-        result
-    }
-
-    #[inline]
-    fn fetch_sink_volume(sink_name: &str) -> u8 {
-        if let Ok(output) = Command::new("pactl")
-            .args(&["get-sink-volume", sink_name])
-            .output()
-        {
-            if output.status.success() {
-                let text = String::from_utf8_lossy(&output.stdout);
-                // Parse output like "Volume: front-left: 65536 /  100% / 0.00 dB"
-                for part in text.split('/') {
-                    if let Some(pct) = part.trim().strip_suffix('%') {
-                        if let Ok(vol) = pct.trim().parse::<u8>() {
-                            return vol;
-                        }
-                    }
-                }
-            }
-        }
-        50 // Default fallback
+        Some(result)
     }
 
     pub fn set_volume_for_app(&self, app_name: &str, volume_percent: u8) -> Result<()> {
@@ -215,39 +592,56 @@ impl PipeWireController {
             cache.remove(app_name);
         }
 
-        let matching_inputs = self.get_matching_app_inputs(app_name);
-        if matching_inputs.is_empty() {
-            eprintln!(
+        // No stream took the volume, so there is nothing to confirm
+        let volume = format!("{}%", volume_percent);
+        if !self.run_on_app_inputs(app_name, "set-sink-input-volume", "set-volume", &volume) {
+            return Err(anyhow!(
                 "App '{}' not found on sink '{}' in sink inputs",
-                app_name, self.default_sink_name
-            );
-            return Ok(());
+                app_name,
+                self.default_sink_name
+            ));
         }
 
-        for (input_index, _) in &matching_inputs {
-            let _ = Command::new("pactl")
-                .args(&[
-                    "set-sink-input-volume",
-                    &input_index.to_string(),
-                    &format!("{}%", volume_percent),
-                ])
-                .output();
+        Ok(())
+    }
+
+    pub fn set_mute_for_sink(&self, sink_name: &str, muted: bool) -> Result<()> {
+        let flag = if muted { "1" } else { "0" };
+        let target = self.with_default_fallback(sink_name);
+        // Mute is per sink; a channel fader mutes by turning its channel down instead
+        let (sink_name, _) = split_channel(&target);
+
+        if sink_name.starts_with(NODE_PREFIX) {
+            if let Some(node_id) = self.resolve_sink_node_id(sink_name) {
+                self.run_change("wpctl", &["set-mute", &node_id.to_string(), flag])?;
+            }
+            return Ok(());
         }
 
+        let Some(resolved_name) = self.resolve_sink_name(sink_name) else {
+            return Ok(());
+        };
+        self.run_change("pactl", &["set-sink-mute", &resolved_name, flag])?;
         Ok(())
     }
 
-    pub fn get_volume_for_app(&self, app_name: &str) -> u8 {
+    pub fn set_mute_for_app(&self, app_name: &str, muted: bool) -> Result<()> {
+        let flag = if muted { "1" } else { "0" };
+        self.run_on_app_inputs(app_name, "set-sink-input-mute", "set-mute", flag);
+        Ok(())
+    }
+
+    pub fn get_volume_for_app(&self, app_name: &str) -> Option<u8> {
         // Check cache first
         if let Ok(cache) = self.app_volume_cache.lock() {
             if let Some(cached) = cache.get(app_name) {
                 if cached.timestamp.elapsed() < VOLUME_CACHE_TTL {
-                    return cached.value;
+                    return Some(cached.value);
                 }
             }
         }
 
-        let result = self.fetch_app_volume(app_name);
+        let result = self.fetch_app_volume(app_name)?;
 
         // Update cache
         if let Ok(mut cache) = self.app_volume_cache.lock() {
@@ -260,25 +654,573 @@ impl PipeWireController {
             );
         }
 
-        result
+        Some(result)
+    }
+}
+
+impl AudioBackend for PipeWireController {
+    fn set_volume(&self, kind: TargetKind, target: &str, volume_percent: u8) -> Result<()> {
+        match kind {
+            TargetKind::Sink => self.set_volume_for_sink(target, volume_percent),
+            TargetKind::App => self.set_volume_for_app(target, volume_percent),
+        }
     }
 
-    fn fetch_app_volume(&self, app_name: &str) -> u8 {
-        let matching_inputs = self.get_matching_app_inputs(app_name);
-        if matching_inputs.is_empty() {
-            return 50;
+    fn get_volume(&self, kind: TargetKind, target: &str) -> Option<u8> {
+        match kind {
+            TargetKind::Sink => self.get_volume_for_sink(target),
+            TargetKind::App => self.get_volume_for_app(target),
         }
-        let sum: u32 = matching_inputs.iter().map(|(_, v)| *v as u32).sum();
-        (sum / matching_inputs.len() as u32) as u8
     }
 
-    pub fn is_app_available(&self, app_name: &str) -> bool {
-        !self.get_matching_app_inputs(app_name).is_empty()
+    fn set_mute(&self, kind: TargetKind, target: &str, muted: bool) -> Result<()> {
+        match kind {
+            TargetKind::Sink => self.set_mute_for_sink(target, muted),
+            TargetKind::App => self.set_mute_for_app(target, muted),
+        }
     }
 
-    pub fn get_app_input_count(&self, app_name: &str) -> usize {
-        self.get_matching_app_inputs(app_name).len()
+    fn list_sinks(&self) -> Vec<String> {
+        list_sink_names()
+    }
+
+    fn list_apps(&self) -> Vec<String> {
+        list_app_names()
+    }
+
+    fn app_input_count(&self, app_name: &str) -> usize {
+        self.get_app_input_count(app_name)
+    }
+
+    fn newest_app(&self) -> Option<String> {
+        self.newest_app_name()
+    }
+
+    fn app_muted(&self, app_name: &str) -> Option<bool> {
+        self.get_mute_for_app(app_name)
+    }
+
+    fn app_display_name(&self, app_name: &str) -> Option<String> {
+        self.resolve_app_display_name(app_name)
     }
+
+    fn app_statuses(&self, app_names: &[String]) -> Vec<AppStatus> {
+        self.scan_app_statuses(app_names)
+    }
+
+    fn rescan(&self) {
+        if let Ok(mut lookups) = self.sink_lookups.lock() {
+            *lookups = SinkLookups::default();
+        }
+    }
+
+    fn set_read_mode(&mut self, mode: VolumeReadMode) {
+        self.read_mode = mode;
+        // Cached levels were read the other way
+        if let Ok(mut cache) = self.sink_volume_cache.lock() {
+            cache.clear();
+        }
+        if let Ok(mut cache) = self.app_volume_cache.lock() {
+            cache.clear();
+        }
+    }
+
+    fn set_default_fallbacks(&mut self, targets: HashSet<String>) {
+        self.default_fallbacks = targets;
+        // Cached levels may have been read from the default sink
+        if let Ok(mut cache) = self.sink_volume_cache.lock() {
+            cache.clear();
+        }
+    }
+
+    fn set_app_wpctl(&mut self, enabled: bool) {
+        self.app_wpctl = enabled;
+        // Cached streams are sink-input indices or node ids depending on the mode
+        if let Ok(mut cache) = self.app_input_cache.lock() {
+            cache.clear();
+        }
+        if let Ok(mut cache) = self.app_volume_cache.lock() {
+            cache.clear();
+        }
+    }
+
+    fn sink_name(&self, target: &str) -> Option<String> {
+        let (target, _) = split_channel(target);
+        match target.strip_prefix(NODE_PREFIX) {
+            // Node ids double as pactl sink indices under pipewire-pulse
+            Some(node) => match node.trim().parse::<u32>() {
+                Ok(id) => self
+                    .short_sinks()
+                    .into_iter()
+                    .find_map(|(index, name)| (index == id).then_some(name)),
+                Err(_) => self
+                    .sink_by_description(node.trim())
+                    .and_then(|sink| sink.name),
+            },
+            None => self.resolve_sink_name(target).map(Cow::into_owned),
+        }
+    }
+}
+
+/// Sound server details as reported by `pactl info`
+#[derive(Debug, Clone, Default)]
+pub struct ServerInfo {
+    pub name: String,
+    pub version: String,
+    pub default_sink: String,
+    pub default_source: String,
+}
+
+impl ServerInfo {
+    /// pipewire-pulse reports itself as e.g. "PulseAudio (on PipeWire 1.0.5)"
+    pub fn is_pipewire(&self) -> bool {
+        self.name.contains("PipeWire")
+    }
+}
+
+impl PipeWireController {
+    /// Name, version and defaults of the running sound server
+    pub fn server_info() -> Result<ServerInfo> {
+        let output = Command::new("pactl")
+            .arg("info")
+            .output()
+            .map_err(|e| anyhow!("Failed to run pactl: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "pactl info failed; is PipeWire/PulseAudio running?"
+            ));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |key: &str| {
+            text.lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+        Ok(ServerInfo {
+            name: field("Server Name"),
+            version: field("Server Version"),
+            default_sink: field("Default Sink"),
+            default_source: field("Default Source"),
+        })
+    }
+}
+
+/// A sink or playing app as pavucontrol names it, offered for import as a mapping
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportCandidate {
+    pub is_sink: bool,
+    pub friendly: String, // Sink description or app name, as shown in pavucontrol
+    pub target: String,   // Match string the mapping needs
+}
+
+impl PipeWireController {
+    /// Sinks from `pactl list sinks` by description, then the apps playing now
+    pub fn import_candidates() -> Vec<ImportCandidate> {
+        let mut candidates = Vec::new();
+        if let Ok(output) = Command::new("pactl").args(["list", "sinks"]).output() {
+            let text = String::from_utf8_lossy(&output.stdout);
+            let mut name: Option<String> = None;
+            for line in text.lines().map(str::trim) {
+                if let Some(value) = line.strip_prefix("Name:") {
+                    name = Some(value.trim().to_string());
+                } else if let Some(description) = line.strip_prefix("Description:") {
+                    // The node name matches exactly, whatever the description says
+                    if let Some(target) = name.take() {
+                        candidates.push(ImportCandidate {
+                            is_sink: true,
+                            friendly: description.trim().to_string(),
+                            target,
+                        });
+                    }
+                }
+            }
+        }
+        candidates.extend(list_app_names().into_iter().map(|app| ImportCandidate {
+            is_sink: false,
+            friendly: app.clone(),
+            target: app,
+        }));
+        candidates
+    }
+}
+
+/// Play a short beep on one sink so the user can tell which output a mapping
+/// points at. Blocks until the tone has played
+pub fn play_test_tone(sink_name: &str) -> Result<()> {
+    const RATE: u32 = 48000;
+    const FREQ: f32 = 880.0;
+    const SECS: f32 = 0.4;
+
+    // Mono s16le sine with 20 ms fades so the start and end don't click
+    let total = (RATE as f32 * SECS) as usize;
+    let fade = (RATE / 50) as usize;
+    let mut samples = Vec::with_capacity(total * 2);
+    for i in 0..total {
+        let envelope = (i.min(total - i) as f32 / fade as f32).min(1.0);
+        let phase = 2.0 * std::f32::consts::PI * FREQ * i as f32 / RATE as f32;
+        let sample = (phase.sin() * envelope * 0.3 * i16::MAX as f32) as i16;
+        samples.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    let mut child = Command::new("pacat")
+        .args([
+            "--playback",
+            "--device",
+            sink_name,
+            "--format=s16le",
+            "--rate=48000",
+            "--channels=1",
+        ])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run pacat: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&samples)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("pacat could not play on sink '{}'", sink_name));
+    }
+    Ok(())
+}
+
+// Sink inputs in `pactl list sink-inputs` output that play on `target_sink` and match
+// a mapping by application name or binary, or by media.role for `role:` mappings
+fn parse_app_inputs(
+    text: &str,
+    app_name: &str,
+    target_sink: u32,
+    mode: VolumeReadMode,
+) -> Vec<AppInput> {
+    let app_name_lower = app_name.to_lowercase();
+    let normalized_config = normalize_app_name(&app_name_lower);
+    // A role mapping matches every stream with that media.role, whatever the app
+    let role = app_name_lower.strip_prefix(ROLE_PREFIX).map(str::trim);
+
+    let mut results = Vec::new();
+    let mut current_input_index: Option<u32> = None;
+    let mut current_sink: Option<u32> = None;
+    let mut matched_app: bool = false;
+    let mut matched_volume: Option<u8> = None;
+    let mut muted = false;
+    let mut display_name: Option<String> = None;
+
+    for line in text.lines() {
+        if line.starts_with("Sink Input #") {
+            if let Some(idx) = current_input_index {
+                if matched_app && matched_sink(current_sink, target_sink) {
+                    if let Some(vol) = matched_volume {
+                        results.push(AppInput {
+                            index: idx,
+                            volume: vol,
+                            muted,
+                            display_name: display_name.take(),
+                        });
+                    }
+                }
+            }
+            if let Some(idx_str) = line
+                .strip_prefix("Sink Input #")
+                .and_then(|s| s.split_whitespace().next())
+            {
+                current_input_index = idx_str.parse::<u32>().ok();
+            }
+            current_sink = None;
+            matched_app = false;
+            matched_volume = None;
+            muted = false;
+            display_name = None;
+            continue;
+        }
+
+        if let Some(_) = current_input_index {
+            if line.trim().starts_with("Sink:") {
+                if let Some(val_str) = line.trim().strip_prefix("Sink:").map(|s| s.trim()) {
+                    current_sink = val_str.parse::<u32>().ok();
+                }
+            }
+
+            if display_name.is_none() {
+                if let Some(value) = line.trim().strip_prefix("application.name = ") {
+                    display_name = Some(value.trim_matches('"').to_string());
+                }
+            }
+
+            if !matched_app {
+                let line_lower = line.to_lowercase();
+                if let Some(role) = role {
+                    matched_app = line_lower
+                        .trim()
+                        .strip_prefix("media.role = ")
+                        .is_some_and(|value| value.trim_matches('"') == role);
+                } else {
+                    let normalized_line = normalize_app_name(&line_lower);
+                    if (line_lower.contains("application.name")
+                        && normalized_line.contains(&normalized_config))
+                        || (line_lower.contains("application.process.binary")
+                            && normalized_line.contains(&normalized_config))
+                    {
+                        matched_app = true;
+                    }
+                }
+            }
+
+            if let Some(value) = line.trim().strip_prefix("Mute:") {
+                muted = value.trim() == "yes";
+            }
+
+            if matched_volume.is_none() && line.contains("Volume:") {
+                matched_volume =
+                    parse_volume_field(line, mode).map(|vol| vol.min(u8::MAX as u32) as u8);
+            }
+        }
+    }
+
+    if let Some(idx) = current_input_index {
+        if matched_app && matched_sink(current_sink, target_sink) {
+            if let Some(vol) = matched_volume {
+                results.push(AppInput {
+                    index: idx,
+                    volume: vol,
+                    muted,
+                    display_name,
+                });
+            }
+        }
+    }
+
+    results
+}
+
+// Per-channel percents from `pactl get-sink-volume`, whose first line reads e.g.
+// "Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB"
+fn parse_channel_volumes(text: &str, mode: VolumeReadMode) -> Vec<u32> {
+    let Some(line) = text
+        .lines()
+        .find(|line| line.trim_start().starts_with("Volume:"))
+    else {
+        return Vec::new();
+    };
+    line.split(',')
+        .filter_map(|channel| parse_volume_field(channel, mode))
+        .collect()
+}
+
+// Volume percent of the first channel in a "65536 / 100% / 0.00 dB" report. In dB
+// mode the dB field is converted back to percent, falling back to the percent field
+fn parse_volume_field(text: &str, mode: VolumeReadMode) -> Option<u32> {
+    let percent = || {
+        text.split('/')
+            .find_map(|part| part.trim().strip_suffix('%')?.trim().parse().ok())
+    };
+    match mode {
+        VolumeReadMode::Percent => percent(),
+        VolumeReadMode::Decibels => text
+            .split('/')
+            .find_map(|part| {
+                // The dB field runs into the next channel or line
+                let field = part.split([',', '\n']).next()?.trim();
+                field.strip_suffix("dB")?.trim().parse::<f32>().ok()
+            })
+            .map(db_to_percent)
+            .or_else(percent),
+    }
+}
+
+// PipeWire's percent is the cube root of the linear gain, so dB = 60 * log10(percent / 100);
+// "-inf dB" comes out as 0
+fn db_to_percent(db: f32) -> u32 {
+    (100.0 * 10f32.powf(db / 60.0)).round() as u32
+}
+
+fn fetch_node_volume(node_id: u32) -> Option<u8> {
+    fetch_node_level(node_id).map(|(volume, _)| volume)
+}
+
+// Volume and mute of a node via `wpctl get-volume`, which prints e.g. "Volume: 0.40 [MUTED]"
+fn fetch_node_level(node_id: u32) -> Option<(u8, bool)> {
+    let output = Command::new("wpctl")
+        .args(["get-volume", &node_id.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut fields = text.trim().strip_prefix("Volume:")?.split_whitespace();
+    let volume = fields.next()?.parse::<f32>().ok()?;
+    let muted = fields.any(|field| field == "[MUTED]");
+    Some(((volume * 100.0).round().clamp(0.0, 255.0) as u8, muted))
+}
+
+// Playback streams in `wpctl status` as (node id, name). They are listed under
+// "Streams:" in the Audio section, each followed by its more deeply indented
+// ports, e.g. "83. Firefox" then "84. output_FL  > Built-in Audio:playback_FL";
+// streams with only input_ ports are recordings
+fn parse_stream_nodes(text: &str) -> Vec<(u32, String)> {
+    let mut streams = Vec::new();
+    let mut in_audio = false;
+    let mut in_streams = false;
+    let mut stream_indent = None;
+    let mut current: Option<(u32, String)> = None;
+    let mut plays = false; // Current stream has an output port
+
+    for line in text.lines() {
+        // Sections such as "Audio" and "Video" start at the left edge
+        let section_start = !line.starts_with([' ', '│', '├', '└']) && !line.trim().is_empty();
+        if section_start {
+            in_audio = line.trim() == "Audio";
+        }
+        if section_start || line.contains("Streams:") {
+            streams.extend(current.take().filter(|_| plays));
+            in_streams = in_audio && line.contains("Streams:");
+            stream_indent = None;
+            continue;
+        }
+        if !in_streams {
+            continue;
+        }
+
+        let entry = line.trim_start_matches([' ', '│', '*']);
+        let indent = line.chars().count() - entry.chars().count();
+        let Some((id, name)) = entry
+            .split_once(". ")
+            .and_then(|(id, name)| Some((id.parse::<u32>().ok()?, name.trim())))
+        else {
+            continue;
+        };
+
+        match stream_indent {
+            Some(stream) if indent > stream => plays |= name.starts_with("output_"),
+            _ => {
+                streams.extend(current.take().filter(|_| plays));
+                stream_indent = Some(indent);
+                current = Some((id, name.to_string()));
+                plays = false;
+            }
+        }
+    }
+    streams.extend(current.filter(|_| plays));
+    streams
+}
+
+// Playback streams from `wpctl status`, empty if wpctl fails
+fn list_stream_nodes() -> Vec<(u32, String)> {
+    match Command::new("wpctl").arg("status").output() {
+        Ok(output) if output.status.success() => {
+            parse_stream_nodes(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Node ids of the streams whose name matches an app mapping (normalized)
+fn matching_stream_nodes(streams: &[(u32, String)], app_name: &str) -> Vec<u32> {
+    let wanted = normalize_app_name(&app_name.to_lowercase());
+    streams
+        .iter()
+        .filter(|(_, name)| normalize_app_name(&name.to_lowercase()).contains(&wanted))
+        .map(|&(id, _)| id)
+        .collect()
+}
+
+// A sink from `pactl list sinks` matched by its description
+#[derive(Clone)]
+struct SinkInfo {
+    node_id: Option<u32>, // object.id, falling back to the pactl index
+    name: Option<String>, // node name usable with pactl
+}
+
+// First sink whose description contains the given text (case-insensitive)
+fn find_sink_by_description(description: &str) -> Option<SinkInfo> {
+    let output = Command::new("pactl")
+        .args(["list", "sinks"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let wanted = description.to_lowercase();
+
+    let mut current_index: Option<u32> = None;
+    let mut object_id: Option<u32> = None;
+    let mut name: Option<String> = None;
+    let mut matched = false;
+
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("Sink #") {
+            if matched {
+                break;
+            }
+            current_index = rest.split_whitespace().next().and_then(|s| s.parse().ok());
+            object_id = None;
+            name = None;
+            continue;
+        }
+
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line
+            .strip_prefix("Description:")
+            .or_else(|| line.strip_prefix("device.description = "))
+        {
+            if value.trim_matches('"').to_lowercase().contains(&wanted) {
+                matched = true;
+            }
+        } else if let Some(value) = line.strip_prefix("object.id = ") {
+            object_id = value.trim_matches('"').parse().ok();
+        }
+    }
+
+    matched.then(|| SinkInfo {
+        node_id: object_id.or(current_index),
+        name,
+    })
+}
+
+// Sink names from `pactl list short sinks` (index, name, driver, ...)
+fn list_sink_names() -> Vec<String> {
+    list_short_sinks()
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect()
+}
+
+// Index and name of each sink in `pactl list short sinks`
+fn list_short_sinks() -> Vec<(u32, String)> {
+    let Ok(output) = Command::new("pactl")
+        .args(["list", "short", "sinks"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let index = fields.next()?.trim().parse().ok()?;
+            Some((index, fields.next()?.to_string()))
+        })
+        .collect()
+}
+
+// Distinct `application.name`s of all playing streams
+fn list_app_names() -> Vec<String> {
+    let Ok(output) = Command::new("pactl").args(["list", "sink-inputs"]).output() else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("application.name = "))
+        .map(|name| name.trim_matches('"').to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
 }
 
 fn matched_sink(current_sink: Option<u32>, target_sink: u32) -> bool {
@@ -298,3 +1240,181 @@ fn normalize_app_name(name: &str) -> String {
         .replace("-", "")
         .replace("_", "")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `pactl list sink-inputs` with two streams on sink 48 and one on sink 50
+    const SINK_INPUTS: &str = r#"Sink Input #77
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 76
+	Sink: 48
+	Sample Specification: float32le 2ch 48000Hz
+	Channel Map: front-left,front-right
+	Corked: no
+	Mute: no
+	Volume: front-left: 52429 /  80% / -5.81 dB,   front-right: 52429 /  80% / -5.81 dB
+	        balance 0.00
+	Properties:
+		media.name = "AudioStream"
+		application.name = "Firefox"
+		application.process.binary = "firefox"
+		media.role = "Music"
+
+Sink Input #79
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 81
+	Sink: 48
+	Mute: yes
+	Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: 32768 /  50% / -18.06 dB
+	        balance 0.00
+	Properties:
+		application.name = "Google Chrome"
+		application.process.binary = "chrome"
+		media.role = "Phone"
+
+Sink Input #83
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 90
+	Sink: 50
+	Mute: no
+	Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
+	        balance 0.00
+	Properties:
+		application.name = "Firefox"
+		application.process.binary = "firefox"
+		media.role = "Music"
+"#;
+
+    fn matched(app_name: &str, mode: VolumeReadMode) -> Vec<(u32, u8, bool, Option<String>)> {
+        parse_app_inputs(SINK_INPUTS, app_name, 48, mode)
+            .into_iter()
+            .map(|input| (input.index, input.volume, input.muted, input.display_name))
+            .collect()
+    }
+
+    #[test]
+    fn reads_channel_volumes_from_pactl() {
+        let stereo = "Volume: front-left: 26214 /  40% / -23.88 dB,   front-right: 65536 / 100% / 0.00 dB\n        balance -0.60\n";
+        assert_eq!(
+            parse_channel_volumes(stereo, VolumeReadMode::Percent),
+            vec![40, 100]
+        );
+        assert_eq!(
+            parse_channel_volumes(stereo, VolumeReadMode::Decibels),
+            vec![40, 100]
+        );
+
+        let surround = "Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB,   rear-left: 0 /   0% / -inf dB,   rear-right: 0 /   0% / -inf dB\n        balance 0.00\n";
+        assert_eq!(
+            parse_channel_volumes(surround, VolumeReadMode::Decibels),
+            vec![100, 100, 0, 0]
+        );
+        assert!(parse_channel_volumes("", VolumeReadMode::Percent).is_empty());
+    }
+
+    #[test]
+    fn reads_the_first_channel_of_a_volume_field() {
+        let line =
+            "Volume: front-left: 52429 /  80% / -5.81 dB,   front-right: 26214 /  40% / -23.88 dB";
+        assert_eq!(parse_volume_field(line, VolumeReadMode::Percent), Some(80));
+        assert_eq!(parse_volume_field(line, VolumeReadMode::Decibels), Some(80));
+        // Boosted volumes and a missing dB field
+        let boosted = "Volume: mono: 98304 / 150% / 10.57 dB";
+        assert_eq!(
+            parse_volume_field(boosted, VolumeReadMode::Decibels),
+            Some(150)
+        );
+        let no_db = "Volume: mono: 32768 /  50%";
+        assert_eq!(
+            parse_volume_field(no_db, VolumeReadMode::Decibels),
+            Some(50)
+        );
+        assert_eq!(
+            parse_volume_field("Mute: no", VolumeReadMode::Percent),
+            None
+        );
+    }
+
+    #[test]
+    fn converts_decibels_to_pipewire_percent() {
+        assert_eq!(db_to_percent(0.0), 100);
+        assert_eq!(db_to_percent(-18.06), 50);
+        assert_eq!(db_to_percent(f32::NEG_INFINITY), 0);
+        assert_eq!(db_to_percent("-inf".parse().unwrap()), 0);
+    }
+
+    #[test]
+    fn finds_playback_streams_in_wpctl_status() {
+        let status = "PipeWire 'pipewire-0' [1.0.5, user@host, cookie:1234]
+ └─ Clients:
+        33. WirePlumber                         [1.0.5, user@host, pid:812]
+
+Audio
+ ├─ Devices:
+ │      42. Built-in Audio                      [alsa]
+ │
+ ├─ Sinks:
+ │  *   48. Built-in Audio Analog Stereo        [vol: 0.40]
+ │
+ ├─ Sources:
+ │  *   49. Built-in Audio Analog Stereo        [vol: 1.00]
+ │
+ └─ Streams:
+        83. Firefox
+             84. output_FL       > Built-in Audio:playback_FL\t[active]
+             85. output_FR       > Built-in Audio:playback_FR\t[active]
+        90. Zoom
+             91. input_FL        < Built-in Audio:capture_FL\t[active]
+        95. spotify
+             96. output_FL       > Built-in Audio:playback_FL\t[active]
+
+Video
+ └─ Streams:
+        100. Camera
+             101. output_0
+
+Settings
+ └─ Default Configured Devices:
+         0. Audio/Sink    alsa_output.pci-0000_00_1f.3.analog-stereo
+";
+        assert_eq!(
+            parse_stream_nodes(status),
+            vec![(83, "Firefox".to_string()), (95, "spotify".to_string())]
+        );
+        assert!(parse_stream_nodes("").is_empty());
+    }
+
+    #[test]
+    fn matches_sink_inputs_by_app_name_on_the_target_sink() {
+        assert_eq!(
+            matched("firefox", VolumeReadMode::Percent),
+            vec![(77, 80, false, Some("Firefox".to_string()))]
+        );
+        // "google-chrome" and "chrome" normalize to the same name
+        assert_eq!(
+            matched("google-chrome", VolumeReadMode::Decibels),
+            vec![(79, 50, true, Some("Google Chrome".to_string()))]
+        );
+        assert!(matched("discord", VolumeReadMode::Percent).is_empty());
+        assert!(parse_app_inputs(SINK_INPUTS, "firefox", 99, VolumeReadMode::Percent).is_empty());
+    }
+
+    #[test]
+    fn matches_sink_inputs_by_media_role() {
+        assert_eq!(
+            matched("role:phone", VolumeReadMode::Percent),
+            vec![(79, 50, true, Some("Google Chrome".to_string()))]
+        );
+        assert_eq!(
+            matched("role: Music", VolumeReadMode::Percent),
+            vec![(77, 80, false, Some("Firefox".to_string()))]
+        );
+        // A role mapping never falls back to name matching
+        assert!(matched("role:firefox", VolumeReadMode::Percent).is_empty());
+    }
+}
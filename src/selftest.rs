@@ -0,0 +1,68 @@
+use crate::config::Config;
+use crate::engine::AudioBackend;
+use crate::midi;
+use crate::pipewire_control::PipeWireController;
+
+/// `--selftest`: check the environment step by step without starting the GUI.
+/// Prints PASS/FAIL per check and returns false if any check failed
+pub fn run(primary_path: &str, fallback_path: &str) -> bool {
+    let mut all_passed = true;
+    let mut report = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("PASS  {} ({})", name, detail),
+        Err(reason) => {
+            println!("FAIL  {}: {}", name, reason);
+            all_passed = false;
+        }
+    };
+
+    // Later checks still run on the defaults if the config doesn't parse
+    let config = match Config::load_with_fallback(primary_path, fallback_path) {
+        Ok(config) => {
+            report(
+                "config parses",
+                Ok(format!("{} mappings", config.get_cc_mapping().len())),
+            );
+            config
+        }
+        Err(e) => {
+            report("config parses", Err(format!("{:#}", e)));
+            Config::default()
+        }
+    };
+
+    let selection = config.get_port_selection();
+    report(
+        "MIDI input port",
+        midi::find_input_port(&selection).ok_or_else(|| "no nanoKontrol2 input port".to_string()),
+    );
+    let output_result = if config.midi.led_feedback.unwrap_or(true) {
+        midi::find_output_port(&selection).ok_or_else(|| "no nanoKontrol2 output port".to_string())
+    } else {
+        Ok("not needed, led_feedback = false".to_string())
+    };
+    report("MIDI output port", output_result);
+
+    let backend_ok = PipeWireController::server_info()
+        .map(|info| info.name)
+        .map_err(|e| e.to_string());
+    let backend_available = backend_ok.is_ok();
+    report("audio backend", backend_ok);
+
+    let default_sink = config
+        .audio
+        .default_sink
+        .clone()
+        .unwrap_or_else(|| "master_sink".to_string());
+    let sink_result = if backend_available {
+        let controller = PipeWireController::new(false, &default_sink);
+        match controller.sink_name(&default_sink) {
+            Some(name) if controller.list_sinks().contains(&name) => Ok(name),
+            _ => Err(format!("default sink '{}' not found", default_sink)),
+        }
+    } else {
+        Err("skipped, no audio backend".to_string())
+    };
+    report("default sink", sink_result);
+
+    all_passed
+}
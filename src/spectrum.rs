@@ -1,12 +1,14 @@
 use rustfft::{num_complex::Complex, FftPlanner};
+use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 /// Number of frequency bands to display
 pub const NUM_BANDS: usize = 32;
 
-/// Sample rate for audio capture
-const SAMPLE_RATE: u32 = 44100;
+/// Fallback sample rate when the source's native rate can't be detected
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
 
 /// FFT size (must be power of 2) - smaller = lower latency but less frequency resolution
 const FFT_SIZE: usize = 512;
@@ -14,6 +16,55 @@ const FFT_SIZE: usize = 512;
 /// Read smaller chunks for faster updates (hop size)
 const HOP_SIZE: usize = 128; // ~2.9ms at 44100Hz
 
+/// RMS level below which a hop counts as silence
+const SILENCE_RMS: f32 = 1e-4;
+
+/// Consecutive silent hops before the analyzer idles (~0.5s at 44100Hz)
+const SILENCE_HOPS: usize = 172;
+
+/// Sample magnitude counted as a clip: full scale, allowing for 16-bit sources
+/// whose largest sample converts to just under 1.0
+const CLIP_LEVEL: f32 = 0.9999;
+
+/// Fall of the held sample peak per hop (~15 dB/s at 44100Hz)
+const SAMPLE_PEAK_DECAY: f32 = 0.995;
+
+/// Sleep between reads while idle on silence
+const IDLE_SLEEP: Duration = Duration::from_millis(50);
+
+/// Frequency range covered by the bands
+const MIN_FREQ: f32 = 20.0;
+const MAX_FREQ: f32 = 20000.0;
+
+/// Level range mapped onto the bar height, in dB
+pub const DB_FLOOR: f32 = -60.0;
+pub const DB_CEILING: f32 = 0.0;
+
+/// Spacing of the bands along the frequency axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FreqScale {
+    #[default]
+    Log,
+    Linear, // Even spacing, easier to read harmonics off
+}
+
+impl FreqScale {
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "linear" => FreqScale::Linear,
+            _ => FreqScale::Log,
+        }
+    }
+
+    /// Frequency at a position (0-1) along the axis
+    fn frequency_at(self, t: f32) -> f32 {
+        match self {
+            FreqScale::Log => MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(t),
+            FreqScale::Linear => MIN_FREQ + (MAX_FREQ - MIN_FREQ) * t,
+        }
+    }
+}
+
 /// Spectrum analyzer state shared between audio thread and UI
 #[derive(Clone)]
 pub struct SpectrumData {
@@ -27,6 +78,14 @@ pub struct SpectrumData {
     pub peaks_right: [f32; NUM_BANDS],
     /// Whether the analyzer is running
     pub running: bool,
+    /// Sample rate the monitor source is captured at
+    pub sample_rate: u32,
+    /// Frequency axis the bands were computed with
+    pub freq_scale: FreqScale,
+    /// Largest absolute sample of either channel, held and falling slowly (1.0 = 0 dBFS)
+    pub sample_peak: f32,
+    /// Hops with a sample at full scale since launch; only ever grows
+    pub clip_count: u64,
 }
 
 impl Default for SpectrumData {
@@ -37,6 +96,10 @@ impl Default for SpectrumData {
             bands_right: [0.0; NUM_BANDS],
             peaks_right: [0.0; NUM_BANDS],
             running: false,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            freq_scale: FreqScale::default(),
+            sample_peak: 0.0,
+            clip_count: 0,
         }
     }
 }
@@ -46,17 +109,47 @@ pub struct SpectrumAnalyzer {
     data: Arc<Mutex<SpectrumData>>,
     handle: Option<thread::JoinHandle<()>>,
     stop_flag: Arc<Mutex<bool>>,
+    stereo_flag: Arc<Mutex<bool>>,
+    weighting_flag: Arc<Mutex<bool>>,
+    scale_flag: Arc<Mutex<FreqScale>>,
 }
 
 impl SpectrumAnalyzer {
     pub fn new() -> Self {
         let data = Arc::new(Mutex::new(SpectrumData::default()));
         let stop_flag = Arc::new(Mutex::new(false));
+        let stereo_flag = Arc::new(Mutex::new(true));
+        let weighting_flag = Arc::new(Mutex::new(false));
+        let scale_flag = Arc::new(Mutex::new(FreqScale::default()));
 
         Self {
             data,
             handle: None,
             stop_flag,
+            stereo_flag,
+            weighting_flag,
+            scale_flag,
+        }
+    }
+
+    /// Analyze left/right separately (true) or a single mono sum (false, one FFT per hop)
+    pub fn set_stereo(&self, stereo: bool) {
+        if let Ok(mut s) = self.stereo_flag.lock() {
+            *s = stereo;
+        }
+    }
+
+    /// Apply A-weighting to the bands so the display follows perceived loudness
+    pub fn set_a_weighting(&self, enabled: bool) {
+        if let Ok(mut w) = self.weighting_flag.lock() {
+            *w = enabled;
+        }
+    }
+
+    /// Spread the bands logarithmically (default) or linearly over 20 Hz - 20 kHz
+    pub fn set_freq_scale(&self, scale: FreqScale) {
+        if let Ok(mut s) = self.scale_flag.lock() {
+            *s = scale;
         }
     }
 
@@ -72,10 +165,20 @@ impl SpectrumAnalyzer {
 
         let data = self.data.clone();
         let stop_flag = self.stop_flag.clone();
+        let stereo_flag = self.stereo_flag.clone();
+        let weighting_flag = self.weighting_flag.clone();
+        let scale_flag = self.scale_flag.clone();
         let sink_monitor = get_monitor_source(sink_name);
 
         let handle = thread::spawn(move || {
-            run_analyzer(data, stop_flag, &sink_monitor);
+            run_analyzer(
+                data,
+                stop_flag,
+                stereo_flag,
+                weighting_flag,
+                scale_flag,
+                &sink_monitor,
+            );
         });
 
         self.handle = Some(handle);
@@ -101,6 +204,11 @@ impl SpectrumAnalyzer {
         }
     }
 
+    /// Whether a capture thread is active
+    pub fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
+
     /// Get the current spectrum data
     pub fn get_data(&self) -> SpectrumData {
         self.data.lock().map(|d| d.clone()).unwrap_or_default()
@@ -129,17 +237,58 @@ fn get_monitor_source(sink_name: &str) -> String {
     }
 }
 
+/// Native sample rate of a capture source, as listed by `pactl list short sources`
+fn detect_sample_rate(source_name: &str) -> Option<u32> {
+    // Resolve the default sink alias to a concrete monitor name first
+    let source = if source_name == "@DEFAULT_SINK@.monitor" {
+        let output = Command::new("pactl")
+            .arg("get-default-sink")
+            .output()
+            .ok()?;
+        format!("{}.monitor", String::from_utf8_lossy(&output.stdout).trim())
+    } else {
+        source_name.to_string()
+    };
+
+    let output = Command::new("pactl")
+        .args(["list", "short", "sources"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    // Lines look like: "63  sink.monitor  module-null-sink.c  float32le 2ch 48000Hz  IDLE"
+    text.lines()
+        .find(|line| line.split_whitespace().nth(1) == Some(source.as_str()))?
+        .split_whitespace()
+        .find_map(|part| part.strip_suffix("Hz")?.parse::<u32>().ok())
+}
+
 /// Main analyzer loop running in separate thread
-fn run_analyzer(data: Arc<Mutex<SpectrumData>>, stop_flag: Arc<Mutex<bool>>, source_name: &str) {
+fn run_analyzer(
+    data: Arc<Mutex<SpectrumData>>,
+    stop_flag: Arc<Mutex<bool>>,
+    stereo_flag: Arc<Mutex<bool>>,
+    weighting_flag: Arc<Mutex<bool>>,
+    scale_flag: Arc<Mutex<FreqScale>>,
+    source_name: &str,
+) {
     use libpulse_binding::def::BufferAttr;
     use libpulse_binding::sample::{Format, Spec};
     use libpulse_binding::stream::Direction;
     use libpulse_simple_binding::Simple;
 
+    // Capture at the source's native rate so no resampling skews the band mapping
+    let sample_rate = detect_sample_rate(source_name).unwrap_or(DEFAULT_SAMPLE_RATE);
+    let mut scale = scale_flag.lock().map(|s| *s).unwrap_or_default();
+    let mut a_weights = a_weighting_table(scale);
+    if let Ok(mut d) = data.lock() {
+        d.sample_rate = sample_rate;
+    }
+
     let spec = Spec {
         format: Format::F32le,
         channels: 2, // Stereo capture
-        rate: SAMPLE_RATE,
+        rate: sample_rate,
     };
 
     // Low-latency buffer configuration
@@ -188,6 +337,11 @@ fn run_analyzer(data: Arc<Mutex<SpectrumData>>, stop_flag: Arc<Mutex<bool>>, sou
     let mut peak_values_left = [0.0f32; NUM_BANDS];
     let mut peak_values_right = [0.0f32; NUM_BANDS];
 
+    // Silence gate: count consecutive quiet hops to skip FFTs while nothing plays
+    let mut silent_hops = 0usize;
+
+    let mut sample_peak = 0.0f32;
+
     loop {
         // Check stop flag
         if let Ok(stop) = stop_flag.lock() {
@@ -208,19 +362,73 @@ fn run_analyzer(data: Arc<Mutex<SpectrumData>>, stop_flag: Arc<Mutex<bool>>, sou
             continue;
         }
 
-        // Deinterleave stereo samples and add to ring buffers
+        let rms =
+            (read_buffer.iter().map(|s| s * s).sum::<f32>() / read_buffer.len() as f32).sqrt();
+
+        // Sample peak from the raw samples, before any windowing or weighting
+        let hop_peak = read_buffer.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        sample_peak = hop_peak.max(sample_peak * SAMPLE_PEAK_DECAY);
+        let clipped = hop_peak >= CLIP_LEVEL;
+        if rms < SILENCE_RMS {
+            silent_hops = silent_hops.saturating_add(1);
+        } else {
+            silent_hops = 0;
+        }
+
+        if silent_hops >= SILENCE_HOPS {
+            // Idle: publish empty bands so the display decays to zero, then
+            // sleep and drop whatever audio was buffered in the meantime
+            for i in 0..NUM_BANDS {
+                peak_values_left[i] *= peak_decay;
+                peak_values_right[i] *= peak_decay;
+            }
+            if let Ok(mut d) = data.lock() {
+                d.bands = [0.0; NUM_BANDS];
+                d.peaks = peak_values_left;
+                d.bands_right = [0.0; NUM_BANDS];
+                d.peaks_right = peak_values_right;
+                d.sample_peak = sample_peak;
+            }
+            thread::sleep(IDLE_SLEEP);
+            let _ = simple.flush();
+            continue;
+        }
+
+        let stereo = stereo_flag.lock().map(|s| *s).unwrap_or(true);
+        let new_scale = scale_flag.lock().map(|s| *s).unwrap_or_default();
+        if new_scale != scale {
+            scale = new_scale;
+            a_weights = a_weighting_table(scale);
+        }
+        let weights = if weighting_flag.lock().map(|w| *w).unwrap_or(false) {
+            &a_weights
+        } else {
+            &FLAT_WEIGHTS
+        };
+
+        // Deinterleave stereo samples and add to ring buffers; in mono mode the
+        // left ring holds the L+R sum and the right ring is left untouched
         for i in 0..HOP_SIZE {
             let left = read_buffer[i * 2];
             let right = read_buffer[i * 2 + 1];
 
-            ring_buffer_left[ring_pos] = left;
-            ring_buffer_right[ring_pos] = right;
+            if stereo {
+                ring_buffer_left[ring_pos] = left;
+                ring_buffer_right[ring_pos] = right;
+            } else {
+                ring_buffer_left[ring_pos] = (left + right) * 0.5;
+            }
             ring_pos = (ring_pos + 1) % FFT_SIZE;
         }
 
-        // Calculate bands for both channels
-        let bands_left = calculate_bands_from_ring(&ring_buffer_left, ring_pos);
-        let bands_right = calculate_bands_from_ring(&ring_buffer_right, ring_pos);
+        // Calculate bands for both channels (a single FFT when mono)
+        let bands_left =
+            calculate_bands_from_ring(&ring_buffer_left, ring_pos, sample_rate, weights, scale);
+        let bands_right = if stereo {
+            calculate_bands_from_ring(&ring_buffer_right, ring_pos, sample_rate, weights, scale)
+        } else {
+            bands_left
+        };
 
         // Update peaks for both channels
         let mut peaks_left = [0.0f32; NUM_BANDS];
@@ -248,28 +456,50 @@ fn run_analyzer(data: Arc<Mutex<SpectrumData>>, stop_flag: Arc<Mutex<bool>>, sou
             d.peaks = peaks_left;
             d.bands_right = bands_right;
             d.peaks_right = peaks_right;
+            d.freq_scale = scale;
+            d.sample_peak = sample_peak;
+            d.clip_count += u64::from(clipped);
         }
     }
 }
 
+/// No per-band weighting (dB offsets)
+const FLAT_WEIGHTS: [f32; NUM_BANDS] = [0.0; NUM_BANDS];
+
+/// A-weighting gain in dB at a frequency (IEC 61672, 0 dB at 1 kHz)
+fn a_weighting_db(freq: f32) -> f32 {
+    let f2 = freq * freq;
+    let ra = (12194.0f32.powi(2) * f2 * f2)
+        / ((f2 + 20.6f32.powi(2))
+            * ((f2 + 107.7f32.powi(2)) * (f2 + 737.9f32.powi(2))).sqrt()
+            * (f2 + 12194.0f32.powi(2)));
+    20.0 * ra.log10() + 2.0
+}
+
+/// A-weighting offsets at each band's center frequency
+fn a_weighting_table(scale: FreqScale) -> [f32; NUM_BANDS] {
+    std::array::from_fn(|band| a_weighting_db(get_band_frequency(band, scale)))
+}
+
 /// Calculate frequency band magnitudes from FFT output
-fn calculate_bands(fft_output: &[Complex<f32>]) -> [f32; NUM_BANDS] {
+fn calculate_bands(
+    fft_output: &[Complex<f32>],
+    sample_rate: u32,
+    weights: &[f32; NUM_BANDS],
+    scale: FreqScale,
+) -> [f32; NUM_BANDS] {
     let mut bands = [0.0f32; NUM_BANDS];
 
-    // Frequency range: 20Hz to 20kHz (log scale)
-    let min_freq = 20.0f32;
-    let max_freq = 20000.0f32;
-
-    let bin_size = SAMPLE_RATE as f32 / FFT_SIZE as f32;
+    let bin_size = sample_rate as f32 / FFT_SIZE as f32;
     let useful_bins = FFT_SIZE / 2;
 
     for band in 0..NUM_BANDS {
-        // Calculate frequency range for this band (logarithmic)
+        // Calculate frequency range for this band
         let t0 = band as f32 / NUM_BANDS as f32;
         let t1 = (band + 1) as f32 / NUM_BANDS as f32;
 
-        let freq_low = min_freq * (max_freq / min_freq).powf(t0);
-        let freq_high = min_freq * (max_freq / min_freq).powf(t1);
+        let freq_low = scale.frequency_at(t0);
+        let freq_high = scale.frequency_at(t1);
 
         // Convert to bin indices
         let bin_low = ((freq_low / bin_size) as usize).min(useful_bins - 1);
@@ -292,9 +522,9 @@ fn calculate_bands(fft_output: &[Complex<f32>]) -> [f32; NUM_BANDS] {
         if count > 0 {
             let avg = sum / count as f32;
             // Convert to dB-like scale and normalize
-            let db = 20.0 * (avg + 1e-10).log10();
-            // Normalize to 0-1 range (assuming -60dB to 0dB range)
-            bands[band] = ((db + 60.0) / 60.0).clamp(0.0, 1.0);
+            let db = 20.0 * (avg + 1e-10).log10() + weights[band];
+            // Normalize to 0-1 over the DB_FLOOR..DB_CEILING window
+            bands[band] = ((db - DB_FLOOR) / (DB_CEILING - DB_FLOOR)).clamp(0.0, 1.0);
         }
     }
 
@@ -302,7 +532,13 @@ fn calculate_bands(fft_output: &[Complex<f32>]) -> [f32; NUM_BANDS] {
 }
 
 /// Calculate bands from a ring buffer (used for both left and right channels)
-fn calculate_bands_from_ring(ring_buffer: &[f32], ring_pos: usize) -> [f32; NUM_BANDS] {
+fn calculate_bands_from_ring(
+    ring_buffer: &[f32],
+    ring_pos: usize,
+    sample_rate: u32,
+    weights: &[f32; NUM_BANDS],
+    scale: FreqScale,
+) -> [f32; NUM_BANDS] {
     let mut planner: FftPlanner<f32> = FftPlanner::new();
     let fft = planner.plan_fft_forward(FFT_SIZE);
     let mut fft_buffer: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); FFT_SIZE];
@@ -324,15 +560,13 @@ fn calculate_bands_from_ring(ring_buffer: &[f32], ring_pos: usize) -> [f32; NUM_
     fft.process(&mut fft_buffer);
 
     // Calculate band magnitudes
-    calculate_bands(&fft_buffer)
+    calculate_bands(&fft_buffer, sample_rate, weights, scale)
 }
 
-/// Get frequency in Hz for a band index
-pub fn get_band_frequency(band: usize) -> f32 {
-    let min_freq = 20.0f32;
-    let max_freq = 20000.0f32;
+/// Get the center frequency in Hz for a band index
+pub fn get_band_frequency(band: usize, scale: FreqScale) -> f32 {
     let t = (band as f32 + 0.5) / NUM_BANDS as f32;
-    min_freq * (max_freq / min_freq).powf(t)
+    scale.frequency_at(t)
 }
 
 /// Convert frequency to musical note name
@@ -1,19 +1,77 @@
 use egui::*;
 
+use crate::config::{mapping_cc, MappingConfig, MuteButtonConfig, ThemeConfig};
+use crate::engine::FaderSettings;
+use crate::log_file::LogFile;
+use crate::midi::MidiMessage;
 pub use crate::panels::theme;
 use crate::panels::VisualizerState;
-pub use crate::panels::{render_console_tab, render_faders_tab, render_settings_tab};
+pub use crate::panels::{
+    render_console_tab, render_faders_tab, render_mini_faders, render_settings_tab,
+    render_spectrum_tab,
+};
+use crate::pipewire_control::{ImportCandidate, ServerInfo};
 use crate::spectrum::SpectrumData;
 
+/// A received MIDI message for the console's MIDI table
+pub struct MidiEvent {
+    pub message: MidiMessage,
+    pub received: std::time::Instant, // When the listener read it, for exact gaps
+    pub time: chrono::DateTime<chrono::Local>, // Wall-clock time of `received`
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     Control,
+    Spectrum,
     Console,
     Settings,
 }
 
+/// One entry of the Settings import list
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    pub candidate: ImportCandidate,
+    pub selected: bool,
+    pub cc: String, // As typed, e.g. "3" or "b3"
+}
+
+/// Action picked from a fader's right-click menu that the app carries out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaderAction {
+    ToggleMute,
+    Solo,
+    Remove,
+}
+
+impl Tab {
+    /// Name stored as `last_tab` in the config
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tab::Control => "control",
+            Tab::Spectrum => "spectrum",
+            Tab::Console => "console",
+            Tab::Settings => "settings",
+        }
+    }
+
+    pub fn parse(name: &str) -> Self {
+        match name.trim().to_lowercase().as_str() {
+            "spectrum" => Tab::Spectrum,
+            "console" => Tab::Console,
+            "settings" => Tab::Settings,
+            _ => Tab::Control,
+        }
+    }
+}
+
 pub struct UiState {
     pub selected_tab: Tab,
+    pub mini_mode: bool,       // Compact mixer view with thin bars and no chrome
+    pub fader_filter: String,  // Control tab only shows faders whose label contains this
+    pub sinks_collapsed: bool, // Control tab section states
+    pub apps_collapsed: bool,
+    pub active_bank: Option<char>, // 'A' or 'B' when a bank button is configured
     pub system_fader_values: Vec<u8>,
     pub system_fader_labels: Vec<(u8, String)>, // (CC number, label)
     pub system_muted: Vec<bool>,                // Track mute state for each system fader
@@ -22,31 +80,63 @@ pub struct UiState {
     pub app_fader_values: Vec<u8>,
     pub app_fader_labels: Vec<(u8, String)>, // (CC number, app name)
     pub app_muted: Vec<bool>,                // Track mute state for each app fader
-    pub app_muted_volume: Vec<u8>,           // Store previous volume when muted
     pub app_available: Vec<bool>,            // Track if app is currently available
-    pub app_input_count: Vec<usize>,         // Number of matching sink inputs per app fader
+    pub fader_settings: std::collections::HashMap<u8, FaderSettings>, // Per-CC range and curve
+    pub fader_colors: std::collections::HashMap<u8, Color32>, // Per-CC accent from `color`
+    pub fader_moved_at: std::collections::HashMap<u8, std::time::Instant>, // Last hardware move per CC
+    pub fader_confirmed_at: std::collections::HashMap<u8, std::time::Instant>, // Last applied volume per CC
+    pub raw_midi_values: std::collections::HashMap<u8, u8>, // Last value received per CC
+    pub applied_percents: std::collections::HashMap<u8, u8>, // Last volume sent per CC, for the debug overlay
+    pub volume_smoothing: std::collections::HashMap<u8, f32>, // Smoothed external volume reads per CC
+    pub app_input_count: Vec<usize>, // Number of matching sink inputs per app fader
     pub console_output: Vec<(String, chrono::DateTime<chrono::Local>)>,
     pub max_console_lines: usize, // Max number of console messages to keep
+    pub console_auto_scroll: bool, // Follow new console messages; off keeps the scroll position
+    pub console_midi_table: bool, // Console tab shows received MIDI as a table instead of the log
+    pub midi_events: std::collections::VecDeque<MidiEvent>, // Received MIDI while the table is shown, up to max_console_lines
+    pub midi_console_run: Option<(u8, u32)>, // CC and repeat count of the last console line, if it is a MIDI line
+    pub log_file: Option<LogFile>,           // Copy of the console, when [logging] log_file is set
     // Tray settings
     pub enable_tray: bool,
     pub close_to_tray: bool,
     pub start_minimized: bool,
     pub config_path: String,
+    pub config_error: Option<String>, // Why the config file failed to load, shown on the Control tab
 
     // Editable config fields - Audio
     pub cfg_use_pipewire: bool,
     pub cfg_default_sink: String,
     pub cfg_volume_control_mode: String,
     pub cfg_volume_curve: String,
+    pub cfg_volume_curve_exponent: f32,
+    pub cfg_volume_read_mode: String,
     pub cfg_debounce_ms: u32,
     pub cfg_applications_sink_search: u64,
+    pub cfg_mute_hold_ms: u32,
+    pub cfg_mute_fade_ms: u32,
+    pub cfg_focus_fader_cc: Option<u8>,
+    pub cfg_duck_button_cc: Option<u8>,
+    pub cfg_duck_keep_cc: Option<u8>,
+    pub cfg_duck_level: u8,
+    pub cfg_selector_cc: Option<u8>,
+    pub cfg_selector_fader_cc: Option<u8>,
+    pub cfg_selector_targets: String, // Comma-separated app names
 
     // Editable config fields - UI
     pub cfg_window_width: u32,
     pub cfg_window_height: u32,
     pub cfg_theme: String,
+    pub cfg_theme_colors: ThemeConfig, // [theme] section, kept as written so saving preserves it
+    pub palette: theme::Palette,       // Parsed [theme] colors
     pub cfg_show_console: bool,
     pub cfg_max_console_lines: usize,
+    pub cfg_hide_unavailable: bool,
+    pub cfg_decimal_percent: bool,
+    pub cfg_color_by_level: bool,
+    pub cfg_show_midi_debug: bool,
+    pub cfg_confirm_flash: bool,
+    pub cfg_max_idle_fps: u32,
+    pub cfg_max_fps: u32, // 0 = unlimited
 
     // Editable config fields - Logging
     pub cfg_logging_enabled: bool,
@@ -54,11 +144,37 @@ pub struct UiState {
     pub cfg_timestamps: bool,
     pub cfg_log_fader_events: bool,
     pub cfg_log_device_info: bool,
+    pub cfg_log_latency: bool,
+    pub cfg_log_file: String, // Empty = no log file
+    pub cfg_control_socket: bool,
+    pub cfg_control_socket_path: Option<String>, // Not editable in the UI, kept for saving
 
     // Editable config fields - MIDI Controls (as strings for editing)
-    pub cfg_sinks: Vec<(u8, String)>, // (CC number, sink name)
-    pub cfg_applications: Vec<(u8, String)>, // (CC number, app name)
-    pub cfg_mute_buttons: Vec<(u8, u8)>, // (button CC, fader CC)
+    pub cfg_sinks: Vec<(u8, MappingConfig)>, // (CC number, sink target + label)
+    pub cfg_applications: Vec<(u8, MappingConfig)>, // (CC number, app target + label)
+    pub cfg_mute_buttons: Vec<(u8, MuteButtonConfig)>, // (button CC, fader CC + behavior)
+    pub mute_button_warnings: Vec<String>,   // Mute buttons of the loaded config without a fader
+    pub cfg_solo_buttons: Vec<(u8, u8)>,     // (button CC, app fader CC)
+    pub cfg_midi_port: Option<String>,       // Chosen device port, None = first match
+    pub cfg_midi_port_index: usize,          // Among ports sharing that name
+    pub cfg_midi_thru: bool,                 // Forward unmapped MIDI to a virtual port
+    pub cfg_led_feedback: bool,              // Light mute button LEDs
+    pub cfg_led_inverted: bool,              // Light LEDs for unmuted faders instead
+    pub cfg_startup_ignore_ms: u32,          // Discard MIDI right after connecting
+    pub cfg_zero_guard_ms: u32,              // Ignore zeros without prior fader motion
+    pub cfg_bank_button_cc: Option<u8>,      // Switches faders between bank A and B
+    pub midi_ports: Vec<String>,             // Matching device ports found at startup
+    pub identify_sink: Option<String>,       // Sink target to play a test tone on
+    pub server_info: Option<Result<ServerInfo, String>>, // From `pactl info`, None until queried
+    pub refresh_server_info: bool,           // Settings asked to query it again
+    pub import_requested: bool,              // Settings asked for sinks and apps to import
+    pub import_rows: Option<Vec<ImportRow>>, // Import list while it is open
+    pub import_filter: String,               // Import list only shows entries containing this
+    pub reset_requested: bool,               // Tab bar Reset button was clicked
+    pub fader_action: Option<(u8, FaderAction)>, // Fader context menu choice, by fader CC
+    pub edit_mapping_cc: Option<u8>,         // Settings scrolls to and focuses this CC's mapping
+    pub remove_mapping_cc: Option<u8>,       // Mapping waiting for the Remove confirmation
+    pub fader_animating: bool,               // Control tab drew a fading highlight or flash
 
     // Settings UI state
     pub settings_dirty: bool,
@@ -69,6 +185,8 @@ pub struct UiState {
     pub new_app_name: String,
     pub new_mute_button_cc: String,
     pub new_mute_fader_cc: String,
+    pub new_solo_button_cc: String,
+    pub new_solo_fader_cc: String,
     pub window_width_str: String,
     pub window_height_str: String,
 
@@ -96,6 +214,13 @@ pub struct UiState {
     pub cfg_spectrum_show_waterfall: bool,
     pub cfg_spectrum_show_labels: bool,
     pub cfg_spectrum_sink_name: String, // Name of the sink to monitor
+    pub cfg_spectrum_cycle_cc: Option<u8>, // Button that steps through the sources
+    pub cfg_window_toggle_cc: Option<u8>, // Button that minimizes and restores the window
+    pub cfg_spectrum_weighting: String, // "none" or "a"
+    pub cfg_spectrum_freq_scale: String, // "log" or "linear"
+    pub cfg_spectrum_smoothing: f32,    // Bar attack speed
+    pub cfg_spectrum_peak_decay_ui: f32, // Peak marker fall speed
+    pub cfg_spectrum_scale: f32,        // Size of the spectrum's hand-painted details
 }
 
 impl UiState {
@@ -114,7 +239,19 @@ impl UiState {
         let app_count = app_labels.len();
 
         Self {
-            selected_tab: Tab::Control,
+            selected_tab: Tab::parse(config.ui.last_tab.as_deref().unwrap_or("control")),
+            mini_mode: false,
+            fader_filter: String::new(),
+            sinks_collapsed: false,
+            apps_collapsed: false,
+            active_bank: None,
+            fader_settings: config.get_fader_settings(),
+            fader_colors: config.get_fader_colors().0,
+            fader_moved_at: std::collections::HashMap::new(),
+            fader_confirmed_at: std::collections::HashMap::new(),
+            raw_midi_values: std::collections::HashMap::new(),
+            applied_percents: std::collections::HashMap::new(),
+            volume_smoothing: std::collections::HashMap::new(),
             system_fader_values: vec![0; system_count],
             system_fader_labels: system_labels,
             system_muted: vec![false; system_count],
@@ -123,15 +260,20 @@ impl UiState {
             app_fader_values: vec![0; app_count],
             app_fader_labels: app_labels,
             app_muted: vec![false; app_count],
-            app_muted_volume: vec![0; app_count],
             app_available: vec![true; app_count],
             app_input_count: vec![0; app_count],
             console_output: Vec::new(),
+            midi_console_run: None,
+            log_file: None,
             max_console_lines,
+            console_auto_scroll: true,
+            console_midi_table: false,
+            midi_events: std::collections::VecDeque::new(),
             enable_tray,
             close_to_tray,
             start_minimized,
             config_path,
+            config_error: None,
             cfg_use_pipewire: config.audio.use_pipewire.unwrap_or(true),
             cfg_default_sink: config.audio.default_sink.clone().unwrap_or_default(),
             cfg_volume_control_mode: config
@@ -144,8 +286,28 @@ impl UiState {
                 .volume_curve
                 .clone()
                 .unwrap_or_else(|| "linear".to_string()),
+            cfg_volume_curve_exponent: config.get_curve_exponent(),
+            cfg_volume_read_mode: config
+                .audio
+                .volume_read_mode
+                .clone()
+                .unwrap_or_else(|| "percent".to_string()),
             cfg_debounce_ms: config.audio.debounce_ms.unwrap_or(100),
             cfg_applications_sink_search: config.audio.applications_sink_search.unwrap_or(10),
+            cfg_mute_hold_ms: config.audio.mute_hold_ms.unwrap_or(500),
+            cfg_mute_fade_ms: config.audio.mute_fade_ms.unwrap_or(1500),
+            cfg_focus_fader_cc: config.audio.focus_fader_cc,
+            cfg_duck_button_cc: config.audio.duck_button_cc,
+            cfg_duck_keep_cc: config.audio.duck_keep_cc,
+            cfg_duck_level: config.audio.duck_level.unwrap_or(20),
+            cfg_selector_cc: config.audio.selector_cc,
+            cfg_selector_fader_cc: config.audio.selector_fader_cc,
+            cfg_selector_targets: config
+                .audio
+                .selector_targets
+                .as_deref()
+                .unwrap_or_default()
+                .join(", "),
             cfg_window_width: config.ui.window_width.unwrap_or(1200),
             cfg_window_height: config.ui.window_height.unwrap_or(1000),
             window_width_str: config.ui.window_width.unwrap_or(1200).to_string(),
@@ -155,8 +317,17 @@ impl UiState {
                 .theme
                 .clone()
                 .unwrap_or_else(|| "default".to_string()),
+            cfg_theme_colors: config.theme.clone(),
+            palette: config.get_palette().0,
             cfg_show_console: config.ui.show_console.unwrap_or(false),
             cfg_max_console_lines: config.ui.max_console_lines.unwrap_or(1000),
+            cfg_hide_unavailable: config.ui.hide_unavailable.unwrap_or(false),
+            cfg_decimal_percent: config.ui.decimal_percent.unwrap_or(false),
+            cfg_color_by_level: config.ui.color_by_level.unwrap_or(false),
+            cfg_show_midi_debug: config.ui.show_midi_debug.unwrap_or(false),
+            cfg_confirm_flash: config.ui.confirm_flash.unwrap_or(false),
+            cfg_max_idle_fps: config.ui.max_idle_fps.unwrap_or(10),
+            cfg_max_fps: config.ui.max_fps.unwrap_or(0),
             cfg_logging_enabled: config.logging.enabled.unwrap_or(true),
             cfg_log_level: config
                 .logging
@@ -166,9 +337,40 @@ impl UiState {
             cfg_timestamps: config.logging.timestamps.unwrap_or(true),
             cfg_log_fader_events: config.logging.log_fader_events.unwrap_or(false),
             cfg_log_device_info: config.logging.log_device_info.unwrap_or(false),
+            cfg_log_latency: config.logging.log_latency.unwrap_or(false),
+            cfg_log_file: config.logging.log_file.clone().unwrap_or_default(),
+            cfg_control_socket: config.control.enabled.unwrap_or(false),
+            cfg_control_socket_path: config.control.socket_path.clone(),
             cfg_sinks: convert_hashmap_to_cc_vec(&config.midi_controls.sinks),
             cfg_applications: convert_hashmap_to_cc_vec(&config.midi_controls.applications),
             cfg_mute_buttons: convert_mute_buttons_hashmap(&config.midi_controls.mute_buttons),
+            mute_button_warnings: config.get_mute_button_warnings(),
+            cfg_solo_buttons: {
+                let mut buttons: Vec<(u8, u8)> =
+                    config.get_solo_button_mappings().into_iter().collect();
+                buttons.sort();
+                buttons
+            },
+            cfg_midi_port: config.midi.port.clone(),
+            cfg_midi_port_index: config.midi.port_index.unwrap_or(0),
+            cfg_midi_thru: config.midi.midi_thru.unwrap_or(false),
+            cfg_led_feedback: config.midi.led_feedback.unwrap_or(true),
+            cfg_led_inverted: config.midi.led_inverted.unwrap_or(false),
+            cfg_startup_ignore_ms: config.midi.startup_ignore_ms.unwrap_or(0),
+            cfg_zero_guard_ms: config.midi.zero_guard_ms.unwrap_or(0),
+            cfg_bank_button_cc: config.midi.bank_button_cc,
+            midi_ports: Vec::new(),
+            identify_sink: None,
+            server_info: None,
+            refresh_server_info: false,
+            reset_requested: false,
+            import_requested: false,
+            import_rows: None,
+            import_filter: String::new(),
+            fader_action: None,
+            edit_mapping_cc: None,
+            remove_mapping_cc: None,
+            fader_animating: false,
             settings_dirty: false,
             settings_save_message: None,
             new_sink_cc: String::new(),
@@ -177,6 +379,8 @@ impl UiState {
             new_app_name: String::new(),
             new_mute_button_cc: String::new(),
             new_mute_fader_cc: String::new(),
+            new_solo_button_cc: String::new(),
+            new_solo_fader_cc: String::new(),
             settings_category: 0,
             show_midi_ui_modal: false,
             midi_ui_texture: None,
@@ -196,23 +400,112 @@ impl UiState {
                 .spectrum_sink_name
                 .clone()
                 .unwrap_or_else(|| "master_sink".to_string()),
+            cfg_spectrum_cycle_cc: config.ui.spectrum_cycle_cc,
+            cfg_window_toggle_cc: config.ui.window_toggle_cc,
+            cfg_spectrum_weighting: config
+                .ui
+                .spectrum_weighting
+                .clone()
+                .unwrap_or_else(|| "none".to_string()),
+            cfg_spectrum_freq_scale: config
+                .ui
+                .spectrum_freq_scale
+                .clone()
+                .unwrap_or_else(|| "log".to_string()),
+            cfg_spectrum_smoothing: config
+                .ui
+                .spectrum_smoothing
+                .unwrap_or(crate::panels::visualizer::DEFAULT_ATTACK_SPEED),
+            cfg_spectrum_peak_decay_ui: config
+                .ui
+                .spectrum_peak_decay_ui
+                .unwrap_or(crate::panels::visualizer::DEFAULT_PEAK_DECAY),
+            cfg_spectrum_scale: config
+                .ui
+                .spectrum_scale
+                .filter(|scale| scale.is_finite())
+                .unwrap_or(1.0)
+                .clamp(
+                    *crate::panels::visualizer::SCALE_RANGE.start(),
+                    *crate::panels::visualizer::SCALE_RANGE.end(),
+                ),
         }
     }
 
     pub fn add_console_message(&mut self, msg: String) {
+        if let Some(ref log_file) = self.log_file {
+            // A collapsed MIDI line goes to the file once, in its final form
+            if let (Some((_, 2..)), Some((last, time))) =
+                (self.midi_console_run, self.console_output.last())
+            {
+                log_file.write(*time, last);
+            }
+        }
         if self.console_output.len() >= self.max_console_lines {
             self.console_output.remove(0);
         }
-        self.console_output.push((msg, chrono::Local::now()));
+        let now = chrono::Local::now();
+        if let Some(ref log_file) = self.log_file {
+            log_file.write(now, &msg);
+        }
+        self.console_output.push((msg, now));
+        self.midi_console_run = None;
+    }
+
+    /// Keep a received message for the console's MIDI table
+    pub fn add_midi_event(&mut self, message: MidiMessage, received: std::time::Instant) {
+        let age = chrono::Duration::from_std(received.elapsed()).unwrap_or_default();
+        self.midi_events.push_back(MidiEvent {
+            message,
+            received,
+            time: chrono::Local::now() - age,
+        });
+        self.trim_console();
     }
 
-    pub fn apply_dark_theme(ctx: &Context) {
+    /// Change how many console lines and MIDI table rows are kept, dropping the oldest
+    pub fn set_max_console_lines(&mut self, max_console_lines: usize) {
+        self.max_console_lines = max_console_lines;
+        self.trim_console();
+    }
+
+    fn trim_console(&mut self) {
+        let excess = self
+            .console_output
+            .len()
+            .saturating_sub(self.max_console_lines);
+        self.console_output.drain(..excess);
+        let excess = self
+            .midi_events
+            .len()
+            .saturating_sub(self.max_console_lines);
+        self.midi_events.drain(..excess);
+    }
+
+    /// Log a received CC. Consecutive messages from the same CC update one line
+    /// with a repeat count, so a fader sweep doesn't flood the console
+    pub fn add_midi_console_message(&mut self, cc: u8, value: u8) {
+        let repeat = match self.midi_console_run {
+            Some((run_cc, count)) if run_cc == cc => count + 1,
+            _ => 1,
+        };
+        let msg = format!("MIDI CC{} -> value: {}", cc, value);
+        match self.console_output.last_mut() {
+            Some(last) if repeat > 1 => {
+                *last = (format!("{} (x{})", msg, repeat), chrono::Local::now());
+            }
+            _ => self.add_console_message(msg),
+        }
+        self.midi_console_run = Some((cc, repeat));
+    }
+
+    pub fn apply_dark_theme(ctx: &Context, palette: &theme::Palette) {
         let mut visuals = Visuals::dark();
-        visuals.override_text_color = Some(theme::TEXT_PRIMARY);
+        visuals.override_text_color = Some(palette.text);
 
         // Panel backgrounds
-        visuals.panel_fill = theme::BG_PRIMARY;
-        visuals.window_fill = theme::BG_PRIMARY;
+        visuals.panel_fill = palette.bg;
+        visuals.window_fill = palette.bg;
 
         // Button styling
         visuals.widgets.inactive.bg_fill = theme::BG_SECONDARY;
@@ -220,14 +513,14 @@ impl UiState {
         visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, theme::TEXT_SECONDARY);
 
         visuals.widgets.hovered.bg_fill = theme::BG_TERTIARY;
-        visuals.widgets.hovered.bg_stroke = Stroke::new(1.0, theme::ACCENT_BLUE);
+        visuals.widgets.hovered.bg_stroke = Stroke::new(1.0, palette.accent);
 
-        visuals.widgets.active.bg_fill = theme::ACCENT_BLUE;
+        visuals.widgets.active.bg_fill = palette.accent;
         visuals.widgets.active.fg_stroke = Stroke::new(1.5, Color32::WHITE);
 
         // Selection
-        visuals.selection.bg_fill = theme::ACCENT_BLUE;
-        visuals.selection.stroke = Stroke::new(1.0, theme::ACCENT_BLUE);
+        visuals.selection.bg_fill = palette.accent;
+        visuals.selection.stroke = Stroke::new(1.0, palette.accent);
 
         // Borders
         visuals.window_stroke = Stroke::new(1.0, theme::BORDER);
@@ -236,7 +529,7 @@ impl UiState {
     }
 
     pub fn render_tabs(&mut self, ctx: &Context) {
-        Self::apply_dark_theme(ctx);
+        Self::apply_dark_theme(ctx, &self.palette);
 
         TopBottomPanel::top("tab_panel")
             .frame(
@@ -249,40 +542,87 @@ impl UiState {
                     ui.style_mut().spacing.item_spacing = vec2(12.0, 0.0);
                     ui.add_space(8.0);
 
-                    // Control tab
-                    if ui
-                        .selectable_label(
-                            self.selected_tab == Tab::Control,
-                            RichText::new("🔈 Control").size(14.0),
-                        )
-                        .clicked()
-                    {
-                        self.selected_tab = Tab::Control;
-                    }
-
-                    // Console tab
-                    if ui
-                        .selectable_label(
-                            self.selected_tab == Tab::Console,
-                            RichText::new("📋 Console").size(14.0),
-                        )
-                        .clicked()
-                    {
-                        self.selected_tab = Tab::Console;
-                    }
-
-                    // Settings tab
-                    if ui
-                        .selectable_label(
+                    // Tabs are hidden in mini mode, leaving only the toggle
+                    if !self.mini_mode {
+                        // Control tab
+                        if ui
+                            .selectable_label(
+                                self.selected_tab == Tab::Control,
+                                RichText::new("🔈 Control").size(14.0),
+                            )
+                            .clicked()
+                        {
+                            self.selected_tab = Tab::Control;
+                        }
+
+                        // Spectrum tab
+                        if ui
+                            .selectable_label(
+                                self.selected_tab == Tab::Spectrum,
+                                RichText::new("📊 Spectrum").size(14.0),
+                            )
+                            .clicked()
+                        {
+                            self.selected_tab = Tab::Spectrum;
+                        }
+
+                        // Console tab
+                        if ui
+                            .selectable_label(
+                                self.selected_tab == Tab::Console,
+                                RichText::new("📋 Console").size(14.0),
+                            )
+                            .clicked()
+                        {
+                            self.selected_tab = Tab::Console;
+                        }
+
+                        // Settings tab, with a dot while changes are unsaved
+                        let settings_label = if self.settings_dirty {
+                            "⚙ Settings •"
+                        } else {
+                            "⚙ Settings"
+                        };
+                        let settings_tab = ui.selectable_label(
                             self.selected_tab == Tab::Settings,
-                            RichText::new("⚙ Settings").size(14.0),
-                        )
-                        .clicked()
-                    {
-                        self.selected_tab = Tab::Settings;
+                            RichText::new(settings_label).size(14.0),
+                        );
+                        let settings_tab = if self.settings_dirty {
+                            settings_tab.on_hover_text("Unsaved changes")
+                        } else {
+                            settings_tab
+                        };
+                        if settings_tab.clicked() {
+                            self.selected_tab = Tab::Settings;
+                        }
                     }
 
-                    ui.add_space(8.0);
+                    // Mini mode toggle
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        ui.add_space(8.0);
+                        let label = if self.mini_mode {
+                            "🗖 Full"
+                        } else {
+                            "🗕 Mini"
+                        };
+                        if ui
+                            .selectable_label(self.mini_mode, RichText::new(label).size(14.0))
+                            .on_hover_text("Toggle the compact mixer view")
+                            .clicked()
+                        {
+                            self.mini_mode = !self.mini_mode;
+                        }
+
+                        if ui
+                            .button(RichText::new("🔄 Reset").size(14.0))
+                            .on_hover_text(
+                                "Reconnect the MIDI device and re-read all volumes from PipeWire",
+                            )
+                            .clicked()
+                        {
+                            self.reset_requested = true;
+                        }
+                    });
                 });
             });
     }
@@ -291,35 +631,67 @@ impl UiState {
         render_faders_tab(self, ctx)
     }
 
+    pub fn render_spectrum_tab(&mut self, ctx: &Context) {
+        render_spectrum_tab(self, ctx);
+    }
+
+    pub fn render_mini_tab(&mut self, ctx: &Context) -> Vec<(bool, usize, u8)> {
+        render_mini_faders(self, ctx)
+    }
+
+    /// Sinks the spectrum analyzer can monitor as (target, label), default output first
+    pub fn spectrum_sources(&self) -> Vec<(String, String)> {
+        let mut sources = vec![("master_sink".to_string(), "Default output".to_string())];
+        sources.extend(
+            self.cfg_sinks
+                .iter()
+                .map(|(_, mapping)| (mapping.resolved_target(), mapping.display_label()))
+                .filter(|(target, _)| target != "master_sink"),
+        );
+        sources
+    }
+
+    /// Window size that fits the visible faders in mini mode
+    pub fn mini_window_size(&self) -> Vec2 {
+        let visible_sinks = self.sink_visibility.iter().filter(|&&v| v).count();
+        let visible_apps = self.app_visibility.iter().filter(|&&v| v).count();
+        let rows = (visible_sinks + visible_apps).max(1);
+        vec2(300.0, 44.0 + rows as f32 * 17.0)
+    }
+
     pub fn render_console_tab(&mut self, ctx: &Context) {
-        render_console_tab(&self.console_output, ctx);
+        render_console_tab(
+            &self.console_output,
+            &self.midi_events,
+            &mut self.console_auto_scroll,
+            &mut self.console_midi_table,
+            &self.palette,
+            ctx,
+        );
     }
 
-    pub fn render_settings_tab(&mut self, ctx: &Context, _tray_functional: bool) -> bool {
-        render_settings_tab(self, ctx, false)
+    pub fn render_settings_tab(&mut self, ctx: &Context, tray_functional: bool) -> bool {
+        render_settings_tab(self, ctx, tray_functional)
     }
 }
 
-// Helper function to convert HashMap<String, String> (with CC keys like "cc_0") to Vec<(u8, String)>
-fn convert_hashmap_to_cc_vec(map: &std::collections::HashMap<String, String>) -> Vec<(u8, String)> {
-    let mut result: Vec<(u8, String)> = map
+// Helper function to convert HashMap<String, String> (with CC keys like "cc_0" or "b_cc_0") to Vec<(u8, String)>
+fn convert_hashmap_to_cc_vec(
+    map: &std::collections::HashMap<String, MappingConfig>,
+) -> Vec<(u8, MappingConfig)> {
+    let mut result: Vec<(u8, MappingConfig)> = map
         .iter()
-        .filter_map(|(k, v)| {
-            if let Some(cc_str) = k.strip_prefix("cc_") {
-                if let Ok(cc) = cc_str.parse::<u8>() {
-                    return Some((cc, v.clone()));
-                }
-            }
-            None
-        })
+        .filter_map(|(k, v)| Some((mapping_cc(k)?, v.clone())))
         .collect();
     result.sort_by_key(|(cc, _)| *cc);
     result
 }
 
 // Helper function to convert mute buttons HashMap
-fn convert_mute_buttons_hashmap(map: &std::collections::HashMap<String, u8>) -> Vec<(u8, u8)> {
-    let mut result: Vec<(u8, u8)> = map
+fn convert_mute_buttons_hashmap(
+    map: &std::collections::HashMap<String, MuteButtonConfig>,
+) -> Vec<(u8, MuteButtonConfig)> {
+    let mut result: Vec<(u8, MuteButtonConfig)> = map
         .iter()
         .filter_map(|(k, v)| {
             if let Some(cc_str) = k.strip_prefix("cc_") {